@@ -11,10 +11,8 @@ use sandboxdb::storage::schema::Schema;
 use sandboxdb::storage::tablespace::metastore::Metastore;
 
 fn main() {
-    let schema =
-        Schema::from_str("id BIGINT, cost FLOAT, available BOOLEAN, date TIMESTAMP").unwrap();
     let page = Page::build(8192, 0).unwrap();
-    let mut file = File::build(10 * 8192 + 50, 0, [0, 0, 1]);
+    let mut file = File::build(10 * 8192 + 50, 0);
     file.insert_page(&page).unwrap();
 
     use_case_01();
@@ -27,12 +25,12 @@ fn use_case_01() {
     let mut database = metastore.new_database("bronze", None).unwrap();
     let mut table = database.new_table("free", None, &schema).unwrap();
     let (_, path) = table.new_file().unwrap();
-    let mut file = File::build(8192 * 10 + 13, 0, [0, 0, 1]);
+    let mut file = File::build(8192 * 10 + 13, 0);
     let mut page = Page::build(8192, 0).unwrap();
-    let tuple = Tuple::build(&schema, &[0; 4], &[2; 33]).unwrap();
+    let tuple = Tuple::build(&schema, &[0], &[2; 33]).unwrap();
 
     for _ in 0..100 {
-        page.insert(tuple.clone()).unwrap()
+        page.insert(tuple.clone()).unwrap();
     }
 
     file.insert_page(&page).unwrap();
@@ -1,12 +1,21 @@
+use std::borrow::Cow;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::rc::Rc;
 
 use crc32fast::hash;
 
+use crate::storage::buffer::disk_manager::DiskManager;
 use crate::storage::buffer::error::Error;
 use crate::storage::buffer::page_meta::PageMeta;
+use crate::storage::file;
 use crate::storage::file::page::Page;
+use crate::storage::file::rows::{PageVisitor, Rows};
+use crate::storage::file::slot::{RowId, Slot};
+use crate::storage::file::tuple::Tuple;
+use crate::storage::schema::value::Value;
+use crate::storage::schema::Schema;
+use crate::storage::tablespace;
 use crate::storage::tablespace::catalog::{Catalog, CatalogTable};
 
 const BUFFER_LIMIT_USED_SIZE: f32 = 0.95;
@@ -18,9 +27,25 @@ pub struct BufferPool {
     pub catalog: Catalog,
     page_metas: HashMap<u32, PageMeta>,
     page_catalogs: HashMap<u32, Rc<CatalogTable>>,
+    page_locations: HashMap<u32, PageLocation>,
     pages: HashMap<u32, Page>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+struct PageLocation {
+    catalog_id: String,
+    file_id: String,
+    page_id: u32,
+}
+
+/// A file of a table seen through the pool, pages it does not hold are read from disk.
+struct FilePages<'a> {
+    pool: &'a mut BufferPool,
+    catalog_table: Rc<CatalogTable>,
+    catalog_id: String,
+    file_id: String,
+}
+
 impl BufferPool {
     pub fn build(size: usize, metastore_path: &str) -> BufferPool {
         BufferPool {
@@ -28,6 +53,7 @@ impl BufferPool {
             catalog: Catalog::build(metastore_path).unwrap(),
             page_metas: HashMap::new(),
             page_catalogs: HashMap::new(),
+            page_locations: HashMap::new(),
             pages: HashMap::new(),
         }
     }
@@ -51,7 +77,7 @@ impl BufferPool {
         if self.used_space() + page.header.page_size as f32
             > BUFFER_LIMIT_USED_SIZE * self.size as f32
         {
-            self.vacuum();
+            self.vacuum()?;
         }
         self.catalog.refresh()?;
         let page_key = Self::buffer_page_key(catalog_id, file_id, page_id);
@@ -61,9 +87,67 @@ impl BufferPool {
             page_key,
             Rc::clone(self.catalog.tables.get(catalog_id).unwrap()),
         );
+        self.page_locations.insert(
+            page_key,
+            PageLocation {
+                catalog_id: catalog_id.to_string(),
+                file_id: file_id.to_string(),
+                page_id,
+            },
+        );
         Ok(page_key)
     }
 
+    pub fn fetch_page(
+        &mut self,
+        catalog_id: &str,
+        file_id: &str,
+        page_id: u32,
+    ) -> Result<u32, Error> {
+        let catalog_table = self.catalog_table(catalog_id)?;
+        let page = DiskManager::read_page(&catalog_table.table, file_id, page_id)?;
+        self.load_page(page, catalog_id, file_id, page_id)
+    }
+
+    fn catalog_table(&mut self, catalog_id: &str) -> Result<Rc<CatalogTable>, Error> {
+        self.catalog.refresh()?;
+        Ok(Rc::clone(self.catalog.tables.get(catalog_id).ok_or(
+            tablespace::error::Error::ObjectNotFound("Table".to_string(), catalog_id.to_string()),
+        )?))
+    }
+
+    fn page_location(&self, page_key: &u32) -> Result<PageLocation, Error> {
+        self.page_locations
+            .get(page_key)
+            .cloned()
+            .ok_or(Error::UnknownTableKey(*page_key))
+    }
+
+    pub fn flush_page(
+        &mut self,
+        catalog_id: &str,
+        file_id: &str,
+        page_id: u32,
+    ) -> Result<(), Error> {
+        self.write_back(&Self::buffer_page_key(catalog_id, file_id, page_id))
+    }
+
+    fn write_back(&mut self, page_key: &u32) -> Result<(), Error> {
+        let page = self
+            .pages
+            .get(page_key)
+            .ok_or(Error::UnknownTableKey(*page_key))?;
+        let location = &self.page_locations[page_key];
+        DiskManager::write_page(
+            &self.page_catalogs[page_key].table,
+            &location.file_id,
+            location.page_id,
+            page,
+        )?;
+        self.page_metas.get_mut(page_key).unwrap().dirty = false;
+        Ok(())
+    }
+
     fn buffer_page_key(catalog_id: &str, file_id: &str, page_id: u32) -> u32 {
         let mut key = Vec::new();
         key.extend_from_slice(catalog_id.as_bytes());
@@ -77,13 +161,12 @@ impl BufferPool {
             if self.used_space() + page.header.page_size as f32
                 > BUFFER_LIMIT_USED_SIZE * self.size as f32
             {
-                self.vacuum();
+                self.vacuum()?;
             }
             self.pages.insert(*page_key, page);
-            self.page_metas
-                .get_mut(page_key)
-                .unwrap()
-                .increment_access();
+            let page_meta = self.page_metas.get_mut(page_key).unwrap();
+            page_meta.increment_access();
+            page_meta.dirty = true;
             Ok(())
         } else {
             Err(Error::UnknownTableKey(*page_key))
@@ -124,24 +207,130 @@ impl BufferPool {
             .collect()
     }
 
-    pub fn vacuum(&mut self) {
+    pub fn insert_tuple(&mut self, page_key: &u32, tuple: Tuple) -> Result<u32, Error> {
+        self.validate_tuple(page_key, &tuple, None)?;
+        let (mut file_pages, page_id) = self.file_pages(page_key)?;
+        Ok(file_pages.insert_tuple_into(page_id, tuple)?.slot)
+    }
+
+    pub fn read_tuple(&mut self, page_key: &u32, slot: u32) -> Result<Tuple, Error> {
+        let (file_pages, page_id) = self.file_pages(page_key)?;
+        file_pages.read_tuple(RowId::build(page_id, slot), file_pages.schema()?)
+    }
+
+    pub fn update_tuple(&mut self, page_key: &u32, slot: u32, tuple: Tuple) -> Result<(), Error> {
+        self.validate_tuple(page_key, &tuple, Some(slot))?;
+        let (mut file_pages, page_id) = self.file_pages(page_key)?;
+        file_pages.update_tuple(RowId::build(page_id, slot), tuple)
+    }
+
+    pub fn delete_tuple(&mut self, page_key: &u32, slot: u32) -> Result<(), Error> {
+        let (mut file_pages, page_id) = self.file_pages(page_key)?;
+        file_pages.delete_tuple(RowId::build(page_id, slot))
+    }
+
+    fn file_pages(&mut self, page_key: &u32) -> Result<(FilePages<'_>, u32), Error> {
+        let catalog_table = self.get_page_catalog(page_key)?;
+        let location = self.page_locations[page_key].clone();
+        let file_pages = FilePages {
+            pool: self,
+            catalog_table,
+            catalog_id: location.catalog_id,
+            file_id: location.file_id,
+        };
+        Ok((file_pages, location.page_id))
+    }
+
+    fn validate_tuple(
+        &mut self,
+        page_key: &u32,
+        tuple: &Tuple,
+        slot: Option<u32>,
+    ) -> Result<(), Error> {
+        let location = self.page_location(page_key)?;
+        let catalog_table = self.catalog_table(&location.catalog_id)?;
+        let table = &catalog_table.table;
+        // Rows are decoded with the schema of their file, an older file would misread them.
+        if table.file_version(&location.file_id) != table.version() {
+            return Err(Error::OutdatedFile(location.file_id));
+        }
+        let schema = &table.schema;
+        let values = tuple.values(schema)?;
+        schema.check_constraints(&values)?;
+        if !schema.keys.is_empty() {
+            self.check_keys(page_key, &values, slot)?;
+        }
+        Ok(())
+    }
+
+    /// Checks the keys row by row, file by file, and stops at the first conflict.
+    fn check_keys(
+        &mut self,
+        page_key: &u32,
+        values: &[Option<Value>],
+        slot: Option<u32>,
+    ) -> Result<(), Error> {
+        let location = self.page_location(page_key)?;
+        let catalog_table = self.catalog_table(&location.catalog_id)?;
+        // The updated row, and where it lies if it moved.
+        let mut skip: Vec<RowId> = Vec::new();
+        if let Some(slot) = slot {
+            skip.push(RowId::build(location.page_id, slot));
+            if let Slot::Forward(target) = self.get_page(page_key)?.get_slot(slot)? {
+                skip.push(*target);
+            }
+        }
+        // Files on disk and pages only held by the pool, evicted pages are read back from disk.
+        let mut file_ids: BTreeSet<String> =
+            catalog_table.table.file_paths.keys().cloned().collect();
+        file_ids.extend(
+            self.page_locations
+                .values()
+                .filter(|other| other.catalog_id == location.catalog_id)
+                .map(|other| other.file_id.clone()),
+        );
+        let table = &catalog_table.table;
+        for file_id in file_ids {
+            let skip = if file_id == location.file_id {
+                &skip[..]
+            } else {
+                &[]
+            };
+            let version = table.file_version(&file_id);
+            let file_pages = FilePages {
+                pool: self,
+                catalog_table: Rc::clone(&catalog_table),
+                catalog_id: location.catalog_id.clone(),
+                file_id,
+            };
+            let schema = file_pages.schema()?;
+            file_pages.visit_rows(schema, &mut |row, tuple| {
+                if !skip.contains(&row) {
+                    let row_values = table.upgrade(version, tuple.values(schema)?)?;
+                    table.schema.check_keys(values, &row_values)?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn vacuum(&mut self) -> Result<(), Error> {
         let mut size_to_free = self.size as f32 * VACUUM_SIZE;
-        let mut meta_sorted = self.get_page_access_sorted();
-        while size_to_free
-            > self
-                .pages
-                .get(meta_sorted.first().unwrap())
-                .unwrap()
-                .header
-                .page_size as f32
-        {
-            let page_key = meta_sorted.first().unwrap();
-            let page = self.pages.remove(page_key).unwrap();
+        for page_key in self.get_page_access_sorted() {
+            if size_to_free <= self.pages[&page_key].header.page_size as f32 {
+                break;
+            }
+            if self.page_metas[&page_key].dirty {
+                self.write_back(&page_key)?;
+            }
+            let page = self.pages.remove(&page_key).unwrap();
             size_to_free -= page.header.page_size as f32;
-            self.page_catalogs.remove(page_key);
-            self.page_metas.remove(page_key);
-            meta_sorted.remove(0);
+            self.page_catalogs.remove(&page_key);
+            self.page_metas.remove(&page_key);
+            self.page_locations.remove(&page_key);
         }
+        Ok(())
     }
 
     fn get_page_access_sorted(&self) -> Vec<u32> {
@@ -177,6 +366,114 @@ impl BufferPool {
     }
 }
 
+impl FilePages<'_> {
+    fn schema(&self) -> Result<&Schema, Error> {
+        Ok(self.catalog_table.table.file_schema(&self.file_id).ok_or(
+            tablespace::error::Error::ObjectNotFound(
+                "Schema of file".to_string(),
+                self.file_id.to_string(),
+            ),
+        )?)
+    }
+}
+
+impl Rows for FilePages<'_> {
+    type Error = Error;
+
+    fn page(&self, index: u32) -> Result<Cow<'_, Page>, Error> {
+        let page_key = BufferPool::buffer_page_key(&self.catalog_id, &self.file_id, index);
+        match self.pool.pages.get(&page_key) {
+            Some(page) => Ok(Cow::Borrowed(page)),
+            None => Ok(Cow::Owned(DiskManager::read_page(
+                &self.catalog_table.table,
+                &self.file_id,
+                index,
+            )?)),
+        }
+    }
+
+    fn page_mut(&mut self, index: u32) -> Result<&mut Page, Error> {
+        let mut page_key = BufferPool::buffer_page_key(&self.catalog_id, &self.file_id, index);
+        if !self.pool.pages.contains_key(&page_key) {
+            page_key = self
+                .pool
+                .fetch_page(&self.catalog_id, &self.file_id, index)?;
+        }
+        let page_meta = self.pool.page_metas.get_mut(&page_key).unwrap();
+        page_meta.increment_access();
+        page_meta.dirty = true;
+        Ok(self.pool.pages.get_mut(&page_key).unwrap())
+    }
+
+    /// Resident pages come from the pool, the others are read with one open of the file.
+    fn visit_pages(&self, visit: &mut PageVisitor<'_, Error>) -> Result<(), Error> {
+        let mut resident: BTreeSet<u32> = BTreeSet::new();
+        for (page_key, location) in &self.pool.page_locations {
+            if location.catalog_id == self.catalog_id && location.file_id == self.file_id {
+                resident.insert(location.page_id);
+                visit(location.page_id, &self.pool.pages[page_key])?;
+            }
+        }
+        if self
+            .catalog_table
+            .table
+            .file_paths
+            .contains_key(&self.file_id)
+        {
+            DiskManager::visit_pages(
+                &self.catalog_table.table,
+                &self.file_id,
+                |page_id| !resident.contains(&page_id),
+                visit,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn page_ids(&self) -> Result<Vec<u32>, Error> {
+        let mut page_ids: BTreeSet<u32> = self
+            .pool
+            .page_locations
+            .values()
+            .filter(|location| {
+                location.catalog_id == self.catalog_id && location.file_id == self.file_id
+            })
+            .map(|location| location.page_id)
+            .collect();
+        if self
+            .catalog_table
+            .table
+            .file_paths
+            .contains_key(&self.file_id)
+        {
+            page_ids.extend(DiskManager::page_ids(
+                &self.catalog_table.table,
+                &self.file_id,
+            )?);
+        }
+        Ok(page_ids.into_iter().collect())
+    }
+
+    fn append_page(&mut self) -> Result<u32, Error> {
+        let first = self
+            .page_ids()?
+            .first()
+            .copied()
+            .ok_or(file::error::Error::PageOverflow(
+                "Insertion failed, this file has no page.".to_string(),
+            ))?;
+        let page_size = self.page(first)?.header.page_size;
+        DiskManager::allocate_page(&self.catalog_table.table, &self.file_id, page_size)
+    }
+
+    fn max_tuple_size(&self) -> Result<u32, Error> {
+        match self.page_ids()?.first() {
+            Some(index) => Ok(self.page(*index)?.max_tuple_size()),
+            None => Ok(0),
+        }
+    }
+}
+
 // todo spill
 
 #[cfg(test)]
@@ -185,7 +482,6 @@ pub mod tests {
     use std::time::Duration;
 
     use crate::storage::schema::encoding::Encoding;
-    use crate::storage::schema::Schema;
     use crate::storage::tablespace::metastore::Metastore;
     use crate::storage::tests::{delete_test_env, init_test_env};
 
@@ -214,6 +510,19 @@ pub mod tests {
         )
     }
 
+    fn file_rows(buffer_pool: &mut BufferPool, page_key: &u32) -> Vec<Vec<Option<Value>>> {
+        let (file_pages, _) = buffer_pool.file_pages(page_key).unwrap();
+        let schema = file_pages.schema().unwrap();
+        let mut rows: Vec<Vec<Option<Value>>> = Vec::new();
+        file_pages
+            .visit_rows(schema, &mut |_, tuple| {
+                rows.push(tuple.values(schema)?);
+                Ok(())
+            })
+            .unwrap();
+        rows
+    }
+
     #[test]
     fn used_space_should_compute_memory() {
         let path = init_test_env(TEST_PATH, "used_space");
@@ -348,6 +657,343 @@ pub mod tests {
         delete_test_env(TEST_PATH, "get_pages_by_table");
     }
 
+    #[test]
+    fn insert_tuple_should_check_keys_across_table_pages() {
+        let path = init_test_env(TEST_PATH, "insert_tuple");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut database = metastore.new_database("db_test", None).unwrap();
+        let schema = Schema::from_str("id BIGINT PRIMARY KEY, name STRING UNIQUE").unwrap();
+        database.new_table("tb_keys", None, &schema).unwrap();
+        let mut buffer_pool = BufferPool::build(100_000, metastore.location.to_str().unwrap());
+        let page_keys: Vec<u32> = (0..2)
+            .map(|page_id| {
+                buffer_pool
+                    .load_page(
+                        Page::build(256, 1).unwrap(),
+                        "db_test.tb_keys",
+                        "0",
+                        page_id,
+                    )
+                    .unwrap()
+            })
+            .collect();
+        let tuple = |id: i128, name: Option<&str>| {
+            Tuple::from_values(
+                &schema,
+                &[
+                    Some(Value::Bigint(id)),
+                    name.map(|name| Value::String(name.to_string())),
+                ],
+            )
+            .unwrap()
+        };
+        buffer_pool
+            .insert_tuple(&page_keys[0], tuple(1, Some("a")))
+            .unwrap();
+        buffer_pool
+            .insert_tuple(&page_keys[1], tuple(2, None))
+            .unwrap();
+        buffer_pool
+            .insert_tuple(&page_keys[1], tuple(3, None))
+            .unwrap();
+        assert!(buffer_pool
+            .insert_tuple(&page_keys[1], tuple(1, Some("b")))
+            .is_err());
+        assert!(buffer_pool
+            .insert_tuple(&page_keys[0], tuple(4, Some("a")))
+            .is_err());
+        assert_eq!(
+            buffer_pool
+                .pages
+                .get(&page_keys[0])
+                .unwrap()
+                .tuples()
+                .count(),
+            1
+        );
+        assert_eq!(
+            buffer_pool
+                .pages
+                .get(&page_keys[1])
+                .unwrap()
+                .tuples()
+                .count(),
+            2
+        );
+        delete_test_env(TEST_PATH, "insert_tuple");
+    }
+
+    #[test]
+    fn insert_tuple_should_check_keys_on_disk() {
+        let path = init_test_env(TEST_PATH, "insert_tuple_disk");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut database = metastore.new_database("db_test", None).unwrap();
+        let schema = Schema::from_str("id BIGINT PRIMARY KEY").unwrap();
+        let mut table = database.new_table("tb_disk_keys", None, &schema).unwrap();
+        let tuple = |id: i128| Tuple::from_values(&schema, &[Some(Value::Bigint(id))]).unwrap();
+        let mut file_ids: Vec<String> = Vec::new();
+        for id in 1..3 {
+            let file_id = DiskManager::create_file(&mut table, 1000, 0).unwrap();
+            DiskManager::allocate_page(&table, &file_id, 128).unwrap();
+            DiskManager::allocate_page(&table, &file_id, 128).unwrap();
+            let mut page = Page::build(128, 0).unwrap();
+            page.insert(tuple(id)).unwrap();
+            DiskManager::write_page(&table, &file_id, 1, &page).unwrap();
+            file_ids.push(file_id);
+        }
+        table.new_file().unwrap();
+        let mut buffer_pool = BufferPool::build(100_000, metastore.location.to_str().unwrap());
+        let page_key = buffer_pool
+            .fetch_page("db_test.tb_disk_keys", &file_ids[0], 0)
+            .unwrap();
+        assert!(buffer_pool.insert_tuple(&page_key, tuple(1)).is_err());
+        assert!(buffer_pool.insert_tuple(&page_key, tuple(2)).is_err());
+        buffer_pool.insert_tuple(&page_key, tuple(3)).unwrap();
+        assert!(buffer_pool.insert_tuple(&page_key, tuple(3)).is_err());
+        delete_test_env(TEST_PATH, "insert_tuple_disk");
+    }
+
+    #[test]
+    fn insert_tuple_should_read_keys_with_file_schema() {
+        let path = init_test_env(TEST_PATH, "insert_tuple_versions");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut database = metastore.new_database("db_test", None).unwrap();
+        let schema = Schema::from_str("id BIGINT PRIMARY KEY").unwrap();
+        let mut table = database.new_table("tb_versions", None, &schema).unwrap();
+        let older_id = DiskManager::create_file(&mut table, 1000, 0).unwrap();
+        DiskManager::allocate_page(&table, &older_id, 128).unwrap();
+        let mut page = Page::build(128, 0).unwrap();
+        page.insert(Tuple::from_values(&schema, &[Some(Value::Bigint(1))]).unwrap())
+            .unwrap();
+        DiskManager::write_page(&table, &older_id, 0, &page).unwrap();
+        table.add_column("name STRING").unwrap();
+        let file_id = DiskManager::create_file(&mut table, 1000, 0).unwrap();
+        DiskManager::allocate_page(&table, &file_id, 128).unwrap();
+        let mut buffer_pool = BufferPool::build(100_000, metastore.location.to_str().unwrap());
+        let page_key = buffer_pool
+            .fetch_page("db_test.tb_versions", &file_id, 0)
+            .unwrap();
+        let tuple = |id: i128| {
+            Tuple::from_values(
+                &table.schema,
+                &[
+                    Some(Value::Bigint(id)),
+                    Some(Value::String("a".to_string())),
+                ],
+            )
+            .unwrap()
+        };
+        assert!(buffer_pool.insert_tuple(&page_key, tuple(1)).is_err());
+        buffer_pool.insert_tuple(&page_key, tuple(2)).unwrap();
+        delete_test_env(TEST_PATH, "insert_tuple_versions");
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_tuple_should_panic_if_file_is_outdated() {
+        let path = init_test_env(TEST_PATH, "insert_tuple_outdated");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut database = metastore.new_database("db_test", None).unwrap();
+        let schema = Schema::from_str("id BIGINT PRIMARY KEY").unwrap();
+        let mut table = database.new_table("tb_outdated", None, &schema).unwrap();
+        let file_id = DiskManager::create_file(&mut table, 1000, 0).unwrap();
+        DiskManager::allocate_page(&table, &file_id, 128).unwrap();
+        let mut buffer_pool = BufferPool::build(100_000, metastore.location.to_str().unwrap());
+        let page_key = buffer_pool
+            .fetch_page("db_test.tb_outdated", &file_id, 0)
+            .unwrap();
+        table.add_column("name STRING").unwrap();
+        let tuple = Tuple::from_values(
+            &table.schema,
+            &[Some(Value::Bigint(1)), Some(Value::String("a".to_string()))],
+        )
+        .unwrap();
+        let result = buffer_pool.insert_tuple(&page_key, tuple);
+        delete_test_env(TEST_PATH, "insert_tuple_outdated");
+        result.unwrap();
+    }
+
+    #[test]
+    fn update_tuple_should_evaluate_checks() {
+        let path = init_test_env(TEST_PATH, "update_tuple");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut database = metastore.new_database("db_test", None).unwrap();
+        let schema = Schema::from_str(
+            "id BIGINT PRIMARY KEY, cost FLOAT, CONSTRAINT positive_cost CHECK (cost >= 0)",
+        )
+        .unwrap();
+        database.new_table("tb_checks", None, &schema).unwrap();
+        let mut buffer_pool = BufferPool::build(100_000, metastore.location.to_str().unwrap());
+        let page_key = buffer_pool
+            .load_page(Page::build(256, 1).unwrap(), "db_test.tb_checks", "0", 0)
+            .unwrap();
+        let tuple = |id: i128, cost: f64| {
+            Tuple::from_values(
+                &schema,
+                &[Some(Value::Bigint(id)), Some(Value::Float(cost))],
+            )
+            .unwrap()
+        };
+        assert!(buffer_pool.insert_tuple(&page_key, tuple(1, -1.0)).is_err());
+        let slot = buffer_pool.insert_tuple(&page_key, tuple(1, 1.0)).unwrap();
+        buffer_pool
+            .update_tuple(&page_key, slot, tuple(1, 2.0))
+            .unwrap();
+        let error = buffer_pool
+            .update_tuple(&page_key, slot, tuple(1, -2.0))
+            .unwrap_err();
+        assert!(error.to_string().contains("positive_cost"));
+        assert_eq!(
+            buffer_pool.pages[&page_key].read_by_slots(&[slot]).unwrap()[&slot]
+                .values(&schema)
+                .unwrap()[1],
+            Some(Value::Float(2.0))
+        );
+        delete_test_env(TEST_PATH, "update_tuple");
+    }
+
+    #[test]
+    fn update_tuple_should_forward_moved_row() {
+        let path = init_test_env(TEST_PATH, "update_tuple_forward");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut database = metastore.new_database("db_test", None).unwrap();
+        let schema = Schema::from_str("id BIGINT PRIMARY KEY, name STRING").unwrap();
+        let mut table = database.new_table("tb_forward", None, &schema).unwrap();
+        let file_id = DiskManager::create_file(&mut table, 1000, 0).unwrap();
+        for _ in 0..2 {
+            DiskManager::allocate_page(&table, &file_id, 128).unwrap();
+        }
+        let mut buffer_pool = BufferPool::build(100_000, metastore.location.to_str().unwrap());
+        let page_key = buffer_pool
+            .fetch_page("db_test.tb_forward", &file_id, 0)
+            .unwrap();
+        let tuple = |id: i128, name: &str| {
+            Tuple::from_values(
+                &schema,
+                &[
+                    Some(Value::Bigint(id)),
+                    Some(Value::String(name.to_string())),
+                ],
+            )
+            .unwrap()
+        };
+        let slot = buffer_pool.insert_tuple(&page_key, tuple(1, "a")).unwrap();
+        buffer_pool.insert_tuple(&page_key, tuple(2, "b")).unwrap();
+        buffer_pool
+            .update_tuple(&page_key, slot, tuple(1, &"c".repeat(60)))
+            .unwrap();
+        assert!(matches!(
+            buffer_pool.pages[&page_key].get_slot(slot).unwrap(),
+            Slot::Forward(_)
+        ));
+        buffer_pool
+            .update_tuple(&page_key, slot, tuple(1, &"d".repeat(60)))
+            .unwrap();
+        assert!(buffer_pool
+            .update_tuple(&page_key, slot, tuple(2, "e"))
+            .is_err());
+        assert_eq!(file_rows(&mut buffer_pool, &page_key).len(), 2);
+        buffer_pool.delete_tuple(&page_key, slot).unwrap();
+        assert_eq!(
+            file_rows(&mut buffer_pool, &page_key),
+            vec![tuple(2, "b").values(&schema).unwrap()]
+        );
+        delete_test_env(TEST_PATH, "update_tuple_forward");
+    }
+
+    #[test]
+    fn insert_tuple_should_chain_large_tuple() {
+        let path = init_test_env(TEST_PATH, "insert_tuple_chain");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut database = metastore.new_database("db_test", None).unwrap();
+        let schema = Schema::from_str("id BIGINT PRIMARY KEY, name STRING").unwrap();
+        let mut table = database.new_table("tb_chain", None, &schema).unwrap();
+        let file_id = DiskManager::create_file(&mut table, 2000, 0).unwrap();
+        DiskManager::allocate_page(&table, &file_id, 128).unwrap();
+        let mut buffer_pool = BufferPool::build(100_000, metastore.location.to_str().unwrap());
+        let page_key = buffer_pool
+            .fetch_page("db_test.tb_chain", &file_id, 0)
+            .unwrap();
+        let tuple = Tuple::from_values(
+            &schema,
+            &[Some(Value::Bigint(1)), Some(Value::String("a".repeat(300)))],
+        )
+        .unwrap();
+        let slot = buffer_pool.insert_tuple(&page_key, tuple.clone()).unwrap();
+        assert_eq!(buffer_pool.read_tuple(&page_key, slot).unwrap(), tuple);
+        assert!(DiskManager::page_ids(&table, &file_id).unwrap().len() > 1);
+        assert_eq!(
+            file_rows(&mut buffer_pool, &page_key),
+            vec![tuple.values(&schema).unwrap()]
+        );
+        buffer_pool.delete_tuple(&page_key, slot).unwrap();
+        assert!(buffer_pool
+            .pages
+            .values()
+            .flat_map(|page| &page.slots)
+            .all(|slot| *slot == Slot::Free));
+        delete_test_env(TEST_PATH, "insert_tuple_chain");
+    }
+
+    #[test]
+    fn vacuum_should_flush_modified_pages() {
+        let path = init_test_env(TEST_PATH, "vacuum_flush");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut database = metastore.new_database("db_test", None).unwrap();
+        let schema = Schema::from_str("id BIGINT").unwrap();
+        let mut table = database.new_table("tb_flush", None, &schema).unwrap();
+        let file_id = DiskManager::create_file(&mut table, 1000, 0).unwrap();
+        DiskManager::allocate_page(&table, &file_id, 64).unwrap();
+        let mut buffer_pool = BufferPool::build(2000, metastore.location.to_str().unwrap());
+        let page_key = buffer_pool
+            .fetch_page("db_test.tb_flush", &file_id, 0)
+            .unwrap();
+        let tuple = Tuple::from_values(&schema, &[Some(Value::Bigint(3))]).unwrap();
+        let slot = buffer_pool.insert_tuple(&page_key, tuple.clone()).unwrap();
+        buffer_pool.vacuum().unwrap();
+        assert!(!buffer_pool.pages.contains_key(&page_key));
+        let page = DiskManager::read_page(&table, &file_id, 0).unwrap();
+        assert_eq!(page.get_slot(slot).unwrap().tuple(), Some(&tuple));
+        delete_test_env(TEST_PATH, "vacuum_flush");
+    }
+
+    #[test]
+    fn flush_page_should_write_fetched_page_back() {
+        let path = init_test_env(TEST_PATH, "flush_page");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut database = metastore.new_database("db_test", None).unwrap();
+        let schema = Schema::from_str("id BIGINT PRIMARY KEY").unwrap();
+        let mut table = database.new_table("tb_disk", None, &schema).unwrap();
+        let file_id = DiskManager::create_file(&mut table, 1000, 1).unwrap();
+        for _ in 0..2 {
+            DiskManager::allocate_page(&table, &file_id, 256).unwrap();
+        }
+        let mut buffer_pool = BufferPool::build(100_000, metastore.location.to_str().unwrap());
+        let page_key = buffer_pool
+            .fetch_page("db_test.tb_disk", &file_id, 1)
+            .unwrap();
+        let tuple = Tuple::from_values(&schema, &[Some(Value::Bigint(5))]).unwrap();
+        let slot = buffer_pool.insert_tuple(&page_key, tuple.clone()).unwrap();
+        buffer_pool
+            .flush_page("db_test.tb_disk", &file_id, 1)
+            .unwrap();
+        let page = DiskManager::read_page(&table, &file_id, 1).unwrap();
+        assert_eq!(page.get_slot(slot).unwrap().tuple(), Some(&tuple));
+        assert_eq!(page, *buffer_pool.get_page(&page_key).unwrap());
+        delete_test_env(TEST_PATH, "flush_page");
+    }
+
+    #[test]
+    #[should_panic]
+    fn fetch_page_should_panic_if_unknown_table() {
+        let path = init_test_env(TEST_PATH, "fetch_page");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut buffer_pool = BufferPool::build(100, metastore.location.to_str().unwrap());
+        metastore.new_database("db_test", None).unwrap();
+        buffer_pool.fetch_page("db_test.tb_none", "0", 0).unwrap();
+        delete_test_env(TEST_PATH, "fetch_page");
+    }
+
     #[test]
     fn vacuum_should_remove_page() {
         let path = init_test_env(TEST_PATH, "vacuum");
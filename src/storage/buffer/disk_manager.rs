@@ -0,0 +1,663 @@
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::storage::buffer::error::Error;
+use crate::storage::file;
+use crate::storage::file::encoding::Encoding;
+use crate::storage::file::file_header::{
+    FileHeader, PageEntry, FILE_HEADER_SIZE, FORMAT_VERSION, PAGE_ENTRY_SIZE,
+};
+use crate::storage::file::page::Page;
+use crate::storage::file::rows::PageVisitor;
+use crate::storage::schema::Schema;
+use crate::storage::tablespace;
+use crate::storage::tablespace::table::Table;
+
+/// Reads and writes single pages of table files without loading the whole `File`.
+pub struct DiskManager;
+
+impl DiskManager {
+    fn open(table: &Table, file_id: &str) -> Result<fs::File, Error> {
+        let path =
+            table
+                .file_paths
+                .get(file_id)
+                .ok_or(tablespace::error::Error::ObjectNotFound(
+                    "File".to_string(),
+                    file_id.to_string(),
+                ))?;
+        Ok(fs::File::options().read(true).write(true).open(path)?)
+    }
+
+    fn read_at(file: &mut fs::File, offset: u32, length: u32) -> Result<Vec<u8>, Error> {
+        let mut bytes = vec![0; length as usize];
+        file.seek(SeekFrom::Start(offset as u64))?;
+        file.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn write_at(file: &mut fs::File, offset: u32, bytes: &[u8]) -> Result<(), Error> {
+        file.seek(SeekFrom::Start(offset as u64))?;
+        file.write_all(bytes)?;
+        Ok(())
+    }
+
+    /// First offset past the directory where `length` bytes fit between the pages, the
+    /// regions left by moved pages are reused before the file grows.
+    fn free_offset(
+        entries: &[PageEntry],
+        skipped: Option<usize>,
+        directory_end: u32,
+        length: u32,
+    ) -> u32 {
+        let mut regions: Vec<(u32, u32)> = (0..entries.len())
+            .filter(|index| Some(*index) != skipped)
+            .map(|index| {
+                (
+                    entries[index].offset,
+                    entries[index].offset + entries[index].length,
+                )
+            })
+            .collect();
+        regions.sort();
+        let mut offset = directory_end;
+        for (start, end) in regions {
+            if start >= offset && start - offset >= length {
+                break;
+            }
+            offset = offset.max(end);
+        }
+        offset
+    }
+
+    fn read_directory(file: &mut fs::File) -> Result<(FileHeader, Vec<PageEntry>), Error> {
+        let length = file.metadata()?.len().min(u32::MAX as u64) as u32;
+        let header = FileHeader::decode(&Self::read_at(file, 0, length.min(FILE_HEADER_SIZE))?)?;
+        if header.version != FORMAT_VERSION {
+            return Err(file::error::Error::OutdatedVersion(header.version))?;
+        }
+        let directory_size = header.directory_size()?;
+        if FILE_HEADER_SIZE + directory_size > length {
+            return Err(file::error::Error::CorruptedPage(format!(
+                "Page directory of {} pages is truncated",
+                header.pages
+            )))?;
+        }
+        let directory = Self::read_at(file, FILE_HEADER_SIZE, directory_size)?;
+        let entries = header.decode_directory(&directory)?;
+        Ok((header, entries))
+    }
+
+    /// Moves pages out of the way of a directory growing up to `directory_end`.
+    fn relocate(
+        file: &mut fs::File,
+        entries: &mut [PageEntry],
+        directory_end: u32,
+    ) -> Result<(), Error> {
+        for index in 0..entries.len() {
+            let entry = entries[index];
+            if entry.offset >= directory_end {
+                continue;
+            }
+            let bytes = Self::read_entry(file, &entry)?;
+            entries[index].offset =
+                Self::free_offset(entries, Some(index), directory_end, entry.length);
+            Self::write_at(file, entries[index].offset, &bytes)?;
+        }
+        Ok(())
+    }
+
+    fn write_directory(
+        file: &mut fs::File,
+        header: FileHeader,
+        entries: &mut [PageEntry],
+    ) -> Result<(), Error> {
+        let header = FileHeader {
+            pages: entries.len() as u32,
+            version: FORMAT_VERSION,
+            ..header
+        };
+        Self::relocate(
+            file,
+            entries,
+            FILE_HEADER_SIZE + header.pages * PAGE_ENTRY_SIZE,
+        )?;
+        let mut directory: Vec<u8> = Vec::new();
+        for entry in entries.iter() {
+            directory.extend(entry.as_bytes()?);
+        }
+        let header = FileHeader {
+            checksum: header.compute_checksum(&directory)?,
+            ..header
+        };
+        let mut bytes = header.as_bytes()?;
+        bytes.extend(directory);
+        Self::write_at(file, 0, &bytes)?;
+        let end = entries
+            .iter()
+            .map(|entry| entry.offset + entry.length)
+            .fold(bytes.len() as u32, u32::max);
+        Ok(file.set_len(end as u64)?)
+    }
+
+    /// Fails when a page ending at `end` would grow the file past its size.
+    fn ensure_room(file: &fs::File, header: &FileHeader, end: u32) -> Result<(), Error> {
+        if (file.metadata()?.len() as u32).max(end) > header.file_size {
+            return Err(file::error::Error::PageOverflow(
+                "Allocation failed, no more place on this file.".to_string(),
+            ))?;
+        }
+        Ok(())
+    }
+
+    fn read_entry(file: &mut fs::File, entry: &PageEntry) -> Result<Vec<u8>, Error> {
+        match entry.offset.checked_add(entry.length) {
+            Some(end) if end as u64 <= file.metadata()?.len() => {
+                Self::read_at(file, entry.offset, entry.length)
+            }
+            _ => Err(file::error::Error::CorruptedPage(format!(
+                "Page {} is out of the file",
+                entry.id
+            )))?,
+        }
+    }
+
+    fn locate(error: Error, file_id: &str, page_id: Option<u32>) -> Error {
+        match error {
+            Error::FileError(error) => Error::FileError(error.locate(Some(file_id), page_id)),
+            error => error,
+        }
+    }
+
+    fn entry(entries: &[PageEntry], page_id: u32) -> Result<usize, Error> {
+        Ok(entries
+            .iter()
+            .position(|entry| entry.id == page_id)
+            .ok_or(file::error::Error::InvalidIndex(page_id))?)
+    }
+
+    pub fn create_file(
+        table: &mut Table,
+        file_size: u32,
+        compression: u8,
+    ) -> Result<String, Error> {
+        let (file_id, path) = table.new_file()?;
+        fs::write(path, file::File::build(file_size, compression).as_bytes()?)?;
+        Ok(file_id)
+    }
+
+    /// Rewrites the files of the table written by an older format, returns their ids.
+    pub fn upgrade_table(table: &Table) -> Result<Vec<String>, Error> {
+        let mut file_ids: Vec<&String> = table.file_paths.keys().collect();
+        file_ids.sort();
+        let mut upgraded: Vec<String> = Vec::new();
+        for file_id in file_ids {
+            let path = &table.file_paths[file_id];
+            let bytes = fs::read(path)?;
+            if bytes.is_empty() || FileHeader::decode(&bytes)?.version == FORMAT_VERSION {
+                continue;
+            }
+            let schema =
+                table
+                    .file_schema(file_id)
+                    .ok_or(tablespace::error::Error::ObjectNotFound(
+                        "Schema of file".to_string(),
+                        file_id.to_string(),
+                    ))?;
+            let file = file::File::from_bytes(&bytes, Some(schema))
+                .map_err(|e| Self::locate(Error::FileError(e), file_id, None))?;
+            let upgrade_path = path.with_extension("upgrade");
+            fs::write(&upgrade_path, file.as_bytes()?)?;
+            fs::rename(&upgrade_path, path)?;
+            upgraded.push(file_id.to_string());
+        }
+        Ok(upgraded)
+    }
+
+    pub fn read_page(table: &Table, file_id: &str, page_id: u32) -> Result<Page, Error> {
+        let schema = table
+            .file_schema(file_id)
+            .ok_or(tablespace::error::Error::ObjectNotFound(
+                "Schema of file".to_string(),
+                file_id.to_string(),
+            ))?;
+        let mut file = Self::open(table, file_id)?;
+        let (_, entries) =
+            Self::read_directory(&mut file).map_err(|e| Self::locate(e, file_id, None))?;
+        let entry = entries[Self::entry(&entries, page_id)?];
+        Self::decode_entry(&mut file, &entry, schema, file_id)
+    }
+
+    /// Reads the pages of a file with a single open, skipping the ones `wanted` refuses.
+    pub fn visit_pages(
+        table: &Table,
+        file_id: &str,
+        wanted: impl Fn(u32) -> bool,
+        visit: &mut PageVisitor<'_, Error>,
+    ) -> Result<(), Error> {
+        let schema = table
+            .file_schema(file_id)
+            .ok_or(tablespace::error::Error::ObjectNotFound(
+                "Schema of file".to_string(),
+                file_id.to_string(),
+            ))?;
+        let mut file = Self::open(table, file_id)?;
+        if file.metadata()?.len() == 0 {
+            return Ok(());
+        }
+        let (_, entries) =
+            Self::read_directory(&mut file).map_err(|e| Self::locate(e, file_id, None))?;
+        for entry in entries.iter().filter(|entry| wanted(entry.id)) {
+            visit(
+                entry.id,
+                &Self::decode_entry(&mut file, entry, schema, file_id)?,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn decode_entry(
+        file: &mut fs::File,
+        entry: &PageEntry,
+        schema: &Schema,
+        file_id: &str,
+    ) -> Result<Page, Error> {
+        let bytes =
+            Self::read_entry(file, entry).map_err(|e| Self::locate(e, file_id, Some(entry.id)))?;
+        Page::from_bytes(&bytes, Some(schema))
+            .map_err(|e| Self::locate(Error::FileError(e), file_id, Some(entry.id)))
+    }
+
+    pub fn page_ids(table: &Table, file_id: &str) -> Result<Vec<u32>, Error> {
+        let mut file = Self::open(table, file_id)?;
+        if file.metadata()?.len() == 0 {
+            return Ok(Vec::new());
+        }
+        let (_, entries) =
+            Self::read_directory(&mut file).map_err(|e| Self::locate(e, file_id, None))?;
+        Ok(entries.iter().map(|entry| entry.id).collect())
+    }
+
+    pub fn write_page(
+        table: &Table,
+        file_id: &str,
+        page_id: u32,
+        page: &Page,
+    ) -> Result<(), Error> {
+        let mut file = Self::open(table, file_id)?;
+        let (header, mut entries) = Self::read_directory(&mut file)?;
+        let index = Self::entry(&entries, page_id)?;
+        let mut bytes = page.as_bytes()?;
+        if bytes.len() as u32 <= entries[index].length {
+            bytes.resize(entries[index].length as usize, 0);
+            return Self::write_at(&mut file, entries[index].offset, &bytes);
+        }
+        let directory_end = FILE_HEADER_SIZE + entries.len() as u32 * PAGE_ENTRY_SIZE;
+        let offset = Self::free_offset(&entries, Some(index), directory_end, bytes.len() as u32);
+        Self::ensure_room(&file, &header, offset + bytes.len() as u32)?;
+        entries[index] = PageEntry::build(page_id, offset, bytes.len() as u32);
+        Self::write_at(&mut file, entries[index].offset, &bytes)?;
+        Self::write_directory(&mut file, header, &mut entries)
+    }
+
+    pub fn allocate_page(table: &Table, file_id: &str, page_size: u32) -> Result<u32, Error> {
+        let mut file = Self::open(table, file_id)?;
+        let (header, mut entries) = Self::read_directory(&mut file)?;
+        let directory_end = FILE_HEADER_SIZE + (entries.len() as u32 + 1) * PAGE_ENTRY_SIZE;
+        let bytes = Page::build(page_size, header.compression)?.as_bytes()?;
+        Self::relocate(&mut file, &mut entries, directory_end)?;
+        let offset = Self::free_offset(&entries, None, directory_end, bytes.len() as u32);
+        Self::ensure_room(&file, &header, offset + bytes.len() as u32)?;
+        let page_id = entries.iter().map(|entry| entry.id + 1).max().unwrap_or(0);
+        Self::write_at(&mut file, offset, &bytes)?;
+        entries.push(PageEntry::build(page_id, offset, bytes.len() as u32));
+        Self::write_directory(&mut file, header, &mut entries)?;
+        Ok(page_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::file::codec::RUN_LENGTH;
+    use crate::storage::file::rows::Rows;
+    use crate::storage::file::tests::get_legacy_test_bytes;
+    use crate::storage::file::tuple::Tuple;
+    use crate::storage::file::File;
+    use crate::storage::tablespace::metastore::Metastore;
+    use crate::storage::tests::{delete_test_env, get_test_schema, init_test_env};
+
+    use super::*;
+
+    const TEST_PATH: &str = "target/tests/disk_manager";
+
+    fn get_test_table(metastore: &mut Metastore) -> Table {
+        let mut database = metastore.new_database("db_test", None).unwrap();
+        database
+            .new_table("tb_test", None, &get_test_schema())
+            .unwrap()
+    }
+
+    fn get_test_tuple(id: u8) -> Tuple {
+        Tuple::build(&get_test_schema(), &[0], &[id; 33]).unwrap()
+    }
+
+    #[test]
+    fn allocate_page_should_extend_file() {
+        let path = init_test_env(TEST_PATH, "allocate_page");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut table = get_test_table(&mut metastore);
+        let file_id = DiskManager::create_file(&mut table, 1000, 0).unwrap();
+        assert_eq!(
+            DiskManager::allocate_page(&table, &file_id, 200).unwrap(),
+            0
+        );
+        assert_eq!(
+            DiskManager::allocate_page(&table, &file_id, 200).unwrap(),
+            1
+        );
+        assert_eq!(
+            DiskManager::read_page(&table, &file_id, 1).unwrap(),
+            Page::build(200, 0).unwrap()
+        );
+        let bytes = fs::read(&table.file_paths[&file_id]).unwrap();
+        let file = File::from_bytes(&bytes, Some(&get_test_schema())).unwrap();
+        assert_eq!(file.as_bytes().unwrap().len(), 17 + 12 * 2 + 200 * 2);
+        delete_test_env(TEST_PATH, "allocate_page");
+    }
+
+    #[test]
+    #[should_panic]
+    fn allocate_page_should_panic_if_full_file() {
+        let path = init_test_env(TEST_PATH, "allocate_page_full");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut table = get_test_table(&mut metastore);
+        let file_id = DiskManager::create_file(&mut table, 500, 0).unwrap();
+        DiskManager::allocate_page(&table, &file_id, 250).unwrap();
+        DiskManager::allocate_page(&table, &file_id, 250).unwrap();
+        delete_test_env(TEST_PATH, "allocate_page_full");
+    }
+
+    #[test]
+    fn write_page_should_only_touch_its_page() {
+        let path = init_test_env(TEST_PATH, "write_page");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut table = get_test_table(&mut metastore);
+        let file_id = DiskManager::create_file(&mut table, 2000, 0).unwrap();
+        for _ in 0..3 {
+            DiskManager::allocate_page(&table, &file_id, 200).unwrap();
+        }
+        let mut page = DiskManager::read_page(&table, &file_id, 1).unwrap();
+        page.insert(get_test_tuple(7)).unwrap();
+        DiskManager::write_page(&table, &file_id, 1, &page).unwrap();
+        assert_eq!(DiskManager::read_page(&table, &file_id, 1).unwrap(), page);
+        assert_eq!(
+            DiskManager::read_page(&table, &file_id, 0).unwrap(),
+            Page::build(200, 0).unwrap()
+        );
+        assert_eq!(
+            DiskManager::read_page(&table, &file_id, 2).unwrap(),
+            Page::build(200, 0).unwrap()
+        );
+        delete_test_env(TEST_PATH, "write_page");
+    }
+
+    #[test]
+    fn write_page_should_move_grown_page() {
+        let path = init_test_env(TEST_PATH, "write_page_grown");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut table = get_test_table(&mut metastore);
+        let file_id = DiskManager::create_file(&mut table, 4000, RUN_LENGTH).unwrap();
+        DiskManager::allocate_page(&table, &file_id, 500).unwrap();
+        DiskManager::allocate_page(&table, &file_id, 500).unwrap();
+        let mut page = DiskManager::read_page(&table, &file_id, 0).unwrap();
+        for id in 0..5 {
+            page.insert(get_test_tuple(id)).unwrap();
+        }
+        DiskManager::write_page(&table, &file_id, 0, &page).unwrap();
+        assert_eq!(DiskManager::read_page(&table, &file_id, 0).unwrap(), page);
+        assert_eq!(
+            DiskManager::read_page(&table, &file_id, 1).unwrap(),
+            Page::build(500, RUN_LENGTH).unwrap()
+        );
+        delete_test_env(TEST_PATH, "write_page_grown");
+    }
+
+    #[test]
+    fn write_page_should_reuse_freed_regions() {
+        let path = init_test_env(TEST_PATH, "write_page_reuse");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut table = get_test_table(&mut metastore);
+        let file_id = DiskManager::create_file(&mut table, 4000, RUN_LENGTH).unwrap();
+        DiskManager::allocate_page(&table, &file_id, 500).unwrap();
+        DiskManager::allocate_page(&table, &file_id, 500).unwrap();
+        let file_path = &table.file_paths[&file_id];
+        for page_id in [0, 1] {
+            let mut page = DiskManager::read_page(&table, &file_id, page_id).unwrap();
+            for id in 0..5 {
+                page.insert(get_test_tuple(id)).unwrap();
+                DiskManager::write_page(&table, &file_id, page_id, &page).unwrap();
+            }
+        }
+        let length = fs::metadata(file_path).unwrap().len();
+        let file =
+            File::from_bytes(&fs::read(file_path).unwrap(), Some(&get_test_schema())).unwrap();
+        assert!(length < 2 * file.as_bytes().unwrap().len() as u64);
+        assert_eq!(file.rows(&get_test_schema()).unwrap().len(), 10);
+        DiskManager::allocate_page(&table, &file_id, 500).unwrap();
+        delete_test_env(TEST_PATH, "write_page_reuse");
+    }
+
+    #[test]
+    fn write_page_should_not_grow_past_file_size() {
+        let path = init_test_env(TEST_PATH, "write_page_full");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut table = get_test_table(&mut metastore);
+        let length = Page::build(500, RUN_LENGTH)
+            .unwrap()
+            .as_bytes()
+            .unwrap()
+            .len() as u32;
+        let file_id =
+            DiskManager::create_file(&mut table, 17 + 12 * 2 + length * 2, RUN_LENGTH).unwrap();
+        DiskManager::allocate_page(&table, &file_id, 500).unwrap();
+        DiskManager::allocate_page(&table, &file_id, 500).unwrap();
+        let file_path = &table.file_paths[&file_id];
+        let before = fs::read(file_path).unwrap();
+        let mut page = DiskManager::read_page(&table, &file_id, 0).unwrap();
+        page.insert(get_test_tuple(1)).unwrap();
+        assert!(matches!(
+            DiskManager::write_page(&table, &file_id, 0, &page),
+            Err(Error::FileError(file::error::Error::PageOverflow(_)))
+        ));
+        assert_eq!(fs::read(file_path).unwrap(), before);
+        delete_test_env(TEST_PATH, "write_page_full");
+    }
+
+    #[test]
+    fn allocate_page_should_count_the_file_end() {
+        let path = init_test_env(TEST_PATH, "allocate_page_end");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut table = get_test_table(&mut metastore);
+        let (file_id, file_path) = table.new_file().unwrap();
+        let mut header = FileHeader {
+            pages: 1,
+            ..FileHeader::build(500, 0)
+        };
+        let entry = PageEntry::build(0, 200, 200).as_bytes().unwrap();
+        header.checksum = header.compute_checksum(&entry).unwrap();
+        let mut bytes = header.as_bytes().unwrap();
+        bytes.extend(entry);
+        bytes.resize(200, 0);
+        bytes.extend(Page::build(200, 0).unwrap().as_bytes().unwrap());
+        fs::write(&file_path, bytes).unwrap();
+        assert!(matches!(
+            DiskManager::allocate_page(&table, &file_id, 200),
+            Err(Error::FileError(file::error::Error::PageOverflow(_)))
+        ));
+        delete_test_env(TEST_PATH, "allocate_page_end");
+    }
+
+    #[test]
+    fn read_page_should_read_files_written_whole() {
+        let path = init_test_env(TEST_PATH, "read_page");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut table = get_test_table(&mut metastore);
+        let (file_id, file_path) = table.new_file().unwrap();
+        let mut file = File::build(2000, 0);
+        file.insert_page(&Page::build(200, 0).unwrap()).unwrap();
+        let row = file.insert_tuple(get_test_tuple(3)).unwrap();
+        fs::write(&file_path, file.as_bytes().unwrap()).unwrap();
+        let mut page = DiskManager::read_page(&table, &file_id, row.page).unwrap();
+        assert_eq!(
+            page.get_slot(row.slot).unwrap().tuple(),
+            Some(&get_test_tuple(3))
+        );
+        assert_eq!(
+            DiskManager::allocate_page(&table, &file_id, 200).unwrap(),
+            1
+        );
+        page.insert(get_test_tuple(4)).unwrap();
+        DiskManager::write_page(&table, &file_id, 0, &page).unwrap();
+        let bytes = fs::read(&file_path).unwrap();
+        let file = File::from_bytes(&bytes, Some(&get_test_schema())).unwrap();
+        assert_eq!(file.rows(&get_test_schema()).unwrap().len(), 2);
+        delete_test_env(TEST_PATH, "read_page");
+    }
+
+    #[test]
+    fn visit_pages_should_skip_unwanted_pages() {
+        let path = init_test_env(TEST_PATH, "visit_pages");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut table = get_test_table(&mut metastore);
+        let file_id = DiskManager::create_file(&mut table, 1000, 0).unwrap();
+        for _ in 0..3 {
+            DiskManager::allocate_page(&table, &file_id, 200).unwrap();
+        }
+        let mut page_ids: Vec<u32> = Vec::new();
+        DiskManager::visit_pages(
+            &table,
+            &file_id,
+            |page_id| page_id != 1,
+            &mut |page_id, _| {
+                page_ids.push(page_id);
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(page_ids, vec![0, 2]);
+        delete_test_env(TEST_PATH, "visit_pages");
+    }
+
+    #[test]
+    fn read_page_should_report_corrupted_page() {
+        let path = init_test_env(TEST_PATH, "read_page_corrupted");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut table = get_test_table(&mut metastore);
+        let file_id = DiskManager::create_file(&mut table, 1000, 0).unwrap();
+        DiskManager::allocate_page(&table, &file_id, 200).unwrap();
+        DiskManager::allocate_page(&table, &file_id, 200).unwrap();
+        let mut bytes = fs::read(&table.file_paths[&file_id]).unwrap();
+        let length = bytes.len();
+        bytes[length - 1] = 1;
+        fs::write(&table.file_paths[&file_id], bytes).unwrap();
+        assert_eq!(
+            DiskManager::read_page(&table, &file_id, 1)
+                .unwrap_err()
+                .to_string(),
+            format!(
+                "File error: Checksum mismatch in file {} on page 1.",
+                file_id
+            )
+        );
+        assert!(DiskManager::read_page(&table, &file_id, 0).is_ok());
+        delete_test_env(TEST_PATH, "read_page_corrupted");
+    }
+
+    #[test]
+    fn read_page_should_reject_entries_out_of_the_file() {
+        let path = init_test_env(TEST_PATH, "read_page_out");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut table = get_test_table(&mut metastore);
+        let (file_id, file_path) = table.new_file().unwrap();
+        let mut header = FileHeader {
+            pages: 1,
+            ..FileHeader::build(1000, 0)
+        };
+        let entry = PageEntry::build(0, 29, u32::MAX - 29).as_bytes().unwrap();
+        header.checksum = header.compute_checksum(&entry).unwrap();
+        let mut bytes = header.as_bytes().unwrap();
+        bytes.extend(entry);
+        bytes.extend(Page::build(200, 0).unwrap().as_bytes().unwrap());
+        fs::write(&file_path, bytes).unwrap();
+        assert!(matches!(
+            DiskManager::read_page(&table, &file_id, 0),
+            Err(Error::FileError(file::error::Error::CorruptedPage(_)))
+        ));
+        delete_test_env(TEST_PATH, "read_page_out");
+    }
+
+    #[test]
+    #[should_panic]
+    fn read_page_should_panic_if_unknown_page() {
+        let path = init_test_env(TEST_PATH, "read_page_unknown");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut table = get_test_table(&mut metastore);
+        let file_id = DiskManager::create_file(&mut table, 1000, 0).unwrap();
+        DiskManager::allocate_page(&table, &file_id, 200).unwrap();
+        DiskManager::read_page(&table, &file_id, 1).unwrap();
+        delete_test_env(TEST_PATH, "read_page_unknown");
+    }
+
+    #[test]
+    fn read_page_should_fail_on_legacy_files_until_upgraded() {
+        let path = init_test_env(TEST_PATH, "legacy_file");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut table = get_test_table(&mut metastore);
+        let (file_id, file_path) = table.new_file().unwrap();
+        fs::write(&file_path, get_legacy_test_bytes()).unwrap();
+        assert!(matches!(
+            DiskManager::read_page(&table, &file_id, 0),
+            Err(Error::FileError(file::error::Error::OutdatedVersion(_)))
+        ));
+        DiskManager::upgrade_table(&table).unwrap();
+        assert_eq!(DiskManager::page_ids(&table, &file_id).unwrap(), vec![0]);
+        DiskManager::read_page(&table, &file_id, 0).unwrap();
+        delete_test_env(TEST_PATH, "legacy_file");
+    }
+
+    #[test]
+    fn upgrade_table_should_rewrite_older_files() {
+        let path = init_test_env(TEST_PATH, "upgrade_table");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut table = get_test_table(&mut metastore);
+        let (older_id, older_path) = table.new_file().unwrap();
+        fs::write(&older_path, get_legacy_test_bytes()).unwrap();
+        let current_id = DiskManager::create_file(&mut table, 1000, 0).unwrap();
+        DiskManager::allocate_page(&table, &current_id, 200).unwrap();
+        let current = fs::read(&table.file_paths[&current_id]).unwrap();
+        table.new_file().unwrap();
+        assert_eq!(DiskManager::upgrade_table(&table).unwrap(), vec![older_id]);
+        let bytes = fs::read(&older_path).unwrap();
+        assert_eq!(bytes[10..13], FORMAT_VERSION);
+        let file = File::from_bytes(&bytes, Some(&get_test_schema())).unwrap();
+        assert_eq!(file.rows(&get_test_schema()).unwrap().len(), 3);
+        assert_eq!(fs::read(&table.file_paths[&current_id]).unwrap(), current);
+        assert!(DiskManager::upgrade_table(&table).unwrap().is_empty());
+        delete_test_env(TEST_PATH, "upgrade_table");
+    }
+
+    #[test]
+    #[should_panic]
+    fn read_page_should_panic_if_future_version() {
+        let path = init_test_env(TEST_PATH, "future_version");
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut table = get_test_table(&mut metastore);
+        let file_id = DiskManager::create_file(&mut table, 1000, 0).unwrap();
+        DiskManager::allocate_page(&table, &file_id, 200).unwrap();
+        let mut bytes = fs::read(&table.file_paths[&file_id]).unwrap();
+        bytes[10] = 2;
+        fs::write(&table.file_paths[&file_id], bytes).unwrap();
+        DiskManager::read_page(&table, &file_id, 0).unwrap();
+        delete_test_env(TEST_PATH, "future_version");
+    }
+}
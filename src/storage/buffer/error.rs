@@ -1,12 +1,14 @@
 use std::fmt;
 
-use crate::storage::{file, tablespace};
+use crate::storage::{file, schema, tablespace};
 
 pub enum Error {
     UnknownTableKey(u32),
+    OutdatedFile(String),
     Tablespace(tablespace::error::Error),
     StdError(std::io::Error),
     FileError(file::error::Error),
+    Schema(schema::error::Error),
 }
 
 impl fmt::Display for Error {
@@ -15,9 +17,15 @@ impl fmt::Display for Error {
             Error::UnknownTableKey(ref msg) => {
                 write!(f, "Table {} doesn't buffered.", msg)
             }
+            Error::OutdatedFile(ref file_id) => write!(
+                f,
+                "File {} is behind the table schema, upgrade it before writing.",
+                file_id
+            ),
             Error::Tablespace(ref err) => write!(f, "Tablespace error: {}.", err),
             Error::StdError(ref err) => write!(f, "Std error: {}.", err),
             Error::FileError(ref err) => write!(f, "File error: {}.", err),
+            Error::Schema(ref err) => write!(f, "Schema error: {}.", err),
         }
     }
 }
@@ -48,6 +56,12 @@ impl From<file::error::Error> for Error {
     }
 }
 
+impl From<schema::error::Error> for Error {
+    fn from(value: schema::error::Error) -> Self {
+        Error::Schema(value)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
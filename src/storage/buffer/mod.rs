@@ -1,4 +1,5 @@
 pub mod buffer_pool;
+pub mod disk_manager;
 pub mod error;
 pub mod page_meta;
 pub mod wal;
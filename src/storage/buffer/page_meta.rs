@@ -4,6 +4,7 @@ use chrono::Local;
 pub struct PageMeta {
     pub last_access: i64,
     pub count_access: usize,
+    pub dirty: bool,
 }
 
 impl PageMeta {
@@ -12,6 +13,7 @@ impl PageMeta {
         PageMeta {
             last_access: now.timestamp_millis(),
             count_access: 1,
+            dirty: false,
         }
     }
 
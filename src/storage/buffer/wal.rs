@@ -54,7 +54,7 @@ impl Wal {
         } else {
             let rows: Vec<WalRow> = file_buffer[..file_buffer.len() - 1]
                 .split(|&b| b == b'\n')
-                .map(|bytes| WalRow::from_bytes(bytes).unwrap())
+                .map(|bytes| WalRow::from_bytes(bytes, None).unwrap())
                 .collect();
             self.checkpoint = self.file.stream_position()?;
             Ok(rows)
@@ -118,7 +118,7 @@ pub mod tests {
             assert_eq!(row.transaction_size, 66);
             assert_eq!(row.operation, Operation::Insert);
         }
-        assert_eq!(wal.checkpoint, 270);
+        assert_eq!(wal.checkpoint, 261);
         delete_test_env(TEST_PATH, "read_01");
     }
 
@@ -129,7 +129,7 @@ pub mod tests {
         let rows = vec![get_test_wal_row(), get_test_wal_row(), get_test_wal_row()];
         wal.write_transaction(&rows).unwrap();
         wal.commit().unwrap();
-        wal.checkpoint = 90;
+        wal.checkpoint = 87;
         let rows = wal.read().unwrap();
         assert_eq!(rows.len(), 2);
         for row in rows {
@@ -137,7 +137,7 @@ pub mod tests {
             assert_eq!(row.transaction_size, 66);
             assert_eq!(row.operation, Operation::Insert);
         }
-        assert_eq!(wal.checkpoint, 270);
+        assert_eq!(wal.checkpoint, 261);
         delete_test_env(TEST_PATH, "read_02");
     }
 
@@ -23,7 +23,7 @@ pub struct WalRow {
     pub buffer_page_id: Option<u32>,
     pub file_id: Option<u32>,
     pub page_id: Option<u32>,
-    pub slot: Option<(u32, u32)>,
+    pub slot: Option<u32>,
 }
 
 impl WalRow {
@@ -52,7 +52,7 @@ impl WalRow {
         transaction_size: u32,
         catalog_table_id: &str,
         old_data: Tuple,
-        id: (u32, u32, u32, u32, u32),
+        id: (u32, u32, u32, u32),
     ) -> WalRow {
         WalRow {
             date_created: Local::now().timestamp_millis(),
@@ -65,7 +65,7 @@ impl WalRow {
             buffer_page_id: Some(id.0),
             file_id: Some(id.1),
             page_id: Some(id.2),
-            slot: Some((id.3, id.4)),
+            slot: Some(id.3),
         }
     }
 
@@ -75,7 +75,7 @@ impl WalRow {
         catalog_table_id: &str,
         new_data: Tuple,
         old_data: Tuple,
-        id: (u32, u32, u32, u32, u32),
+        id: (u32, u32, u32, u32),
     ) -> WalRow {
         WalRow {
             date_created: Local::now().timestamp_millis(),
@@ -88,7 +88,7 @@ impl WalRow {
             buffer_page_id: Some(id.0),
             file_id: Some(id.1),
             page_id: Some(id.2),
-            slot: Some((id.3, id.4)),
+            slot: Some(id.3),
         }
     }
 }
@@ -111,7 +111,7 @@ pub mod tests {
             Tuple::build(
                 &Schema::from_str("id BIGINT, cost FLOAT, available BOOLEAN, date TIMESTAMP")
                     .unwrap(),
-                &[0, 0, 1, 0],
+                &[0b0100],
                 &[4; 32],
             )
             .unwrap(),
@@ -123,9 +123,9 @@ pub mod tests {
         assert_eq!(
             get_test_wal_row().as_bytes().unwrap()[8..],
             vec![
-                23, 0, 0, 0, 66, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 56, 55, 0, 0, 0, 0, 0, 1, 0, 4,
-                0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 32, 0, 0, 0, 0, 0, 0, 0, 4, 4, 4, 4, 4, 4, 4, 4,
-                4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 0, 0, 0, 0
+                23, 0, 0, 0, 66, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 56, 55, 0, 0, 0, 0, 0, 1, 0, 1,
+                0, 0, 0, 0, 0, 0, 0, 4, 32, 0, 0, 0, 0, 0, 0, 0, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4,
+                4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 0, 0, 0, 0
             ]
         );
     }
@@ -135,12 +135,15 @@ pub mod tests {
         let mut row = get_test_wal_row();
         row.date_created = 0;
         assert_eq!(
-            WalRow::from_bytes(&[
-                0, 0, 0, 0, 0, 0, 0, 0, 23, 0, 0, 0, 66, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 56, 55,
-                0, 0, 0, 0, 0, 1, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 32, 0, 0, 0, 0, 0, 0, 0,
-                4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4,
-                4, 4, 4, 4, 0, 0, 0, 0
-            ])
+            WalRow::from_bytes(
+                &[
+                    0, 0, 0, 0, 0, 0, 0, 0, 23, 0, 0, 0, 66, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 56,
+                    55, 0, 0, 0, 0, 0, 1, 0, 1, 0, 0, 0, 0, 0, 0, 0, 4, 32, 0, 0, 0, 0, 0, 0, 0, 4,
+                    4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4,
+                    4, 4, 4, 4, 4, 0, 0, 0, 0
+                ],
+                None
+            )
             .unwrap(),
             row
         )
@@ -170,11 +173,11 @@ pub mod tests {
             Tuple::build(
                 &Schema::from_str("id BIGINT, cost FLOAT, available BOOLEAN, date TIMESTAMP")
                     .unwrap(),
-                &[0, 0, 1, 0],
+                &[0b0100],
                 &[4; 32],
             )
             .unwrap(),
-            (0, 1, 2, 3, 4),
+            (0, 1, 2, 3),
         );
         assert_eq!(row.transaction_id, 23);
         assert_eq!(row.transaction_size, 66);
@@ -185,7 +188,7 @@ pub mod tests {
         assert_eq!(row.buffer_page_id.unwrap(), 0);
         assert_eq!(row.file_id.unwrap(), 1);
         assert_eq!(row.page_id.unwrap(), 2);
-        assert_eq!(row.slot.unwrap(), (3, 4));
+        assert_eq!(row.slot.unwrap(), 3);
     }
 
     #[test]
@@ -197,18 +200,18 @@ pub mod tests {
             Tuple::build(
                 &Schema::from_str("id BIGINT, cost FLOAT, available BOOLEAN, date TIMESTAMP")
                     .unwrap(),
-                &[0, 0, 1, 0],
+                &[0b0100],
                 &[4; 32],
             )
             .unwrap(),
             Tuple::build(
                 &Schema::from_str("id BIGINT, cost FLOAT, available BOOLEAN, date TIMESTAMP")
                     .unwrap(),
-                &[0, 0, 1, 0],
+                &[0b0100],
                 &[4; 32],
             )
             .unwrap(),
-            (0, 1, 2, 3, 4),
+            (0, 1, 2, 3),
         );
         assert_eq!(row.transaction_id, 23);
         assert_eq!(row.transaction_size, 66);
@@ -219,6 +222,6 @@ pub mod tests {
         assert_eq!(row.buffer_page_id.unwrap(), 0);
         assert_eq!(row.file_id.unwrap(), 1);
         assert_eq!(row.page_id.unwrap(), 2);
-        assert_eq!(row.slot.unwrap(), (3, 4));
+        assert_eq!(row.slot.unwrap(), 3);
     }
 }
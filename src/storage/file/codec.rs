@@ -0,0 +1,343 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::storage::file::error::Error;
+
+pub const RAW: u8 = 0;
+pub const RUN_LENGTH: u8 = 1;
+pub const DICTIONARY: u8 = 2;
+pub const LZ: u8 = 3;
+
+/// Decoders stop once `size` bytes are rebuilt, so trailing padding is ignored.
+pub trait Codec {
+    fn compress(&self, bytes: &[u8]) -> Vec<u8>;
+    fn decompress(&self, bytes: &[u8], size: usize) -> Result<Vec<u8>, Error>;
+}
+
+pub fn codec(compression: u8) -> Result<&'static dyn Codec, Error> {
+    match compression {
+        RAW => Ok(&Raw),
+        RUN_LENGTH => Ok(&RunLength),
+        DICTIONARY => Ok(&Dictionary),
+        LZ => Ok(&Lz),
+        _ => Err(Error::UnknownCodec(compression)),
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn build(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes, position: 0 }
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8], Error> {
+        let bytes = self
+            .bytes
+            .get(self.position..self.position + length)
+            .ok_or(Error::CorruptedPage(
+                "Compressed page ended unexpectedly".to_string(),
+            ))?;
+        self.position += length;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+}
+
+fn checked(bytes: Vec<u8>, size: usize) -> Result<Vec<u8>, Error> {
+    match bytes.len() == size {
+        true => Ok(bytes),
+        false => Err(Error::CorruptedPage(format!(
+            "Compressed page inflates to {} bytes instead of {}",
+            bytes.len(),
+            size
+        ))),
+    }
+}
+
+/// Reserves no more than the input length, a corrupted size must not allocate on its own.
+fn buffer(bytes: &[u8], size: usize) -> Vec<u8> {
+    Vec::with_capacity(size.min(bytes.len()))
+}
+
+fn push_literals(compressed: &mut Vec<u8>, literals: &[u8]) {
+    for chunk in literals.chunks(128) {
+        compressed.push(chunk.len() as u8 - 1);
+        compressed.extend_from_slice(chunk);
+    }
+}
+
+pub struct Raw;
+
+impl Codec for Raw {
+    fn compress(&self, bytes: &[u8]) -> Vec<u8> {
+        bytes.to_vec()
+    }
+
+    fn decompress(&self, bytes: &[u8], size: usize) -> Result<Vec<u8>, Error> {
+        Ok(Reader::build(bytes).take(size)?.to_vec())
+    }
+}
+
+/// Control byte below 128 announces `n + 1` literal bytes, above it a byte repeated `n - 126` times.
+pub struct RunLength;
+
+impl Codec for RunLength {
+    fn compress(&self, bytes: &[u8]) -> Vec<u8> {
+        let mut compressed: Vec<u8> = Vec::new();
+        let mut index = 0;
+        while index < bytes.len() {
+            let run = bytes[index..]
+                .iter()
+                .take(129)
+                .take_while(|byte| **byte == bytes[index])
+                .count();
+            if run >= 2 {
+                compressed.push((run + 126) as u8);
+                compressed.push(bytes[index]);
+                index += run;
+            } else {
+                let start = index;
+                while index < bytes.len()
+                    && index - start < 128
+                    && bytes.get(index + 1) != Some(&bytes[index])
+                {
+                    index += 1;
+                }
+                push_literals(&mut compressed, &bytes[start..index]);
+            }
+        }
+        compressed
+    }
+
+    fn decompress(&self, bytes: &[u8], size: usize) -> Result<Vec<u8>, Error> {
+        let mut reader = Reader::build(bytes);
+        let mut inflated = buffer(bytes, size);
+        while inflated.len() < size {
+            match reader.byte()? {
+                control @ 0..=127 => inflated.extend_from_slice(reader.take(control as usize + 1)?),
+                control => {
+                    let byte = reader.byte()?;
+                    inflated.resize(inflated.len() + control as usize - 126, byte);
+                }
+            }
+        }
+        checked(inflated, size)
+    }
+}
+
+const WORD_SIZE: usize = 4;
+const ESCAPE: u8 = u8::MAX;
+
+/// Frequent 4-byte words are replaced by a one byte code, others are escaped.
+pub struct Dictionary;
+
+impl Codec for Dictionary {
+    fn compress(&self, bytes: &[u8]) -> Vec<u8> {
+        let words = bytes.chunks_exact(WORD_SIZE);
+        let remainder = words.remainder();
+        let mut frequencies: HashMap<&[u8], usize> = HashMap::new();
+        for word in words.clone() {
+            *frequencies.entry(word).or_default() += 1;
+        }
+        let mut dictionary: Vec<(&[u8], usize)> = frequencies
+            .into_iter()
+            .filter(|(_, frequency)| *frequency > 1)
+            .collect();
+        dictionary.sort_by_key(|(word, frequency)| (Reverse(*frequency), *word));
+        dictionary.truncate(ESCAPE as usize);
+        let codes: HashMap<&[u8], u8> = (0_u8..)
+            .zip(&dictionary)
+            .map(|(code, (word, _))| (*word, code))
+            .collect();
+
+        let mut compressed = vec![dictionary.len() as u8];
+        dictionary
+            .iter()
+            .for_each(|(word, _)| compressed.extend_from_slice(word));
+        for word in words {
+            match codes.get(word) {
+                Some(code) => compressed.push(*code),
+                None => {
+                    compressed.push(ESCAPE);
+                    compressed.extend_from_slice(word);
+                }
+            }
+        }
+        compressed.extend_from_slice(remainder);
+        compressed
+    }
+
+    fn decompress(&self, bytes: &[u8], size: usize) -> Result<Vec<u8>, Error> {
+        let mut reader = Reader::build(bytes);
+        let entries = reader.byte()? as usize;
+        let dictionary: Vec<&[u8]> = reader
+            .take(entries * WORD_SIZE)?
+            .chunks(WORD_SIZE)
+            .collect();
+        let mut inflated = buffer(bytes, size);
+        for _ in 0..size / WORD_SIZE {
+            match reader.byte()? {
+                ESCAPE => inflated.extend_from_slice(reader.take(WORD_SIZE)?),
+                code => inflated.extend_from_slice(dictionary.get(code as usize).ok_or(
+                    Error::CorruptedPage(format!("Unknown dictionary code {}", code)),
+                )?),
+            }
+        }
+        inflated.extend_from_slice(reader.take(size % WORD_SIZE)?);
+        checked(inflated, size)
+    }
+}
+
+const MIN_MATCH: usize = 4;
+const MAX_MATCH: usize = MIN_MATCH + 127;
+const WINDOW: usize = u16::MAX as usize;
+
+/// LZ77 style, a control byte above 127 copies `n - 124` bytes from a 2 bytes back reference.
+pub struct Lz;
+
+impl Codec for Lz {
+    fn compress(&self, bytes: &[u8]) -> Vec<u8> {
+        let mut compressed: Vec<u8> = Vec::new();
+        let mut positions: HashMap<&[u8], usize> = HashMap::new();
+        let mut literals = 0;
+        let mut index = 0;
+        while index < bytes.len() {
+            let mut matched = 0;
+            let mut distance = 0;
+            if let Some(key) = bytes.get(index..index + MIN_MATCH) {
+                if let Some(candidate) = positions.insert(key, index) {
+                    distance = index - candidate;
+                    matched = (0..MAX_MATCH.min(bytes.len() - index))
+                        .take_while(|offset| bytes[candidate + offset] == bytes[index + offset])
+                        .count();
+                }
+            }
+            if matched >= MIN_MATCH && distance <= WINDOW {
+                push_literals(&mut compressed, &bytes[literals..index]);
+                compressed.push(0x80 | (matched - MIN_MATCH) as u8);
+                compressed.extend_from_slice(&(distance as u16).to_le_bytes());
+                index += matched;
+                literals = index;
+            } else {
+                index += 1;
+            }
+        }
+        push_literals(&mut compressed, &bytes[literals..]);
+        compressed
+    }
+
+    fn decompress(&self, bytes: &[u8], size: usize) -> Result<Vec<u8>, Error> {
+        let mut reader = Reader::build(bytes);
+        let mut inflated = buffer(bytes, size);
+        while inflated.len() < size {
+            match reader.byte()? {
+                control @ 0..=127 => inflated.extend_from_slice(reader.take(control as usize + 1)?),
+                control => {
+                    let length = (control & 0x7F) as usize + MIN_MATCH;
+                    let distance = u16::from_le_bytes(reader.take(2)?.try_into().unwrap()) as usize;
+                    if distance == 0 || distance > inflated.len() {
+                        return Err(Error::CorruptedPage(format!(
+                            "Back reference {} is out of the page",
+                            distance
+                        )));
+                    }
+                    let start = inflated.len() - distance;
+                    for offset in 0..length {
+                        inflated.push(inflated[start + offset]);
+                    }
+                }
+            }
+        }
+        checked(inflated, size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_test_bytes() -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        for row in 0..40_u8 {
+            bytes.extend_from_slice(&[0, 0b0100]);
+            bytes.extend_from_slice(&[row, 0, 0, 0, 0, 0, 0, 0]);
+            bytes.extend_from_slice(b"bronze");
+        }
+        bytes.extend_from_slice(&[0; 300]);
+        bytes.extend_from_slice(&[7, 3, 250]);
+        bytes
+    }
+
+    #[test]
+    fn codecs_should_roundtrip_bytes() {
+        let bytes = get_test_bytes();
+        for compression in [RAW, RUN_LENGTH, DICTIONARY, LZ] {
+            let codec = codec(compression).unwrap();
+            let mut compressed = codec.compress(&bytes);
+            compressed.extend_from_slice(&[0; 16]);
+            assert_eq!(codec.decompress(&compressed, bytes.len()).unwrap(), bytes);
+            assert!(codec
+                .decompress(&codec.compress(&[]), 0)
+                .unwrap()
+                .is_empty());
+        }
+    }
+
+    #[test]
+    fn codecs_should_shrink_repetitive_bytes() {
+        let bytes = get_test_bytes();
+        for compression in [RUN_LENGTH, DICTIONARY, LZ] {
+            assert!(codec(compression).unwrap().compress(&bytes).len() < bytes.len() * 2 / 3);
+        }
+    }
+
+    #[test]
+    fn run_length_should_encode_runs_and_literals() {
+        assert_eq!(
+            RunLength.compress(&[1, 2, 3, 3, 3, 3]),
+            vec![1, 1, 2, 130, 3]
+        );
+    }
+
+    #[test]
+    fn lz_should_reference_previous_bytes() {
+        assert_eq!(
+            Lz.compress(b"abcdabcdabcd"),
+            vec![3, 97, 98, 99, 100, 0x80 | 4, 4, 0]
+        );
+    }
+
+    #[test]
+    fn decompress_should_not_trust_size() {
+        for compression in [RAW, RUN_LENGTH, DICTIONARY, LZ] {
+            assert!(matches!(
+                codec(compression)
+                    .unwrap()
+                    .decompress(&[0, 1], u32::MAX as usize),
+                Err(Error::CorruptedPage(_))
+            ));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn codec_should_panic_if_unknown() {
+        codec(42).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn decompress_should_panic_if_truncated() {
+        let bytes = get_test_bytes();
+        let compressed = Lz.compress(&bytes);
+        Lz.decompress(&compressed[..compressed.len() / 2], bytes.len())
+            .unwrap();
+    }
+}
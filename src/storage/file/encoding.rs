@@ -1,12 +1,13 @@
 use serde::{Deserialize, Serialize};
 
 use crate::storage::file::error::Error;
+use crate::storage::schema::Schema;
 
 pub trait Encoding: Serialize + for<'de> Deserialize<'de> {
     fn as_bytes(&self) -> Result<Vec<u8>, Error> {
         Ok(bincode::serialize(&self)?)
     }
-    fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+    fn from_bytes(bytes: &[u8], _schema: Option<&Schema>) -> Result<Self, Error> {
         Ok(bincode::deserialize(bytes)?)
     }
     fn bytes_size(&self) -> Result<usize, Error> {
@@ -1,12 +1,24 @@
 use std::fmt;
 
+use crate::storage::file::file_header::FORMAT_VERSION;
+use crate::storage::schema;
+
 pub enum Error {
     PageOverflow(String),
     InvalidIndex(u32),
-    InvalidSlot((u32, u32)),
+    InvalidSlot(u32),
     CorruptedTuple(String),
+    CorruptedPage(String),
+    UnknownCodec(u8),
+    ChecksumMismatch {
+        file: Option<String>,
+        page: Option<u32>,
+    },
+    UnsupportedVersion([u8; 3]),
+    OutdatedVersion([u8; 3]),
     MissingSchema,
     Bincode(Box<bincode::ErrorKind>),
+    Schema(schema::error::Error),
 }
 
 impl fmt::Display for Error {
@@ -14,10 +26,49 @@ impl fmt::Display for Error {
         match self {
             Error::PageOverflow(ref msg) => write!(f, "{}", msg),
             Error::InvalidIndex(ref msg) => write!(f, "{:?} not found", msg),
-            Error::InvalidSlot(ref msg) => write!(f, "{:?} not found", msg),
+            Error::InvalidSlot(ref msg) => write!(f, "Slot {} not found", msg),
             Error::CorruptedTuple(ref msg) => write!(f, "{}", msg),
+            Error::CorruptedPage(ref msg) => write!(f, "{}", msg),
+            Error::UnknownCodec(ref msg) => write!(f, "Unknown compression codec {}", msg),
+            Error::ChecksumMismatch { ref file, page } => {
+                write!(f, "Checksum mismatch")?;
+                if let Some(file) = file {
+                    write!(f, " in file {}", file)?;
+                }
+                match page {
+                    Some(page) => write!(f, " on page {}", page),
+                    None => Ok(()),
+                }
+            }
+            Error::UnsupportedVersion(ref version) => write!(
+                f,
+                "File format version {:?} is newer than the supported {:?}",
+                version, FORMAT_VERSION
+            ),
+            Error::OutdatedVersion(ref version) => write!(
+                f,
+                "File format version {:?} is older than {:?}, upgrade the table first",
+                version, FORMAT_VERSION
+            ),
             Error::MissingSchema => write!(f, "Need a schema to read these bytes"),
             Error::Bincode(ref err) => write!(f, "Bincode error {}", err),
+            Error::Schema(ref err) => write!(f, "Schema error {}", err),
+        }
+    }
+}
+
+impl Error {
+    /// Fills in where a checksum mismatch happened, once the caller knows it.
+    pub fn locate(self, file: Option<&str>, page: Option<u32>) -> Error {
+        match self {
+            Error::ChecksumMismatch {
+                file: found_file,
+                page: found_page,
+            } => Error::ChecksumMismatch {
+                file: found_file.or(file.map(str::to_string)),
+                page: found_page.or(page),
+            },
+            error => error,
         }
     }
 }
@@ -35,3 +86,9 @@ impl From<Box<bincode::ErrorKind>> for Error {
         Error::Bincode(value)
     }
 }
+
+impl From<schema::error::Error> for Error {
+    fn from(value: schema::error::Error) -> Self {
+        Error::Schema(value)
+    }
+}
@@ -1,6 +1,17 @@
+use std::cmp::Ordering;
+
 use crate::storage::file;
+use crate::storage::file::encoding::Encoding;
+use crc32fast::hash;
 use serde::{Deserialize, Serialize};
 
+/// Layout written by this crate, any older version is the legacy layout without page directory.
+pub const FORMAT_VERSION: [u8; 3] = [1, 0, 0];
+pub const FILE_HEADER_SIZE: u32 = 17;
+/// Header of legacy files, the current one appends a checksum.
+pub const LEGACY_FILE_HEADER_SIZE: u32 = 13;
+pub const PAGE_ENTRY_SIZE: u32 = 12;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FileHeader {
     pub file_size: u32,
@@ -8,40 +19,207 @@ pub struct FileHeader {
     pub visibility: u8,
     pub compression: u8,
     pub version: [u8; 3],
+    pub checksum: u32,
 }
 
 impl FileHeader {
-    pub fn build(file_size: u32, compression: u8, version: [u8; 3]) -> FileHeader {
+    pub fn build(file_size: u32, compression: u8) -> FileHeader {
         FileHeader {
             file_size,
             pages: 0,
             visibility: 0,
             compression,
-            version,
+            version: FORMAT_VERSION,
+            checksum: 0,
+        }
+    }
+
+    pub fn from_legacy_bytes(bytes: &[u8]) -> Result<FileHeader, file::error::Error> {
+        let mut header = bytes
+            .get(..LEGACY_FILE_HEADER_SIZE as usize)
+            .ok_or(file::error::Error::CorruptedPage(
+                "File header is truncated".to_string(),
+            ))?
+            .to_vec();
+        header.extend_from_slice(&0_u32.to_le_bytes());
+        FileHeader::from_bytes(&header, None)
+    }
+
+    /// Reads the header of a file of any version, legacy ones stop before the checksum.
+    pub fn decode(bytes: &[u8]) -> Result<FileHeader, file::error::Error> {
+        let header = FileHeader::from_legacy_bytes(bytes)?;
+        match header.version.cmp(&FORMAT_VERSION) {
+            Ordering::Greater => Err(file::error::Error::UnsupportedVersion(header.version)),
+            Ordering::Less => Ok(header),
+            Ordering::Equal => {
+                FileHeader::from_bytes(
+                    bytes.get(..FILE_HEADER_SIZE as usize).ok_or(
+                        file::error::Error::CorruptedPage("File header is truncated".to_string()),
+                    )?,
+                    None,
+                )
+            }
+        }
+    }
+
+    pub fn directory_size(&self) -> Result<u32, file::error::Error> {
+        self.pages
+            .checked_mul(PAGE_ENTRY_SIZE)
+            .filter(|size| size.checked_add(FILE_HEADER_SIZE).is_some())
+            .ok_or(file::error::Error::CorruptedPage(format!(
+                "Page directory of {} pages is too large",
+                self.pages
+            )))
+    }
+
+    /// Verifies and reads the page directory following a current header.
+    pub fn decode_directory(&self, directory: &[u8]) -> Result<Vec<PageEntry>, file::error::Error> {
+        if directory.len() != self.directory_size()? as usize {
+            return Err(file::error::Error::CorruptedPage(format!(
+                "Page directory of {} pages is truncated",
+                self.pages
+            )));
+        }
+        if self.compute_checksum(directory)? != self.checksum {
+            return Err(file::error::Error::ChecksumMismatch {
+                file: None,
+                page: None,
+            });
+        }
+        directory
+            .chunks(PAGE_ENTRY_SIZE as usize)
+            .map(|chunk| PageEntry::from_bytes(chunk, None))
+            .collect()
+    }
+
+    /// Covers the header and the page directory, pages carry their own checksum.
+    pub fn compute_checksum(&self, directory: &[u8]) -> Result<u32, file::error::Error> {
+        let mut bytes = FileHeader {
+            checksum: 0,
+            ..self.clone()
         }
+        .as_bytes()?;
+        bytes.extend_from_slice(directory);
+        Ok(hash(&bytes))
+    }
+}
+
+impl Encoding for FileHeader {}
+
+/// Page directory entry, written right after the header, one per page in id order.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PageEntry {
+    pub id: u32,
+    pub offset: u32,
+    pub length: u32,
+}
+
+impl PageEntry {
+    pub fn build(id: u32, offset: u32, length: u32) -> PageEntry {
+        PageEntry { id, offset, length }
     }
 }
 
-impl file::encoding::Encoding for FileHeader {}
+impl Encoding for PageEntry {}
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::storage::file::encoding::Encoding;
 
     #[test]
     fn as_bytes_should_convert_file_header() {
         assert_eq!(
-            FileHeader::build(1000, 3, [0, 12, 54]).as_bytes().unwrap(),
-            [232, 3, 0, 0, 0, 0, 0, 0, 0, 3, 0, 12, 54]
+            FileHeader::build(1000, 3).as_bytes().unwrap(),
+            [232, 3, 0, 0, 0, 0, 0, 0, 0, 3, 1, 0, 0, 0, 0, 0, 0]
         )
     }
 
     #[test]
     fn from_bytes_should_convert_bytes() {
         assert_eq!(
-            FileHeader::from_bytes(&[232, 3, 0, 0, 0, 0, 0, 0, 0, 3, 0, 12, 54]).unwrap(),
-            FileHeader::build(1000, 3, [0, 12, 54])
+            FileHeader::from_bytes(&[232, 3, 0, 0, 0, 0, 0, 0, 0, 3, 1, 0, 0, 0, 0, 0, 0], None)
+                .unwrap(),
+            FileHeader::build(1000, 3)
         )
     }
+
+    #[test]
+    fn from_legacy_bytes_should_read_header_without_checksum() {
+        assert_eq!(
+            FileHeader::from_legacy_bytes(&[232, 3, 0, 0, 0, 0, 0, 0, 0, 3, 0, 12, 54, 9, 9])
+                .unwrap(),
+            FileHeader {
+                version: [0, 12, 54],
+                ..FileHeader::build(1000, 3)
+            }
+        )
+    }
+
+    #[test]
+    fn decode_should_dispatch_on_version() {
+        let bytes = FileHeader::build(1000, 3).as_bytes().unwrap();
+        assert_eq!(
+            FileHeader::decode(&bytes).unwrap(),
+            FileHeader::build(1000, 3)
+        );
+        assert_eq!(
+            FileHeader::decode(&[232, 3, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0])
+                .unwrap()
+                .version,
+            [0, 0, 0]
+        );
+        assert!(FileHeader::decode(&bytes[..13]).is_err());
+        let mut bytes = bytes;
+        bytes[10] = 2;
+        assert!(matches!(
+            FileHeader::decode(&bytes),
+            Err(file::error::Error::UnsupportedVersion([2, 0, 0]))
+        ));
+    }
+
+    #[test]
+    fn decode_directory_should_verify_checksum() {
+        let directory = PageEntry::build(0, 29, 500).as_bytes().unwrap();
+        let mut header = FileHeader {
+            pages: 1,
+            ..FileHeader::build(1000, 3)
+        };
+        header.checksum = header.compute_checksum(&directory).unwrap();
+        assert_eq!(
+            header.decode_directory(&directory).unwrap(),
+            vec![PageEntry::build(0, 29, 500)]
+        );
+        assert!(header.decode_directory(&directory[..6]).is_err());
+        header.pages = u32::MAX;
+        assert!(header.decode_directory(&directory).is_err());
+        header.pages = 1;
+        header.checksum ^= 1;
+        assert!(header.decode_directory(&directory).is_err());
+    }
+
+    #[test]
+    fn compute_checksum_should_cover_header_and_directory() {
+        let mut header = FileHeader::build(1000, 3);
+        let directory = PageEntry::build(0, 29, 500).as_bytes().unwrap();
+        let checksum = header.compute_checksum(&directory).unwrap();
+        header.checksum = checksum;
+        assert_eq!(header.compute_checksum(&directory).unwrap(), checksum);
+        assert_ne!(header.compute_checksum(&[0; 12]).unwrap(), checksum);
+        header.file_size = 2000;
+        assert_ne!(header.compute_checksum(&directory).unwrap(), checksum);
+    }
+
+    #[test]
+    fn page_entry_should_take_fixed_size() {
+        let entry = PageEntry::build(2, 37, 500);
+        assert_eq!(
+            entry.as_bytes().unwrap(),
+            [2, 0, 0, 0, 37, 0, 0, 0, 244, 1, 0, 0]
+        );
+        assert_eq!(entry.bytes_size().unwrap() as u32, PAGE_ENTRY_SIZE);
+        assert_eq!(
+            FileHeader::build(1000, 3).bytes_size().unwrap() as u32,
+            FILE_HEADER_SIZE
+        );
+    }
 }
@@ -1,46 +1,62 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use crate::storage::file::encoding::Encoding;
 use serde::{Deserialize, Serialize};
 
 use crate::storage::file::error::Error;
-use crate::storage::file::file_header::FileHeader;
+use crate::storage::file::file_header::{
+    FileHeader, PageEntry, FILE_HEADER_SIZE, FORMAT_VERSION, LEGACY_FILE_HEADER_SIZE,
+    PAGE_ENTRY_SIZE,
+};
 use crate::storage::file::page::Page;
-use crate::storage::file::page_header::PageHeader;
+use crate::storage::file::page_header::{PageHeader, PAGE_HEADER_SIZE};
+use crate::storage::file::rows::Rows;
+use crate::storage::file::slot::{RowId, Slot};
+use crate::storage::schema::Schema;
 
+pub mod codec;
 pub mod encoding;
 pub mod error;
 pub mod file_header;
 pub mod page;
 pub mod page_header;
+pub mod rows;
+pub mod slot;
 pub mod tuple;
 pub mod tuple_header;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct File {
     header: FileHeader,
-    pages: HashMap<u32, Page>,
+    pages: BTreeMap<u32, Page>,
 }
 
 impl File {
-    pub fn build(file_size: u32, compression: u8, version: [u8; 3]) -> File {
+    pub fn build(file_size: u32, compression: u8) -> File {
         File {
-            header: FileHeader::build(file_size, compression, version),
-            pages: HashMap::new(),
+            header: FileHeader::build(file_size, compression),
+            pages: BTreeMap::new(),
         }
     }
 
-    pub fn insert_page(&mut self, page: &Page) -> Result<(), Error> {
-        let page_index = self.pages.len() as u32;
-        if self.header.bytes_size()? as u32 + (page_index + 1) * page.header.page_size
+    pub fn insert_page(&mut self, page: &Page) -> Result<u32, Error> {
+        let reserved: u32 = self
+            .pages
+            .values()
+            .map(|page| PAGE_ENTRY_SIZE + page.header.page_size)
+            .sum();
+        if FILE_HEADER_SIZE + reserved + PAGE_ENTRY_SIZE + page.header.page_size
             > self.header.file_size
         {
             Err(Error::PageOverflow(
                 "Insertion failed, no more place on this file.".to_string(),
             ))
         } else {
+            let page_index = self.pages.keys().next_back().map_or(0, |index| index + 1);
             self.pages.insert(page_index, page.clone());
-            Ok(())
+            self.header.pages = self.pages.len() as u32;
+            Ok(page_index)
         }
     }
 
@@ -48,6 +64,7 @@ impl File {
         self.pages
             .remove(&index)
             .ok_or(Error::InvalidIndex(index))?;
+        self.header.pages = self.pages.len() as u32;
         Ok(())
     }
 
@@ -69,44 +86,186 @@ impl File {
         }
         Ok(pages)
     }
+
+    pub fn vacuum(&mut self) -> Result<(), Error> {
+        let mut reachable: HashSet<RowId> = HashSet::new();
+        for page in self.pages.values() {
+            for slot in &page.slots {
+                if let Slot::Forward(target) = slot {
+                    if let Ok(chain) = rows::read_chain(self, *target) {
+                        reachable.extend(chain.into_iter().map(|(row, _)| row));
+                    }
+                }
+            }
+        }
+        for (index, page) in self.pages.iter_mut() {
+            let orphans: Vec<u32> = (0_u32..)
+                .zip(&page.slots)
+                .filter(|(slot, record)| {
+                    matches!(record, Slot::Chunk { .. })
+                        && !reachable.contains(&RowId::build(*index, *slot))
+                })
+                .map(|(slot, _)| slot)
+                .collect();
+            page.delete_by_slots(&orphans)?;
+            page.compact();
+        }
+        Ok(())
+    }
+
+    /// Legacy files chain pages of the first page size right after the header.
+    pub fn from_legacy_bytes(bytes: &[u8]) -> Result<File, Error> {
+        let mut pages: BTreeMap<u32, Page> = BTreeMap::new();
+        let header = FileHeader::from_legacy_bytes(bytes)?;
+        let data = &bytes[LEGACY_FILE_HEADER_SIZE as usize..];
+        if let Some(page_header) = data.get(..PAGE_HEADER_SIZE as usize) {
+            let page_size = PageHeader::from_bytes(page_header, None)?.page_size as usize;
+            if !(PAGE_HEADER_SIZE as usize..=data.len()).contains(&page_size) {
+                return Err(Error::CorruptedPage(format!(
+                    "Page size {} does not fit the file",
+                    page_size
+                )));
+            }
+            for (index, chunk) in (0_u32..).zip(data.chunks(page_size)) {
+                pages.insert(
+                    index,
+                    Page::from_legacy_bytes(chunk).map_err(|e| e.locate(None, Some(index)))?,
+                );
+            }
+        }
+        let header = FileHeader {
+            pages: pages.len() as u32,
+            version: FORMAT_VERSION,
+            ..header
+        };
+        Ok(File { header, pages })
+    }
+}
+
+impl Rows for File {
+    type Error = Error;
+
+    fn page(&self, index: u32) -> Result<Cow<'_, Page>, Error> {
+        self.pages
+            .get(&index)
+            .map(Cow::Borrowed)
+            .ok_or(Error::InvalidIndex(index))
+    }
+
+    fn page_mut(&mut self, index: u32) -> Result<&mut Page, Error> {
+        self.pages.get_mut(&index).ok_or(Error::InvalidIndex(index))
+    }
+
+    fn page_ids(&self) -> Result<Vec<u32>, Error> {
+        Ok(self.pages.keys().copied().collect())
+    }
+
+    fn append_page(&mut self) -> Result<u32, Error> {
+        let page_size = self
+            .pages
+            .values()
+            .next()
+            .ok_or(Error::PageOverflow(
+                "Insertion failed, this file has no page.".to_string(),
+            ))?
+            .header
+            .page_size;
+        self.insert_page(&Page::build(page_size, self.header.compression)?)
+    }
+
+    fn max_tuple_size(&self) -> Result<u32, Error> {
+        Ok(self
+            .pages
+            .values()
+            .map(Page::max_tuple_size)
+            .min()
+            .unwrap_or(0))
+    }
 }
 
 impl Encoding for File {
     fn as_bytes(&self) -> Result<Vec<u8>, Error> {
-        let mut concat_bytes: Vec<u8> = Vec::new();
-        concat_bytes.extend_from_slice(&self.header.as_bytes()?);
-        self.pages
-            .iter()
-            .for_each(|(_, v)| concat_bytes.extend_from_slice(&v.as_bytes().unwrap()));
+        let mut header = FileHeader {
+            pages: self.pages.len() as u32,
+            ..self.header.clone()
+        };
+        let mut directory: Vec<u8> = Vec::new();
+        let mut data: Vec<u8> = Vec::new();
+        let mut offset = FILE_HEADER_SIZE + header.pages * PAGE_ENTRY_SIZE;
+        for (index, page) in &self.pages {
+            let bytes = page.as_bytes()?;
+            directory.extend(PageEntry::build(*index, offset, bytes.len() as u32).as_bytes()?);
+            offset += bytes.len() as u32;
+            data.extend(bytes);
+        }
+        header.checksum = header.compute_checksum(&directory)?;
+        let mut concat_bytes = header.as_bytes()?;
+        concat_bytes.extend(directory);
+        concat_bytes.extend(data);
         Ok(concat_bytes)
     }
 
-    fn from_bytes(bytes: &[u8]) -> Result<File, Error> {
-        let mut pages: HashMap<u32, Page> = HashMap::new();
-        let header = FileHeader::from_bytes(&bytes[..13])?;
-        let page_size = PageHeader::from_bytes(&bytes[13..27])?.page_size as usize;
-        let chunks = bytes[13..].chunks(page_size);
-        for (index, chunk) in (0_u32..).zip(chunks) {
-            let page = Page::from_bytes(chunk)?;
-            pages.insert(index, page);
+    fn from_bytes(bytes: &[u8], schema: Option<&Schema>) -> Result<File, Error> {
+        let header = FileHeader::decode(bytes)?;
+        if header.version != FORMAT_VERSION {
+            return File::from_legacy_bytes(bytes);
         }
+        let schema = schema.ok_or(Error::MissingSchema)?;
+        let directory = bytes
+            .get(FILE_HEADER_SIZE as usize..)
+            .and_then(|directory| directory.get(..header.directory_size().ok()? as usize))
+            .ok_or(Error::CorruptedPage(format!(
+                "Page directory of {} pages is truncated",
+                header.pages
+            )))?;
+        let mut pages: BTreeMap<u32, Page> = BTreeMap::new();
+        for entry in header.decode_directory(directory)? {
+            let page = entry
+                .offset
+                .checked_add(entry.length)
+                .and_then(|end| bytes.get(entry.offset as usize..end as usize))
+                .ok_or(Error::CorruptedPage(format!(
+                    "Page {} is out of the file",
+                    entry.id
+                )))?;
+            let page =
+                Page::from_bytes(page, Some(schema)).map_err(|e| e.locate(None, Some(entry.id)))?;
+            pages.insert(entry.id, page);
+        }
+        let header = FileHeader {
+            checksum: 0,
+            ..header
+        };
         Ok(File { header, pages })
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub mod tests {
     use crate::storage::file::page::tests::get_test_page;
+    use crate::storage::file::tuple::Tuple;
+    use crate::storage::schema::encoding::Encoding as SchemaEncoding;
+    use crate::storage::schema::value::Value;
+    use crate::storage::tests::get_test_schema;
 
     use super::*;
 
     fn get_test_file() -> File {
-        let mut file = File::build(500 * 10 + 10, 0, [0, 10, 28]);
-        file.pages.insert(0, get_test_page());
+        let mut file = File::build(500 * 10 + 10, 0);
+        file.insert_page(&get_test_page()).unwrap();
         file
     }
 
     fn get_test_bytes() -> Vec<u8> {
+        vec![
+            146, 19, 0, 0, 1, 0, 0, 0, 0, 0, 1, 0, 0, 91, 125, 49, 181, 0, 0, 0, 0, 29, 0, 0, 0,
+            58, 0, 0, 0, 244, 1, 0, 0, 3, 0, 0, 0, 165, 91, 108, 19, 0, 1, 1, 78, 1, 128, 0, 0, 19,
+            129, 0, 1, 190, 1, 128, 0, 0, 35, 129, 0, 0, 234, 129, 0, 0, 27, 255, 0, 197, 0, 0, 8,
+            151, 65, 200, 0, 0, 1, 143, 8, 221, 0, 159, 2, 145, 0,
+        ]
+    }
+
+    pub fn get_legacy_test_bytes() -> Vec<u8> {
         vec![
             146, 19, 0, 0, 0, 0, 0, 0, 0, 0, 0, 10, 28, 244, 1, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 0, 1,
             78, 1, 0, 0, 38, 0, 0, 0, 190, 1, 0, 0, 54, 0, 0, 0, 234, 0, 0, 0, 46, 0, 0, 0, 0, 0,
@@ -133,7 +292,11 @@ mod tests {
     #[test]
     fn as_bytes_should_convert_file() {
         assert_eq!(
-            File::from_bytes(&get_test_file().as_bytes().unwrap()).unwrap(),
+            File::from_bytes(
+                &get_test_file().as_bytes().unwrap(),
+                Some(&get_test_schema())
+            )
+            .unwrap(),
             get_test_file()
         )
     }
@@ -141,11 +304,303 @@ mod tests {
     #[test]
     fn from_bytes_should_convert_bytes() {
         assert_eq!(
-            File::from_bytes(&get_test_bytes()).unwrap(),
+            File::from_bytes(&get_test_bytes(), Some(&get_test_schema())).unwrap(),
             get_test_file()
         )
     }
 
+    #[test]
+    fn as_bytes_should_be_deterministic() {
+        let mut file = get_test_file();
+        file.insert_page(&Page::build(200, 0).unwrap()).unwrap();
+        file.insert_page(&get_test_page()).unwrap();
+        file.delete_by_index(1).unwrap();
+        let bytes = file.as_bytes().unwrap();
+        assert_eq!(bytes, file.clone().as_bytes().unwrap());
+        let decoded = File::from_bytes(&bytes, Some(&get_test_schema())).unwrap();
+        assert_eq!(decoded, file);
+        assert_eq!(decoded.as_bytes().unwrap(), bytes);
+        assert_eq!(
+            decoded.pages.keys().copied().collect::<Vec<u32>>(),
+            vec![0, 2]
+        );
+    }
+
+    #[test]
+    fn from_bytes_should_read_mixed_page_sizes() {
+        let mut file = get_test_file();
+        let mut page = Page::build(120, 0).unwrap();
+        page.insert(Tuple::build(&get_test_schema(), &[0b1011], &[1]).unwrap())
+            .unwrap();
+        file.insert_page(&page).unwrap();
+        let bytes = file.as_bytes().unwrap();
+        assert_eq!(bytes.len(), 17 + 12 * 2 + 58 + 120);
+        assert_eq!(
+            File::from_bytes(&bytes, Some(&get_test_schema())).unwrap(),
+            file
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_bytes_should_panic_if_page_out_of_file() {
+        let bytes = get_test_bytes();
+        File::from_bytes(&bytes[..bytes.len() - 1], Some(&get_test_schema())).unwrap();
+    }
+
+    #[test]
+    fn from_bytes_should_locate_checksum_mismatch() {
+        let mut bytes = get_test_bytes();
+        bytes[17] = 5;
+        assert!(matches!(
+            File::from_bytes(&bytes, Some(&get_test_schema())),
+            Err(Error::ChecksumMismatch { page: None, .. })
+        ));
+        let mut bytes = get_test_bytes();
+        let length = bytes.len();
+        bytes[length - 3] ^= 1;
+        assert!(matches!(
+            File::from_bytes(&bytes, Some(&get_test_schema())),
+            Err(Error::ChecksumMismatch { page: Some(0), .. })
+        ));
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_bytes_should_panic_if_future_version() {
+        let mut bytes = get_test_bytes();
+        bytes[10] = 2;
+        File::from_bytes(&bytes, Some(&get_test_schema())).unwrap();
+    }
+
+    #[test]
+    fn from_legacy_bytes_should_repack_tuples() {
+        assert_eq!(
+            File::from_legacy_bytes(&get_legacy_test_bytes()).unwrap(),
+            get_test_file()
+        )
+    }
+
+    #[test]
+    fn from_bytes_should_read_older_versions_as_legacy() {
+        let mut bytes = get_legacy_test_bytes();
+        assert_eq!(
+            File::from_bytes(&bytes, Some(&get_test_schema())).unwrap(),
+            get_test_file()
+        );
+        bytes[10..13].copy_from_slice(&[0, 1, 0]);
+        assert_eq!(File::from_bytes(&bytes, None).unwrap(), get_test_file());
+    }
+
+    #[test]
+    fn from_legacy_bytes_should_reject_corrupted_page_sizes() {
+        let mut bytes = get_legacy_test_bytes();
+        for page_size in [0_u32, u32::MAX] {
+            bytes[13..17].copy_from_slice(&page_size.to_le_bytes());
+            assert!(matches!(
+                File::from_legacy_bytes(&bytes),
+                Err(Error::CorruptedPage(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn from_bytes_should_reject_corrupted_directories() {
+        let mut file = get_test_file();
+        file.header.pages = u32::MAX;
+        let bytes = file.header.as_bytes().unwrap();
+        assert!(matches!(
+            File::from_bytes(&bytes, Some(&get_test_schema())),
+            Err(Error::CorruptedPage(_))
+        ));
+        let mut header = FileHeader {
+            pages: 1,
+            ..get_test_file().header
+        };
+        let entry = PageEntry::build(0, u32::MAX, 10).as_bytes().unwrap();
+        header.checksum = header.compute_checksum(&entry).unwrap();
+        let mut bytes = header.as_bytes().unwrap();
+        bytes.extend(entry);
+        assert!(matches!(
+            File::from_bytes(&bytes, Some(&get_test_schema())),
+            Err(Error::CorruptedPage(_))
+        ));
+    }
+
+    fn get_row_test_file() -> (File, RowId) {
+        let mut file = File::build(17 + (12 + 60) * 2, 0);
+        file.insert_page(&Page::build(60, 1).unwrap()).unwrap();
+        file.insert_page(&Page::build(60, 1).unwrap()).unwrap();
+        let rows: Vec<RowId> = (0..2)
+            .map(|_| {
+                file.insert_tuple(Tuple::build(&get_test_schema(), &[0b1011], &[1]).unwrap())
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(rows, vec![RowId::build(0, 0), RowId::build(0, 1)]);
+        (file, rows[0])
+    }
+
+    #[test]
+    fn update_tuple_should_forward_relocated_tuple() {
+        let (mut file, row) = get_row_test_file();
+        let tuple = Tuple::build(&get_test_schema(), &[0], &[18; 33]).unwrap();
+        file.update_tuple(row, tuple.clone()).unwrap();
+        assert_eq!(
+            file.pages[&0].get_slot(0).unwrap(),
+            &Slot::Forward(RowId::build(1, 0))
+        );
+        assert_eq!(file.read_tuple(row, &get_test_schema()).unwrap(), tuple);
+        let tuple = Tuple::build(&get_test_schema(), &[0], &[7; 33]).unwrap();
+        file.update_tuple(row, tuple.clone()).unwrap();
+        assert_eq!(file.read_tuple(row, &get_test_schema()).unwrap(), tuple);
+        assert_eq!(file.pages[&1].tuples().count(), 1);
+    }
+
+    #[test]
+    fn delete_tuple_should_remove_forwarded_tuple() {
+        let (mut file, row) = get_row_test_file();
+        file.update_tuple(
+            row,
+            Tuple::build(&get_test_schema(), &[0], &[18; 33]).unwrap(),
+        )
+        .unwrap();
+        file.delete_tuple(row).unwrap();
+        assert_eq!(file.pages[&0].slots[0], Slot::Free);
+        assert_eq!(file.pages[&1].slots, vec![Slot::Free]);
+        assert!(file.read_tuple(row, &get_test_schema()).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn update_tuple_should_panic_if_file_full() {
+        let (mut file, row) = get_row_test_file();
+        file.insert_tuple(Tuple::build(&get_test_schema(), &[0], &[18; 33]).unwrap())
+            .unwrap();
+        file.update_tuple(
+            row,
+            Tuple::build(&get_test_schema(), &[0], &[18; 33]).unwrap(),
+        )
+        .unwrap();
+    }
+
+    fn get_overflow_test_file() -> (File, Schema, RowId, Tuple) {
+        let schema = Schema::from_str("id INT, name STRING").unwrap();
+        let mut file = File::build(17 + (12 + 64) * 6, 0);
+        file.insert_page(&Page::build(64, 1).unwrap()).unwrap();
+        let tuple = Tuple::from_values(
+            &schema,
+            &[Some(Value::Int(1)), Some(Value::String("a".repeat(100)))],
+        )
+        .unwrap();
+        let row = file.insert_tuple(tuple.clone()).unwrap();
+        (file, schema, row, tuple)
+    }
+
+    #[test]
+    fn insert_tuple_should_chain_large_tuple() {
+        let (file, schema, row, tuple) = get_overflow_test_file();
+        assert_eq!(file.pages.len(), 4);
+        assert!(matches!(
+            file.pages[&row.page].get_slot(row.slot).unwrap(),
+            Slot::Forward(_)
+        ));
+        assert_eq!(file.read_tuple(row, &schema).unwrap(), tuple);
+        assert_eq!(file.rows(&schema).unwrap(), vec![(row, tuple.clone())]);
+        let bytes = file.as_bytes().unwrap();
+        assert_eq!(
+            File::from_bytes(&bytes, Some(&schema))
+                .unwrap()
+                .read_tuple(row, &schema)
+                .unwrap(),
+            tuple
+        );
+    }
+
+    #[test]
+    fn update_tuple_should_free_chain() {
+        let (mut file, schema, row, _) = get_overflow_test_file();
+        let tuple = Tuple::from_values(
+            &schema,
+            &[Some(Value::Int(2)), Some(Value::String("b".to_string()))],
+        )
+        .unwrap();
+        file.update_tuple(row, tuple.clone()).unwrap();
+        assert_eq!(file.read_tuple(row, &schema).unwrap(), tuple);
+        assert!(file
+            .pages
+            .values()
+            .flat_map(|page| &page.slots)
+            .all(|slot| !matches!(slot, Slot::Chunk { .. })));
+    }
+
+    #[test]
+    fn delete_tuple_should_free_chain() {
+        let (mut file, schema, row, _) = get_overflow_test_file();
+        file.delete_tuple(row).unwrap();
+        assert!(file
+            .pages
+            .values()
+            .flat_map(|page| &page.slots)
+            .all(|slot| *slot == Slot::Free));
+        assert_eq!(file.rows(&schema).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn visit_rows_should_stop_at_first_error() {
+        let (mut file, schema, _, tuple) = get_overflow_test_file();
+        let small = Tuple::from_values(
+            &schema,
+            &[Some(Value::Int(2)), Some(Value::String("b".to_string()))],
+        )
+        .unwrap();
+        file.insert_tuple(small.clone()).unwrap();
+        let mut visited: Vec<Tuple> = Vec::new();
+        file.visit_rows(&schema, &mut |_, tuple| {
+            visited.push(tuple.clone());
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(visited.len(), 2);
+        assert!(visited.contains(&tuple) && visited.contains(&small));
+        let mut count = 0;
+        assert!(file
+            .visit_rows(&schema, &mut |visited, _| {
+                count += 1;
+                Err(Error::InvalidSlot(visited.slot))
+            })
+            .is_err());
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn vacuum_should_free_unreachable_chunks() {
+        let (mut file, schema, row, tuple) = get_overflow_test_file();
+        let orphan = rows::place(&mut file, None, 11, |page| {
+            page.insert_chunk(&[1, 2, 3], None)
+        })
+        .unwrap();
+        file.vacuum().unwrap();
+        assert!(file.pages[&orphan.page].get_slot(orphan.slot).is_err());
+        assert_eq!(file.read_tuple(row, &schema).unwrap(), tuple);
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_tuple_should_panic_if_chain_dont_fit() {
+        let schema = Schema::from_str("id INT, name STRING").unwrap();
+        let mut file = File::build(17 + (12 + 64) * 2, 0);
+        file.insert_page(&Page::build(64, 1).unwrap()).unwrap();
+        file.insert_tuple(
+            Tuple::from_values(
+                &schema,
+                &[Some(Value::Int(1)), Some(Value::String("a".repeat(100)))],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    }
+
     #[test]
     fn insert_page_should_push_in_hashmap() {
         let mut file = get_test_file();
@@ -1,177 +1,488 @@
+use std::cmp::Reverse;
 use std::collections::HashMap;
+use std::mem;
+use std::ops::Range;
 
 use crc32fast::hash;
 use serde::{Deserialize, Serialize};
 
+use crate::storage::file::codec::{codec, RAW};
 use crate::storage::file::encoding::Encoding;
 use crate::storage::file::error::Error;
-use crate::storage::file::page_header::PageHeader;
+use crate::storage::file::page_header::{PageHeader, PAGE_HEADER_SIZE};
+use crate::storage::file::slot::{RowId, Slot, CHUNK_HEADER_SIZE, SLOT_SIZE};
 use crate::storage::file::tuple::Tuple;
+use crate::storage::schema::Schema;
+
+const CHECKSUM_RANGE: Range<usize> = 8..12;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Page {
     pub header: PageHeader,
-    pub tuples: HashMap<(u32, u32), Tuple>,
+    pub slots: Vec<Slot>,
 }
 
 impl Page {
     pub fn build(page_size: u32, compression: u8) -> Result<Page, Error> {
+        codec(compression)?;
         let header = PageHeader::build(page_size, compression);
         Ok(Page {
             header,
-            tuples: HashMap::new(),
+            slots: Vec::new(),
         })
     }
 
-    fn get_free_slots(&self) -> Result<Vec<(u32, u32)>, Error> {
-        let slots: Vec<(u32, u32)> = self.tuples.keys().cloned().collect();
-        let mut slots: Vec<u32> = slots
+    fn directory_end(&self) -> u32 {
+        PAGE_HEADER_SIZE + self.slots.len() as u32 * SLOT_SIZE
+    }
+
+    fn get_free_spaces(&self) -> Vec<(u32, u32)> {
+        let mut regions: Vec<(u32, u32)> = self.slots.iter().filter_map(Slot::region).collect();
+        regions.sort();
+        regions.push((self.header.page_size, 0));
+        let mut start = self.directory_end();
+        let mut free_spaces: Vec<(u32, u32)> = Vec::new();
+        for (offset, length) in regions {
+            if offset > start {
+                free_spaces.push((start, offset - start));
+            }
+            start = start.max(offset + length);
+        }
+        free_spaces
+    }
+
+    fn find_space(&self, size: u32, new_slot: bool) -> Option<u32> {
+        let directory_end = self.directory_end();
+        let free_spaces = self.get_free_spaces();
+        let directory_space = free_spaces
+            .first()
+            .is_some_and(|(offset, length)| *offset == directory_end && *length >= SLOT_SIZE);
+        if new_slot && !directory_space {
+            return None;
+        }
+        free_spaces
             .into_iter()
-            .flat_map(|(offset, length)| vec![offset, offset + length])
+            .map(
+                |(offset, length)| match offset == directory_end && new_slot {
+                    true => (offset + SLOT_SIZE, length.saturating_sub(SLOT_SIZE)),
+                    false => (offset, length),
+                },
+            )
+            .filter(|(_, length)| *length >= size)
+            .min_by_key(|(_, length)| *length)
+            .map(|(offset, length)| offset + length - size)
+    }
+
+    /// Whether `size` bytes fit once the page is compacted.
+    fn fits_compacted(&self, size: u32, new_slot: bool) -> bool {
+        let used: u32 = self
+            .slots
+            .iter()
+            .filter_map(|slot| Some(slot.region()?.1))
+            .sum();
+        let directory_end = self.directory_end() + if new_slot { SLOT_SIZE } else { 0 };
+        self.header.page_size >= directory_end + used + size
+    }
+
+    fn allocate(&mut self, size: u32, new_slot: bool) -> Option<u32> {
+        match self.find_space(size, new_slot) {
+            None if self.fits_compacted(size, new_slot) => {
+                self.compact();
+                self.find_space(size, new_slot)
+            }
+            offset => offset,
+        }
+    }
+
+    pub fn compact(&mut self) {
+        let mut regions: Vec<&mut Slot> = self
+            .slots
+            .iter_mut()
+            .filter(|slot| slot.region().is_some())
             .collect();
-        slots.sort();
-        if !slots.contains(&self.header.page_size) {
-            slots.push(self.header.page_size);
+        regions.sort_by_key(|slot| Reverse(slot.region()));
+        let mut end = self.header.page_size;
+        for slot in regions {
+            if let Slot::Tuple { offset, length, .. } | Slot::Chunk { offset, length, .. } = slot {
+                end -= *length;
+                *offset = end;
+            }
         }
-        if !slots.contains(&(14 + self.header.slots * 8)) {
-            slots.insert(0, 14 + self.header.slots * 8)
-        } else {
-            slots.remove(0);
+    }
+
+    fn overflow() -> Error {
+        Error::PageOverflow("Insertion failed, no more place on this page.".to_string())
+    }
+
+    pub fn max_tuple_size(&self) -> u32 {
+        self.header
+            .page_size
+            .saturating_sub(PAGE_HEADER_SIZE + SLOT_SIZE)
+    }
+
+    /// Whether a slot of `length` bytes can be inserted, leaves the page untouched.
+    pub fn can_fit(&self, length: u32) -> bool {
+        let new_slot = !self.slots.contains(&Slot::Free);
+        self.find_space(length, new_slot).is_some() || self.fits_compacted(length, new_slot)
+    }
+
+    fn place(&mut self, length: u32, build: impl FnOnce(u32) -> Slot) -> Result<u32, Error> {
+        let free_slot = self.slots.iter().position(|slot| *slot == Slot::Free);
+        let offset = self
+            .allocate(length, free_slot.is_none())
+            .ok_or_else(Page::overflow)?;
+        let slot = build(offset);
+        match free_slot {
+            Some(index) => {
+                self.slots[index] = slot;
+                Ok(index as u32)
+            }
+            None => {
+                self.slots.push(slot);
+                self.header.slots = self.slots.len() as u32;
+                Ok(self.header.slots - 1)
+            }
         }
-        let free_slots: Vec<(u32, u32)> = slots
-            .chunks_exact(2)
-            .map(|chunk| (chunk[0], chunk[1] - chunk[0]))
-            .filter(|(_, length)| *length > 0)
-            .collect();
-        Ok(free_slots)
     }
 
-    pub fn insert(&mut self, tuple: Tuple) -> Result<(), Error> {
-        let tuple_size = tuple.bytes_size()? as u32;
-        let mut free_slots: Vec<(u32, u32)> = self
-            .get_free_slots()?
-            .into_iter()
-            .filter(|(_, length)| *length > tuple_size)
-            .collect();
-        if free_slots.is_empty() {
-            Err(Error::PageOverflow(
-                "Insertion failed, no more place on this page.".to_string(),
-            ))
-        } else {
-            free_slots.sort_by_key(|(_, length)| *length);
-            let slot: (u32, u32) = (
-                free_slots.first().unwrap().0 + free_slots.first().unwrap().1 - tuple_size,
-                tuple_size,
-            );
-            self.tuples.insert(slot, tuple);
-            self.header.slots += 1;
-            Ok(())
+    pub fn insert(&mut self, tuple: Tuple) -> Result<u32, Error> {
+        let length = tuple.bytes_size()? as u32;
+        self.place(length, |offset| Slot::Tuple {
+            offset,
+            length,
+            tuple,
+        })
+    }
+
+    pub fn insert_chunk(&mut self, bytes: &[u8], next: Option<RowId>) -> Result<u32, Error> {
+        let length = CHUNK_HEADER_SIZE + bytes.len() as u32;
+        self.place(length, |offset| Slot::Chunk {
+            offset,
+            length,
+            next,
+            bytes: bytes.to_vec(),
+        })
+    }
+
+    pub fn insert_forward(&mut self, row: RowId) -> Result<u32, Error> {
+        self.place(0, |_| Slot::Forward(row))
+    }
+
+    pub fn get_slot(&self, slot: u32) -> Result<&Slot, Error> {
+        match self.slots.get(slot as usize) {
+            None | Some(Slot::Free) => Err(Error::InvalidSlot(slot)),
+            Some(found) => Ok(found),
         }
     }
 
-    pub fn delete_by_slots(&mut self, slots: &[(u32, u32)]) -> Result<(), Error> {
+    pub fn tuples(&self) -> impl Iterator<Item = (u32, &Tuple)> {
+        (0_u32..)
+            .zip(&self.slots)
+            .filter_map(|(index, slot)| Some((index, slot.tuple()?)))
+    }
+
+    pub fn delete_by_slots(&mut self, slots: &[u32]) -> Result<(), Error> {
+        if let Some(slot) = slots
+            .iter()
+            .find(|slot| matches!(self.slots.get(**slot as usize), Some(Slot::Forward(_))))
+        {
+            return Err(Error::InvalidSlot(*slot));
+        }
         for slot in slots {
-            if self.tuples.remove(slot).is_some() {
-                self.header.slots -= 1;
+            if let Some(found) = self.slots.get_mut(*slot as usize) {
+                *found = Slot::Free;
             }
         }
         Ok(())
     }
 
-    pub fn update_by_slot(&mut self, slot: (u32, u32), tuple: Tuple) -> Result<(), Error> {
-        match self.tuples.insert(slot, tuple) {
-            None => Err(Error::InvalidSlot(slot)),
-            Some(_) => Ok(()),
+    pub fn update_by_slot(&mut self, slot: u32, tuple: Tuple) -> Result<(), Error> {
+        match self.get_slot(slot)? {
+            Slot::Tuple { .. } => self.rewrite(slot, tuple),
+            _ => Err(Error::InvalidSlot(slot)),
         }
     }
 
-    pub fn read_by_slots(
-        &self,
-        slots: &[(u32, u32)],
-    ) -> Result<HashMap<(u32, u32), &Tuple>, Error> {
-        let mut tuples: HashMap<(u32, u32), &Tuple> = HashMap::new();
-        for slot in slots {
-            if let Some(tuple) = self.tuples.get(slot) {
-                tuples.insert(*slot, tuple);
+    /// Brings a forwarded tuple back to its slot, the caller frees the returned target.
+    pub fn unforward(&mut self, slot: u32, tuple: Tuple) -> Result<RowId, Error> {
+        match self.get_slot(slot)? {
+            Slot::Forward(target) => {
+                let target = *target;
+                self.rewrite(slot, tuple)?;
+                Ok(target)
             }
+            _ => Err(Error::InvalidSlot(slot)),
+        }
+    }
+
+    /// Frees a forwarding slot, the caller frees the returned target.
+    pub fn delete_forward(&mut self, slot: u32) -> Result<RowId, Error> {
+        match self.get_slot(slot)? {
+            Slot::Forward(target) => {
+                let target = *target;
+                self.slots[slot as usize] = Slot::Free;
+                Ok(target)
+            }
+            _ => Err(Error::InvalidSlot(slot)),
+        }
+    }
+
+    fn rewrite(&mut self, slot: u32, tuple: Tuple) -> Result<(), Error> {
+        let length = tuple.bytes_size()? as u32;
+        let offset = match self.get_slot(slot)? {
+            Slot::Tuple {
+                offset,
+                length: current,
+                ..
+            } if length <= *current => *offset,
+            _ => {
+                let previous = mem::replace(&mut self.slots[slot as usize], Slot::Free);
+                match self.allocate(length, false) {
+                    Some(offset) => offset,
+                    None => {
+                        self.slots[slot as usize] = previous;
+                        return Err(Page::overflow());
+                    }
+                }
+            }
+        };
+        self.slots[slot as usize] = Slot::Tuple {
+            offset,
+            length,
+            tuple,
+        };
+        Ok(())
+    }
+
+    pub fn forward(&mut self, slot: u32, row: RowId) -> Result<(), Error> {
+        if let Slot::Chunk { .. } = self.get_slot(slot)? {
+            return Err(Error::InvalidSlot(slot));
+        }
+        self.slots[slot as usize] = Slot::Forward(row);
+        Ok(())
+    }
+
+    pub fn read_by_slots(&self, slots: &[u32]) -> Result<HashMap<u32, &Tuple>, Error> {
+        Ok(slots
+            .iter()
+            .filter_map(|slot| Some((*slot, self.slots.get(*slot as usize)?.tuple()?)))
+            .collect())
+    }
+
+    pub fn from_legacy_bytes(bytes: &[u8]) -> Result<Page, Error> {
+        Page::read_slots(bytes, |bytes| {
+            let tuple = Tuple::from_legacy_bytes(bytes)?;
+            Ok((tuple.bytes_size()? as u32, tuple))
+        })
+    }
+
+    fn read_slots(
+        bytes: &[u8],
+        read_tuple: impl Fn(&[u8]) -> Result<(u32, Tuple), Error>,
+    ) -> Result<Page, Error> {
+        let header = PageHeader::from_bytes(
+            bytes
+                .get(..PAGE_HEADER_SIZE as usize)
+                .ok_or(Error::CorruptedPage("Page header is truncated".to_string()))?,
+            None,
+        )?;
+        let directory = header
+            .slots
+            .checked_mul(SLOT_SIZE)
+            .and_then(|size| range(bytes, PAGE_HEADER_SIZE, size))
+            .ok_or(Error::CorruptedPage(format!(
+                "Page directory of {} slots is truncated",
+                header.slots
+            )))?;
+        let mut slots: Vec<Slot> = Vec::with_capacity(header.slots as usize);
+        for chunk in directory.chunks(SLOT_SIZE as usize) {
+            let entry: (u32, u32) = (
+                u32::from_le_bytes(chunk[0..4].try_into().unwrap()),
+                u32::from_le_bytes(chunk[4..8].try_into().unwrap()),
+            );
+            slots.push(match (entry, Slot::forward(entry), Slot::chunk(entry)) {
+                ((0, 0), _, _) => Slot::Free,
+                (_, Some(row), _) => Slot::Forward(row),
+                (_, _, Some((offset, length))) => Slot::from_chunk_bytes(
+                    offset,
+                    range(bytes, offset, length)
+                        .filter(|chunk| chunk.len() >= CHUNK_HEADER_SIZE as usize)
+                        .ok_or(Error::CorruptedPage(format!(
+                            "Slot {} is out of the page",
+                            slots.len()
+                        )))?,
+                ),
+                ((offset, length), _, _) => {
+                    let tuple_bytes = range(bytes, offset, length).ok_or(Error::CorruptedPage(
+                        format!("Slot {} is out of the page", slots.len()),
+                    ))?;
+                    let (length, tuple) = read_tuple(tuple_bytes)?;
+                    Slot::Tuple {
+                        offset,
+                        length,
+                        tuple,
+                    }
+                }
+            });
+        }
+        Ok(Page { header, slots })
+    }
+
+    fn inflate(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        let header = bytes
+            .get(..PAGE_HEADER_SIZE as usize)
+            .ok_or(Error::CorruptedPage(format!(
+                "Page {:?} is shorter than its header",
+                bytes
+            )))?;
+        let PageHeader {
+            page_size,
+            compression,
+            ..
+        } = PageHeader::from_bytes(header, None)?;
+        let body_size = page_size
+            .checked_sub(PAGE_HEADER_SIZE)
+            .ok_or(Error::CorruptedPage(format!(
+                "Page size {} is smaller than its header",
+                page_size
+            )))?;
+        let mut page = header.to_vec();
+        page.extend(
+            codec(compression)?
+                .decompress(&bytes[PAGE_HEADER_SIZE as usize..], body_size as usize)?,
+        );
+        Ok(page)
+    }
+
+    /// Uncompressed page with a zeroed checksum field, the bytes the checksum covers.
+    fn image(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = vec![0; self.header.page_size as usize];
+        let header = PageHeader {
+            checksum: 0,
+            ..self.header.clone()
+        };
+        bytes[..PAGE_HEADER_SIZE as usize].copy_from_slice(&header.as_bytes()?);
+        for (index, slot) in self.slots.iter().enumerate() {
+            let (first, second) = slot.entry();
+            let start = (PAGE_HEADER_SIZE + index as u32 * SLOT_SIZE) as usize;
+            bytes[start..start + 4].copy_from_slice(&first.to_le_bytes());
+            bytes[start + 4..start + 8].copy_from_slice(&second.to_le_bytes());
+            let (offset, data) = match slot {
+                Slot::Tuple { offset, tuple, .. } => (*offset as usize, tuple.as_bytes()?),
+                Slot::Chunk {
+                    offset,
+                    next,
+                    bytes: chunk,
+                    ..
+                } => (*offset as usize, Slot::chunk_bytes(*next, chunk)),
+                _ => continue,
+            };
+            bytes[offset..offset + data.len()].copy_from_slice(&data);
+        }
+        Ok(bytes)
+    }
+
+    /// Checksums only live on disk, legacy pages predate them and never get here.
+    fn verify(image: &mut [u8]) -> Result<(), Error> {
+        let stored = u32::from_le_bytes(image[CHECKSUM_RANGE].try_into().unwrap());
+        image[CHECKSUM_RANGE].fill(0);
+        match stored == hash(image) {
+            true => Ok(()),
+            false => Err(Error::ChecksumMismatch {
+                file: None,
+                page: None,
+            }),
         }
-        Ok(tuples)
     }
 
     pub fn refresh_checksum(&mut self) -> Result<(), Error> {
-        self.header.checksum = hash(&self.as_bytes()?[17..]);
+        self.header.checksum = hash(&self.image()?);
         Ok(())
     }
 
     pub fn valid_checksum(&self) -> Result<bool, Error> {
-        Ok(self.header.checksum == hash(&self.as_bytes()?[17..]))
+        Ok(self.header.checksum == hash(&self.image()?))
     }
 }
 
+/// Bytes at `offset..offset + length`, none when the range overflows or leaves the page.
+fn range(bytes: &[u8], offset: u32, length: u32) -> Option<&[u8]> {
+    bytes.get(offset as usize..offset.checked_add(length)? as usize)
+}
+
 impl Encoding for Page {
     fn as_bytes(&self) -> Result<Vec<u8>, Error> {
-        let mut concat_bytes: Vec<u8> = Vec::new();
-        concat_bytes.extend_from_slice(&self.header.as_bytes()?);
-        let tuple_offset_start = concat_bytes.len() as u32 + self.header.slots * 8;
-        let mut tuples: Vec<u8> = vec![0; (self.header.page_size - tuple_offset_start) as usize];
-        self.tuples.iter().for_each(|(k, v)| {
-            concat_bytes.extend_from_slice(&bincode::serialize(k).unwrap());
-            tuples.splice(
-                (k.0 - tuple_offset_start) as usize..(k.0 + k.1 - tuple_offset_start) as usize,
-                v.as_bytes().unwrap(),
-            );
-        });
-        concat_bytes.extend_from_slice(&tuples);
-        Ok(concat_bytes)
-    }
-
-    fn from_bytes(bytes: &[u8]) -> Result<Page, Error> {
-        let header = PageHeader::from_bytes(&bytes[..14]).unwrap();
-        let slots: Vec<(u32, u32)> = bytes[14..(14 + (header.slots as usize * 8))]
-            .chunks(8)
-            .map(|chunk| {
-                (
-                    bincode::deserialize(&chunk[0..4]).unwrap(),
-                    bincode::deserialize(&chunk[4..8]).unwrap(),
-                )
-            })
-            .collect();
-        let mut tuples: HashMap<(u32, u32), Tuple> = HashMap::new();
-        slots.iter().for_each(|(offset, length)| {
-            tuples.insert(
-                (*offset, *length),
-                Tuple::from_bytes(&bytes[*offset as usize..(offset + length) as usize]).unwrap(),
-            );
-        });
-        Ok(Page { header, tuples })
+        let mut bytes = self.image()?;
+        let checksum = hash(&bytes);
+        bytes[CHECKSUM_RANGE].copy_from_slice(&checksum.to_le_bytes());
+        if self.header.compression != RAW {
+            let body =
+                codec(self.header.compression)?.compress(&bytes[PAGE_HEADER_SIZE as usize..]);
+            bytes.truncate(PAGE_HEADER_SIZE as usize);
+            bytes.extend(body);
+        }
+        Ok(bytes)
+    }
+
+    fn from_bytes(bytes: &[u8], schema: Option<&Schema>) -> Result<Page, Error> {
+        let schema = schema.ok_or(Error::MissingSchema)?;
+        let mut image = Page::inflate(bytes)?;
+        Page::verify(&mut image)?;
+        Page::read_slots(&image, |bytes| {
+            Ok((bytes.len() as u32, Tuple::from_bytes(bytes, Some(schema))?))
+        })
     }
 }
 
 #[cfg(test)]
 pub mod tests {
+    use crate::storage::file::codec::{DICTIONARY, LZ, RUN_LENGTH};
+    use crate::storage::schema::encoding::Encoding as SchemaEncoding;
+    use crate::storage::schema::Schema;
     use crate::storage::tests::get_test_schema;
 
     use super::*;
 
+    fn tuple_slot(offset: u32, length: u32, tuple: Tuple) -> Slot {
+        Slot::Tuple {
+            offset,
+            length,
+            tuple,
+        }
+    }
+
     pub fn get_test_page() -> Page {
         let mut page = Page::build(500, 1).unwrap();
-        page.tuples.insert(
-            (446, 54),
-            Tuple::build(&get_test_schema(), &[0; 4], &[2; 33]).unwrap(),
-        );
-        page.tuples.insert(
-            (334, 38),
-            Tuple::build(&get_test_schema(), &[1, 0, 0, 0], &[8; 17]).unwrap(),
-        );
-        page.tuples.insert(
-            (234, 46),
-            Tuple::build(&get_test_schema(), &[0, 0, 0, 1], &[65; 25]).unwrap(),
-        );
+        page.slots = vec![
+            tuple_slot(
+                334,
+                19,
+                Tuple::build(&get_test_schema(), &[0b0001], &[8; 17]).unwrap(),
+            ),
+            tuple_slot(
+                446,
+                35,
+                Tuple::build(&get_test_schema(), &[0], &[2; 33]).unwrap(),
+            ),
+            tuple_slot(
+                234,
+                27,
+                Tuple::build(&get_test_schema(), &[0b1000], &[65; 25]).unwrap(),
+            ),
+        ];
         page.header.slots = 3;
         page
     }
 
     fn get_test_page_bytes() -> Vec<u8> {
+        vec![
+            244, 1, 0, 0, 3, 0, 0, 0, 165, 91, 108, 19, 0, 1, 1, 78, 1, 128, 0, 0, 19, 129, 0, 1,
+            190, 1, 128, 0, 0, 35, 129, 0, 0, 234, 129, 0, 0, 27, 255, 0, 197, 0, 0, 8, 151, 65,
+            200, 0, 0, 1, 143, 8, 221, 0, 159, 2, 145, 0,
+        ]
+    }
+
+    fn get_legacy_test_page_bytes() -> Vec<u8> {
         vec![
             244, 1, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 0, 1, 78, 1, 0, 0, 38, 0, 0, 0, 190, 1, 0, 0, 54,
             0, 0, 0, 234, 0, 0, 0, 46, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
@@ -197,7 +508,11 @@ pub mod tests {
     #[test]
     fn as_bytes_should_convert_page() {
         assert_eq!(
-            Page::from_bytes(&get_test_page().as_bytes().unwrap()).unwrap(),
+            Page::from_bytes(
+                &get_test_page().as_bytes().unwrap(),
+                Some(&get_test_schema())
+            )
+            .unwrap(),
             get_test_page()
         );
     }
@@ -206,57 +521,86 @@ pub mod tests {
     fn from_bytes_should_convert_bytes() {
         assert_eq!(
             get_test_page(),
-            Page::from_bytes(&get_test_page_bytes()).unwrap()
+            Page::from_bytes(&get_test_page_bytes(), Some(&get_test_schema())).unwrap()
+        );
+    }
+
+    #[test]
+    fn as_bytes_should_compress_with_page_codec() {
+        for compression in [RAW, RUN_LENGTH, DICTIONARY, LZ] {
+            let mut page = get_test_page();
+            page.header.compression = compression;
+            let bytes = page.as_bytes().unwrap();
+            assert_eq!(bytes.len() < 500, compression != RAW);
+            assert_eq!(
+                page,
+                Page::from_bytes(&bytes, Some(&get_test_schema())).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn build_should_panic_if_unknown_codec() {
+        Page::build(500, 9).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_bytes_should_panic_without_schema() {
+        Page::from_bytes(&get_test_page_bytes(), None).unwrap();
+    }
+
+    #[test]
+    fn from_legacy_bytes_should_repack_tuples() {
+        assert_eq!(
+            get_test_page(),
+            Page::from_legacy_bytes(&get_legacy_test_page_bytes()).unwrap()
         );
     }
 
     #[test]
-    fn get_free_slots_should_return_empty_slots_case_normal() {
+    fn get_free_spaces_should_return_empty_spaces_case_normal() {
         let mut page = get_test_page();
         page.header.page_size += 10;
         assert_eq!(
-            page.get_free_slots().unwrap(),
-            vec![(38, 196), (280, 54), (372, 74), (500, 10)]
+            page.get_free_spaces(),
+            vec![(38, 196), (261, 73), (353, 93), (481, 29)]
         );
     }
 
     #[test]
-    fn get_free_slots_should_return_all_empty_slots_case_maximum() {
+    fn get_free_spaces_should_return_all_empty_spaces_case_maximum() {
         assert_eq!(
-            get_test_page().get_free_slots().unwrap(),
-            vec![(38, 196,), (280, 54,), (372, 74,)]
+            get_test_page().get_free_spaces(),
+            vec![(38, 196), (261, 73), (353, 93), (481, 19)]
         );
     }
 
     #[test]
-    fn get_free_slots_should_return_empty_slots_case_none() {
+    fn get_free_spaces_should_return_empty_spaces_case_none() {
         let mut page = Page::build(500, 1).unwrap();
-        page.tuples.insert(
-            (22, 478),
-            Tuple::build(&get_test_schema(), &[0, 0, 0, 1], &[65; 25]).unwrap(),
-        );
-        page.header.slots += 1;
-        assert_eq!(page.get_free_slots().unwrap(), vec![]);
+        page.slots = vec![tuple_slot(
+            22,
+            478,
+            Tuple::build(&get_test_schema(), &[0b1000], &[65; 25]).unwrap(),
+        )];
+        page.header.slots = 1;
+        assert_eq!(page.get_free_spaces(), vec![]);
     }
 
     #[test]
-    fn get_free_slots_should_return_empty_slots_case_minimum() {
+    fn get_free_spaces_should_return_empty_spaces_case_minimum() {
         let mut page = Page::build(500, 1).unwrap();
-        page.tuples.insert(
-            (38, 103),
-            Tuple::build(&get_test_schema(), &[0, 0, 0, 1], &[65; 25]).unwrap(),
-        );
-        page.tuples.insert(
-            (152, 25),
-            Tuple::build(&get_test_schema(), &[0, 0, 0, 1], &[65; 25]).unwrap(),
-        );
-        page.tuples.insert(
-            (200, 25),
-            Tuple::build(&get_test_schema(), &[0, 0, 0, 1], &[65; 25]).unwrap(),
-        );
+        let tuple = Tuple::build(&get_test_schema(), &[0b1000], &[65; 25]).unwrap();
+        page.slots = vec![
+            tuple_slot(38, 103, tuple.clone()),
+            tuple_slot(152, 25, tuple.clone()),
+            tuple_slot(200, 25, tuple),
+        ];
         page.header.slots = 3;
         assert_eq!(
-            page.get_free_slots().unwrap(),
+            page.get_free_spaces(),
             vec![(141, 11), (177, 23), (225, 275)]
         );
     }
@@ -264,26 +608,123 @@ pub mod tests {
     #[test]
     fn insert_should_append_tuple() {
         let mut page = get_test_page();
-        page.insert(Tuple::build(&get_test_schema(), &[1, 1, 0, 1], &[1]).unwrap())
+        let slots = [
+            Tuple::build(&get_test_schema(), &[0b1011], &[1]).unwrap(),
+            Tuple::build(&get_test_schema(), &[0], &[32; 33]).unwrap(),
+            Tuple::build(&get_test_schema(), &[0], &[18; 33]).unwrap(),
+        ]
+        .into_iter()
+        .map(|tuple| page.insert(tuple).unwrap())
+        .collect::<Vec<u32>>();
+        assert_eq!(slots, vec![3, 4, 5]);
+        assert!(page.bytes_size().unwrap() < 500);
+        assert_eq!(
+            page,
+            Page::from_bytes(&page.as_bytes().unwrap(), Some(&get_test_schema())).unwrap()
+        );
+        assert_eq!(
+            page.get_free_spaces(),
+            vec![(62, 172), (261, 3), (353, 93), (481, 16)]
+        )
+    }
+
+    #[test]
+    fn insert_should_fill_exact_free_space() {
+        let schema = Schema::from_str("id INT, name STRING").unwrap();
+        let mut page = Page::build(14 + 8 + 11, 1).unwrap();
+        page.insert(Tuple::build(&schema, &[0], &[1, 0, 0, 0, 1, 0, 0, 0, 97]).unwrap())
             .unwrap();
-        page.insert(Tuple::build(&get_test_schema(), &[0, 0, 0, 0], &[32; 33]).unwrap())
+        assert_eq!(page.get_free_spaces(), vec![]);
+        assert_eq!(
+            page,
+            Page::from_bytes(&page.as_bytes().unwrap(), Some(&schema)).unwrap()
+        );
+    }
+
+    #[test]
+    fn can_fit_should_match_insert() {
+        let schema = Schema::from_str("id INT, name STRING").unwrap();
+        let tuple = Tuple::build(&schema, &[0], &[1, 0, 0, 0, 1, 0, 0, 0, 97]).unwrap();
+        let mut page = Page::build(14 + 8 + 11, 1).unwrap();
+        assert!(page.can_fit(11));
+        assert!(!page.can_fit(12));
+        page.insert(tuple).unwrap();
+        assert!(!page.can_fit(0));
+        page.delete_by_slots(&[0]).unwrap();
+        assert!(page.can_fit(11));
+    }
+
+    #[test]
+    fn insert_should_reuse_free_slots() {
+        let mut page = get_test_page();
+        page.delete_by_slots(&[1]).unwrap();
+        let slot = page
+            .insert(Tuple::build(&get_test_schema(), &[0b1011], &[1]).unwrap())
+            .unwrap();
+        assert_eq!(slot, 1);
+        assert_eq!(page.header.slots, 3);
+        assert_eq!(page.get_slot(1).unwrap().region(), Some((331, 3)));
+    }
+
+    #[test]
+    fn insert_should_compact_fragmented_page() {
+        let mut page = Page::build(14 + 8 * 2 + 20 + 35 + 20, 1).unwrap();
+        page.slots = vec![
+            tuple_slot(
+                50,
+                35,
+                Tuple::build(&get_test_schema(), &[0], &[2; 33]).unwrap(),
+            ),
+            Slot::Free,
+        ];
+        page.header.slots = 2;
+        let slot = page
+            .insert(Tuple::build(&get_test_schema(), &[0], &[32; 33]).unwrap())
             .unwrap();
-        page.insert(Tuple::build(&get_test_schema(), &[0, 0, 0, 0], &[18; 33]).unwrap())
+        assert_eq!(slot, 1);
+        assert_eq!(page.get_slot(0).unwrap().region(), Some((70, 35)));
+        assert_eq!(page.get_slot(1).unwrap().region(), Some((35, 35)));
+        assert_eq!(page.get_free_spaces(), vec![(30, 5)]);
+    }
+
+    #[test]
+    fn insert_should_not_grow_directory_over_tuple() {
+        let mut page = Page::build(100, 1).unwrap();
+        let tuple = Tuple::build(&get_test_schema(), &[0b1011], &[1]).unwrap();
+        page.slots = vec![tuple_slot(22, 3, tuple.clone())];
+        page.header.slots = 1;
+        assert_eq!(page.insert(tuple).unwrap(), 1);
+        assert_eq!(page.get_slot(0).unwrap().region(), Some((97, 3)));
+        assert_eq!(page.get_slot(1).unwrap().region(), Some((94, 3)));
+    }
+
+    #[test]
+    fn insert_chunk_should_survive_encoding() {
+        let mut page = get_test_page();
+        let chunk = page
+            .insert_chunk(&[5; 20], Some(RowId::build(3, 1)))
             .unwrap();
-        assert_eq!(page.bytes_size().unwrap(), 500);
-        assert_eq!(page, Page::from_bytes(&page.as_bytes().unwrap()).unwrap());
+        let forward = page.insert_forward(RowId::build(0, chunk)).unwrap();
+        assert_eq!(page.get_slot(chunk).unwrap().region(), Some((306, 28)));
         assert_eq!(
-            page.get_free_slots().unwrap(),
-            vec![(62, 118), (280, 32), (372, 20)]
-        )
+            page.get_slot(forward).unwrap(),
+            &Slot::Forward(RowId::build(0, 3))
+        );
+        assert_eq!(
+            page,
+            Page::from_bytes(&page.as_bytes().unwrap(), Some(&get_test_schema())).unwrap()
+        );
+        assert!(page
+            .update_by_slot(chunk, get_test_page().slots[0].tuple().unwrap().clone())
+            .is_err());
     }
 
     #[test]
     #[should_panic]
     fn insert_should_panic_if_full_page() {
         let mut page = get_test_page();
-        for _ in 0..8 {
-            page.insert(Tuple::build(&get_test_schema(), &[0, 0, 0, 0], &[18; 33]).unwrap())
+        for _ in 0..9 {
+            page.insert(Tuple::build(&get_test_schema(), &[0], &[18; 33]).unwrap())
                 .unwrap();
         }
     }
@@ -291,81 +732,229 @@ pub mod tests {
     #[test]
     fn delete_by_slots_should_remove_tuples() {
         let mut page = get_test_page();
-        page.insert(Tuple::build(&get_test_schema(), &[1, 1, 0, 1], &[1]).unwrap())
-            .unwrap();
-        page.insert(Tuple::build(&get_test_schema(), &[0, 0, 0, 0], &[32; 33]).unwrap())
-            .unwrap();
-        page.insert(Tuple::build(&get_test_schema(), &[0, 0, 0, 0], &[18; 33]).unwrap())
+        page.insert(Tuple::build(&get_test_schema(), &[0b1011], &[1]).unwrap())
             .unwrap();
-        page.delete_by_slots(&[(180, 54), (392, 54), (312, 22), (27, 11)])
+        page.insert(Tuple::build(&get_test_schema(), &[0], &[32; 33]).unwrap())
             .unwrap();
-        assert_eq!(page.tuples.len(), 3);
+        page.delete_by_slots(&[3, 4, 9]).unwrap();
+        assert_eq!(page.tuples().count(), 3);
+        assert_eq!(page.slots[..3], get_test_page().slots[..]);
+        assert_eq!(page.slots[3..], [Slot::Free, Slot::Free]);
         assert_eq!(
-            page.tuples.get(&(446, 54)).unwrap(),
-            get_test_page().tuples.get(&(446, 54)).unwrap()
-        );
-        assert_eq!(
-            page.tuples.get(&(234, 46)).unwrap(),
-            get_test_page().tuples.get(&(234, 46)).unwrap()
-        );
-        assert_eq!(
-            page.tuples.get(&(334, 38)).unwrap(),
-            get_test_page().tuples.get(&(334, 38)).unwrap()
+            page,
+            Page::from_bytes(&page.as_bytes().unwrap(), Some(&get_test_schema())).unwrap()
         );
     }
 
     #[test]
-    fn update_by_slot_should_replace_tuple() {
+    fn update_by_slot_should_replace_tuple_in_place() {
         let mut page = get_test_page();
         page.update_by_slot(
-            (234, 46),
-            Tuple::build(&get_test_schema(), &[1, 1, 0, 1], &[1]).unwrap(),
+            2,
+            Tuple::build(&get_test_schema(), &[0b1011], &[1]).unwrap(),
         )
         .unwrap();
+        assert_eq!(page.get_slot(2).unwrap().region(), Some((234, 3)));
         assert_eq!(
-            page.tuples[&(234, 46)].as_bytes().unwrap(),
-            [0, 4, 0, 0, 0, 0, 0, 0, 0, 1, 1, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 1]
+            page.read_by_slots(&[2]).unwrap()[&2].as_bytes().unwrap(),
+            [0, 0b1011, 1]
         );
     }
 
+    #[test]
+    fn update_by_slot_should_move_tuple_if_bigger() {
+        let mut page = get_test_page();
+        let tuple = Tuple::build(&get_test_schema(), &[0], &[32; 33]).unwrap();
+        page.update_by_slot(0, tuple.clone()).unwrap();
+        assert_eq!(page.get_slot(0).unwrap().region(), Some((411, 35)));
+        assert_eq!(page.read_by_slots(&[0]).unwrap()[&0], &tuple);
+        assert_eq!(page.tuples().count(), 3);
+    }
+
+    #[test]
+    fn update_by_slot_should_keep_tuple_if_page_full() {
+        let mut page = Page::build(14 + 8 + 30, 1).unwrap();
+        let slot = page
+            .insert(Tuple::build(&get_test_schema(), &[0b1011], &[1]).unwrap())
+            .unwrap();
+        let full_page = page.clone();
+        let error = page
+            .update_by_slot(
+                slot,
+                Tuple::build(&get_test_schema(), &[0], &[18; 33]).unwrap(),
+            )
+            .unwrap_err();
+        assert!(matches!(error, Error::PageOverflow(_)));
+        assert_eq!(page, full_page);
+    }
+
     #[test]
     #[should_panic]
-    fn update_by_slot_should_panic_key_not_found() {
+    fn update_by_slot_should_panic_slot_not_found() {
         let mut page = get_test_page();
         page.update_by_slot(
-            (251, 30),
-            Tuple::build(&get_test_schema(), &[1, 1, 0, 1], &[1]).unwrap(),
+            7,
+            Tuple::build(&get_test_schema(), &[0b1011], &[1]).unwrap(),
         )
         .unwrap();
     }
 
+    #[test]
+    fn compact_should_merge_free_spaces() {
+        let mut page = get_test_page();
+        page.compact();
+        assert_eq!(page.get_slot(0).unwrap().region(), Some((446, 19)));
+        assert_eq!(page.get_slot(1).unwrap().region(), Some((465, 35)));
+        assert_eq!(page.get_slot(2).unwrap().region(), Some((419, 27)));
+        assert_eq!(page.get_free_spaces(), vec![(38, 381)]);
+        assert_eq!(
+            page,
+            Page::from_bytes(&page.as_bytes().unwrap(), Some(&get_test_schema())).unwrap()
+        );
+    }
+
+    #[test]
+    fn compact_should_move_chunks_with_tuples() {
+        let mut page = Page::build(200, 0).unwrap();
+        page.slots = vec![
+            tuple_slot(
+                150,
+                35,
+                Tuple::build(&get_test_schema(), &[0], &[2; 33]).unwrap(),
+            ),
+            Slot::from_chunk_bytes(100, &Slot::chunk_bytes(None, &[7; 40])),
+            tuple_slot(
+                60,
+                35,
+                Tuple::build(&get_test_schema(), &[0], &[3; 33]).unwrap(),
+            ),
+        ];
+        page.header.slots = 3;
+        page.compact();
+        assert_eq!(page.get_slot(0).unwrap().region(), Some((165, 35)));
+        assert_eq!(page.get_slot(1).unwrap().region(), Some((117, 48)));
+        assert_eq!(page.get_slot(2).unwrap().region(), Some((82, 35)));
+        assert_eq!(
+            page,
+            Page::from_bytes(&page.as_bytes().unwrap(), Some(&get_test_schema())).unwrap()
+        );
+    }
+
+    #[test]
+    fn forward_should_free_space_and_survive_encoding() {
+        let mut page = get_test_page();
+        page.forward(1, RowId::build(4, 2)).unwrap();
+        assert_eq!(
+            page.get_slot(1).unwrap(),
+            &Slot::Forward(RowId::build(4, 2))
+        );
+        assert_eq!(
+            page.get_free_spaces(),
+            vec![(38, 196), (261, 73), (353, 147)]
+        );
+        assert_eq!(
+            page,
+            Page::from_bytes(&page.as_bytes().unwrap(), Some(&get_test_schema())).unwrap()
+        );
+    }
+
+    #[test]
+    fn update_by_slot_should_reject_forward() {
+        let mut page = get_test_page();
+        page.forward(1, RowId::build(4, 2)).unwrap();
+        let tuple = Tuple::build(&get_test_schema(), &[0b1011], &[1]).unwrap();
+        assert!(matches!(
+            page.update_by_slot(1, tuple.clone()),
+            Err(Error::InvalidSlot(1))
+        ));
+        assert!(matches!(
+            page.delete_by_slots(&[0, 1]),
+            Err(Error::InvalidSlot(1))
+        ));
+        assert_eq!(page.tuples().count(), 2);
+        assert_eq!(
+            page.unforward(1, tuple.clone()).unwrap(),
+            RowId::build(4, 2)
+        );
+        assert_eq!(page.read_by_slots(&[1]).unwrap()[&1], &tuple);
+    }
+
+    #[test]
+    fn delete_forward_should_return_target() {
+        let mut page = get_test_page();
+        page.forward(1, RowId::build(4, 2)).unwrap();
+        assert_eq!(page.delete_forward(1).unwrap(), RowId::build(4, 2));
+        assert_eq!(page.slots[1], Slot::Free);
+        assert!(page.delete_forward(0).is_err());
+    }
+
     #[test]
     fn read_by_slots_should_return_tuples() {
         let mut page = get_test_page();
-        page.insert(Tuple::build(&get_test_schema(), &[1, 1, 0, 1], &[1]).unwrap())
-            .unwrap();
-        page.insert(Tuple::build(&get_test_schema(), &[0, 0, 0, 0], &[32; 33]).unwrap())
-            .unwrap();
-        page.insert(Tuple::build(&get_test_schema(), &[0, 0, 0, 0], &[18; 33]).unwrap())
-            .unwrap();
-        let tuples = page
-            .read_by_slots(&[(312, 22), (334, 38), (27, 11)])
+        let slot = page
+            .insert(Tuple::build(&get_test_schema(), &[0b1011], &[1]).unwrap())
             .unwrap();
+        let tuples = page.read_by_slots(&[slot, 0, 9]).unwrap();
+        assert_eq!(tuples.len(), 2);
         assert_eq!(
-            *tuples[&(312, 22)],
-            Tuple::build(&get_test_schema(), &[1, 1, 0, 1], &[1]).unwrap()
+            *tuples[&slot],
+            Tuple::build(&get_test_schema(), &[0b1011], &[1]).unwrap()
         );
         assert_eq!(
-            *tuples[&(334, 38)],
+            *tuples[&0],
             Tuple::build(
                 &get_test_schema(),
-                &[1, 0, 0, 0],
+                &[0b0001],
                 &[8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8]
             )
             .unwrap()
         );
     }
 
+    #[test]
+    fn from_bytes_should_detect_corruption() {
+        let mut bytes = get_test_page().as_bytes().unwrap();
+        bytes[20] ^= 1;
+        assert!(matches!(
+            Page::from_bytes(&bytes, Some(&get_test_schema())),
+            Err(Error::ChecksumMismatch {
+                file: None,
+                page: None
+            })
+        ));
+        let mut bytes = Page::build(200, RAW).unwrap().as_bytes().unwrap();
+        bytes[150] = 7;
+        assert!(Page::from_bytes(&bytes, Some(&get_test_schema())).is_err());
+    }
+
+    #[test]
+    fn from_bytes_should_reject_unstamped_pages() {
+        let mut bytes = get_test_page_bytes();
+        bytes[CHECKSUM_RANGE].fill(0);
+        assert!(matches!(
+            Page::from_bytes(&bytes, Some(&get_test_schema())),
+            Err(Error::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn from_legacy_bytes_should_reject_out_of_page_slots() {
+        let mut bytes = Page::build(200, RAW).unwrap().as_bytes().unwrap();
+        bytes[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            Page::from_legacy_bytes(&bytes),
+            Err(Error::CorruptedPage(_))
+        ));
+        let mut bytes = Page::build(200, RAW).unwrap().as_bytes().unwrap();
+        bytes[4] = 1;
+        bytes[PAGE_HEADER_SIZE as usize..PAGE_HEADER_SIZE as usize + 8]
+            .copy_from_slice(&[245, 255, 255, 255, 100, 0, 0, 0]);
+        assert!(matches!(
+            Page::from_legacy_bytes(&bytes),
+            Err(Error::CorruptedPage(_))
+        ));
+    }
+
     #[test]
     fn valid_checksum_should_control_integrity() {
         let mut page = get_test_page();
@@ -1,6 +1,8 @@
 use crate::storage::file::encoding::Encoding;
 use serde::{Deserialize, Serialize};
 
+pub const PAGE_HEADER_SIZE: u32 = 14;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PageHeader {
     pub page_size: u32,
@@ -40,7 +42,7 @@ mod tests {
     #[test]
     fn from_bytes_should_convert_bytes() {
         assert_eq!(
-            PageHeader::from_bytes(&[213, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3]).unwrap(),
+            PageHeader::from_bytes(&[213, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3], None).unwrap(),
             PageHeader::build(981, 3)
         )
     }
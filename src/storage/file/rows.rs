@@ -0,0 +1,283 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use crate::storage::file::encoding::Encoding;
+use crate::storage::file::error::Error;
+use crate::storage::file::page::Page;
+use crate::storage::file::slot::{RowId, Slot, CHUNK_HEADER_SIZE};
+use crate::storage::file::tuple::Tuple;
+use crate::storage::schema::Schema;
+
+pub type PageVisitor<'a, E> = dyn FnMut(u32, &Page) -> Result<(), E> + 'a;
+
+pub type RowVisitor<'a, E> = dyn FnMut(RowId, &Tuple) -> Result<(), E> + 'a;
+
+/// Row operations over the pages of a file, following forwarded tuples and chaining the
+/// ones larger than a page.
+pub trait Rows {
+    type Error: From<Error>;
+
+    fn page(&self, index: u32) -> Result<Cow<'_, Page>, Self::Error>;
+
+    fn page_mut(&mut self, index: u32) -> Result<&mut Page, Self::Error>;
+
+    fn page_ids(&self) -> Result<Vec<u32>, Self::Error>;
+
+    /// Adds an empty page to the file and returns its index.
+    fn append_page(&mut self) -> Result<u32, Self::Error>;
+
+    fn max_tuple_size(&self) -> Result<u32, Self::Error>;
+
+    fn insert_tuple(&mut self, tuple: Tuple) -> Result<RowId, Self::Error> {
+        insert(self, None, tuple)
+    }
+
+    /// Keeps the row on the given page, only the chunks of a large tuple go elsewhere.
+    fn insert_tuple_into(&mut self, index: u32, tuple: Tuple) -> Result<RowId, Self::Error> {
+        insert(self, Some(index), tuple)
+    }
+
+    fn read_tuple(&self, row: RowId, schema: &Schema) -> Result<Tuple, Self::Error> {
+        let row = forwarded(self, row)?.unwrap_or(row);
+        match self.page(row.page)?.get_slot(row.slot)? {
+            Slot::Tuple { tuple, .. } => Ok(tuple.clone()),
+            Slot::Chunk { .. } => read_chained(self, row, schema),
+            _ => Err(Error::InvalidSlot(row.slot).into()),
+        }
+    }
+
+    /// Visits the pages one at a time, in no particular order.
+    fn visit_pages(&self, visit: &mut PageVisitor<'_, Self::Error>) -> Result<(), Self::Error> {
+        for index in self.page_ids()? {
+            visit(index, self.page(index)?.as_ref())?;
+        }
+        Ok(())
+    }
+
+    /// Visits each row once without collecting them and stops at the first error. A moved row
+    /// is visited where it now lies, a chained one at its forward.
+    fn visit_rows(
+        &self,
+        schema: &Schema,
+        visit: &mut RowVisitor<'_, Self::Error>,
+    ) -> Result<(), Self::Error> {
+        self.visit_pages(&mut |index, page| {
+            for (slot, record) in (0_u32..).zip(&page.slots) {
+                let row = RowId::build(index, slot);
+                match record {
+                    Slot::Tuple { tuple, .. } => visit(row, tuple)?,
+                    Slot::Forward(target) => {
+                        if let Slot::Chunk { .. } = self.page(target.page)?.get_slot(target.slot)? {
+                            visit(row, &read_chained(self, *target, schema)?)?;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn rows(&self, schema: &Schema) -> Result<Vec<(RowId, Tuple)>, Self::Error> {
+        let mut targets: HashSet<RowId> = HashSet::new();
+        let mut found: Vec<(RowId, Option<Tuple>)> = Vec::new();
+        for index in self.page_ids()? {
+            let page = self.page(index)?;
+            for (slot, record) in (0_u32..).zip(&page.slots) {
+                let row = RowId::build(index, slot);
+                match record {
+                    Slot::Tuple { tuple, .. } => found.push((row, Some(tuple.clone()))),
+                    Slot::Forward(target) => {
+                        targets.insert(*target);
+                        found.push((row, None));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        found
+            .into_iter()
+            .filter(|(row, _)| !targets.contains(row))
+            .map(|(row, tuple)| match tuple {
+                Some(tuple) => Ok((row, tuple)),
+                None => Ok((row, self.read_tuple(row, schema)?)),
+            })
+            .collect()
+    }
+
+    fn update_tuple(&mut self, row: RowId, tuple: Tuple) -> Result<(), Self::Error> {
+        let forwarded = forwarded(self, row)?;
+        let bytes = tuple.as_bytes()?;
+        if bytes.len() as u32 > self.max_tuple_size()? {
+            let head = write_chain(self, &bytes)?;
+            self.page_mut(row.page)?.forward(row.slot, head)?;
+        } else if let Some(target) = forwarded {
+            if let Slot::Tuple { .. } = self.page(target.page)?.get_slot(target.slot)? {
+                match self
+                    .page_mut(target.page)?
+                    .update_by_slot(target.slot, tuple.clone())
+                {
+                    Err(Error::PageOverflow(_)) => {}
+                    result => return Ok(result?),
+                }
+            }
+            // Forwards always point from the original slot, so a row never chains.
+            match self.page_mut(row.page)?.unforward(row.slot, tuple.clone()) {
+                Err(Error::PageOverflow(_)) => {
+                    let moved = place(self, None, bytes.len() as u32, |page| {
+                        page.insert(tuple.clone())
+                    })?;
+                    self.page_mut(row.page)?.forward(row.slot, moved)?;
+                }
+                result => {
+                    result?;
+                }
+            }
+        } else {
+            match self
+                .page_mut(row.page)?
+                .update_by_slot(row.slot, tuple.clone())
+            {
+                Err(Error::PageOverflow(_)) => {
+                    let moved = place(self, None, bytes.len() as u32, |page| {
+                        page.insert(tuple.clone())
+                    })?;
+                    self.page_mut(row.page)?.forward(row.slot, moved)?;
+                }
+                result => result?,
+            }
+        }
+        if let Some(target) = forwarded {
+            free(self, target)?;
+        }
+        Ok(())
+    }
+
+    fn delete_tuple(&mut self, row: RowId) -> Result<(), Self::Error> {
+        match forwarded(self, row)? {
+            Some(_) => {
+                let target = self.page_mut(row.page)?.delete_forward(row.slot)?;
+                free(self, target)
+            }
+            None => Ok(self.page_mut(row.page)?.delete_by_slots(&[row.slot])?),
+        }
+    }
+}
+
+fn forwarded<R: Rows + ?Sized>(rows: &R, row: RowId) -> Result<Option<RowId>, R::Error> {
+    match rows.page(row.page)?.get_slot(row.slot)? {
+        Slot::Forward(target) => Ok(Some(*target)),
+        _ => Ok(None),
+    }
+}
+
+fn insert<R: Rows + ?Sized>(
+    rows: &mut R,
+    index: Option<u32>,
+    tuple: Tuple,
+) -> Result<RowId, R::Error> {
+    let bytes = tuple.as_bytes()?;
+    if bytes.len() as u32 <= rows.max_tuple_size()? {
+        return place(rows, index, bytes.len() as u32, |page| {
+            page.insert(tuple.clone())
+        });
+    }
+    let head = write_chain(rows, &bytes)?;
+    place(rows, index, 0, |page| page.insert_forward(head)).or_else(|error| {
+        free(rows, head)?;
+        Err(error)
+    })
+}
+
+/// Inserts `length` bytes on the given page, or on the first page with room, appending one
+/// if none has.
+pub(super) fn place<R: Rows + ?Sized>(
+    rows: &mut R,
+    index: Option<u32>,
+    length: u32,
+    insert: impl FnOnce(&mut Page) -> Result<u32, Error>,
+) -> Result<RowId, R::Error> {
+    if let Some(index) = index {
+        return Ok(RowId::build(index, insert(rows.page_mut(index)?)?));
+    }
+    for index in rows.page_ids()? {
+        // Full pages are never borrowed mutably, so they stay clean in the buffer pool.
+        if rows.page(index)?.can_fit(length) {
+            return Ok(RowId::build(index, insert(rows.page_mut(index)?)?));
+        }
+    }
+    let index = rows.append_page()?;
+    Ok(RowId::build(index, insert(rows.page_mut(index)?)?))
+}
+
+fn write_chain<R: Rows + ?Sized>(rows: &mut R, bytes: &[u8]) -> Result<RowId, R::Error> {
+    let chunk_size = rows
+        .max_tuple_size()?
+        .saturating_sub(CHUNK_HEADER_SIZE)
+        .max(1);
+    let mut chain: Vec<RowId> = Vec::new();
+    for chunk in bytes.chunks(chunk_size as usize).rev() {
+        let next = chain.last().copied();
+        let length = CHUNK_HEADER_SIZE + chunk.len() as u32;
+        match place(rows, None, length, |page| page.insert_chunk(chunk, next)) {
+            Ok(row) => chain.push(row),
+            Err(error) => {
+                for row in chain {
+                    rows.page_mut(row.page)?.delete_by_slots(&[row.slot])?;
+                }
+                return Err(error);
+            }
+        }
+    }
+    Ok(chain.last().copied().ok_or(Error::CorruptedTuple(
+        "Cannot chain an empty tuple".to_string(),
+    ))?)
+}
+
+fn read_chained<R: Rows + ?Sized>(
+    rows: &R,
+    head: RowId,
+    schema: &Schema,
+) -> Result<Tuple, R::Error> {
+    let bytes: Vec<u8> = read_chain(rows, head)?
+        .into_iter()
+        .flat_map(|(_, bytes)| bytes)
+        .collect();
+    Ok(Tuple::from_bytes(&bytes, Some(schema))?)
+}
+
+pub(super) fn read_chain<R: Rows + ?Sized>(
+    rows: &R,
+    head: RowId,
+) -> Result<Vec<(RowId, Vec<u8>)>, R::Error> {
+    let mut chain: Vec<(RowId, Vec<u8>)> = Vec::new();
+    let mut next = Some(head);
+    while let Some(row) = next {
+        match rows.page(row.page)?.get_slot(row.slot)? {
+            Slot::Chunk {
+                next: following,
+                bytes,
+                ..
+            } if !chain.iter().any(|(chained, _)| *chained == row) => {
+                chain.push((row, bytes.clone()));
+                next = *following;
+            }
+            _ => return Err(Error::InvalidSlot(row.slot).into()),
+        }
+    }
+    Ok(chain)
+}
+
+fn free<R: Rows + ?Sized>(rows: &mut R, row: RowId) -> Result<(), R::Error> {
+    let chain: Vec<RowId> = match rows.page(row.page)?.get_slot(row.slot)? {
+        Slot::Chunk { .. } => read_chain(rows, row)?
+            .into_iter()
+            .map(|(row, _)| row)
+            .collect(),
+        _ => vec![row],
+    };
+    for row in chain {
+        rows.page_mut(row.page)?.delete_by_slots(&[row.slot])?;
+    }
+    Ok(())
+}
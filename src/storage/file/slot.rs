@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+
+use crate::storage::file::tuple::Tuple;
+
+pub const SLOT_SIZE: u32 = 8;
+pub const CHUNK_HEADER_SIZE: u32 = 8;
+const FORWARD_FLAG: u32 = 1 << 31;
+const CHUNK_FLAG: u32 = 1 << 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct RowId {
+    pub page: u32,
+    pub slot: u32,
+}
+
+impl RowId {
+    pub fn build(page: u32, slot: u32) -> RowId {
+        RowId { page, slot }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Slot {
+    /// Deleted, the slot number can be reused by the next insert.
+    Free,
+    Tuple {
+        offset: u32,
+        length: u32,
+        tuple: Tuple,
+    },
+    /// The tuple outgrew its page and now lives at the given row, or in the chain starting there.
+    Forward(RowId),
+    /// Part of a tuple too large for any page, chained to the following part.
+    Chunk {
+        offset: u32,
+        length: u32,
+        next: Option<RowId>,
+        bytes: Vec<u8>,
+    },
+}
+
+impl Slot {
+    pub fn region(&self) -> Option<(u32, u32)> {
+        match self {
+            Slot::Tuple { offset, length, .. } | Slot::Chunk { offset, length, .. } => {
+                Some((*offset, *length))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn tuple(&self) -> Option<&Tuple> {
+        match self {
+            Slot::Tuple { tuple, .. } => Some(tuple),
+            _ => None,
+        }
+    }
+
+    /// Directory entry, forwards are kept in the entry itself so relocating never needs space.
+    pub fn entry(&self) -> (u32, u32) {
+        match self {
+            Slot::Free => (0, 0),
+            Slot::Tuple { offset, length, .. } => (*offset, *length),
+            Slot::Forward(row) => (row.page, row.slot | FORWARD_FLAG),
+            Slot::Chunk { offset, length, .. } => (*offset, *length | CHUNK_FLAG),
+        }
+    }
+
+    pub fn forward(entry: (u32, u32)) -> Option<RowId> {
+        (entry.1 & FORWARD_FLAG != 0).then(|| RowId::build(entry.0, entry.1 & !FORWARD_FLAG))
+    }
+
+    pub fn chunk(entry: (u32, u32)) -> Option<(u32, u32)> {
+        (entry.1 & FORWARD_FLAG == 0 && entry.1 & CHUNK_FLAG != 0)
+            .then_some((entry.0, entry.1 & !CHUNK_FLAG))
+    }
+
+    pub fn chunk_bytes(next: Option<RowId>, bytes: &[u8]) -> Vec<u8> {
+        let next = next.map_or((u32::MAX, u32::MAX), |row| (row.page, row.slot));
+        let mut chunk = next.0.to_le_bytes().to_vec();
+        chunk.extend_from_slice(&next.1.to_le_bytes());
+        chunk.extend_from_slice(bytes);
+        chunk
+    }
+
+    pub fn from_chunk_bytes(offset: u32, bytes: &[u8]) -> Slot {
+        let page = u32::from_le_bytes(bytes[0..4].try_into().unwrap());
+        let slot = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+        Slot::Chunk {
+            offset,
+            length: bytes.len() as u32,
+            next: (page != u32::MAX).then_some(RowId::build(page, slot)),
+            bytes: bytes[CHUNK_HEADER_SIZE as usize..].to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_should_flag_forwards() {
+        let entry = Slot::Forward(RowId::build(3, 7)).entry();
+        assert_eq!(entry, (3, 7 | FORWARD_FLAG));
+        assert_eq!(Slot::forward(entry), Some(RowId::build(3, 7)));
+        assert_eq!(Slot::forward((120, 35)), None);
+        assert_eq!(Slot::Free.entry(), (0, 0));
+    }
+
+    #[test]
+    fn chunk_bytes_should_roundtrip() {
+        let bytes = Slot::chunk_bytes(Some(RowId::build(2, 5)), &[7, 7, 7]);
+        assert_eq!(bytes, [2, 0, 0, 0, 5, 0, 0, 0, 7, 7, 7]);
+        let chunk = Slot::from_chunk_bytes(40, &bytes);
+        assert_eq!(chunk.entry(), (40, 11 | CHUNK_FLAG));
+        assert_eq!(Slot::chunk(chunk.entry()), Some((40, 11)));
+        assert_eq!(Slot::chunk((40, 11)), None);
+        assert_eq!(
+            Slot::from_chunk_bytes(40, &Slot::chunk_bytes(None, &[1])),
+            Slot::Chunk {
+                offset: 40,
+                length: 9,
+                next: None,
+                bytes: vec![1],
+            }
+        );
+    }
+}
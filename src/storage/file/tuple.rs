@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::storage::file::error::Error;
 use crate::storage::file::tuple_header::TupleHeader;
+use crate::storage::schema::bitmap;
+use crate::storage::schema::value::Value;
 use crate::storage::schema::Schema;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -13,7 +15,8 @@ pub struct Tuple {
 
 impl Tuple {
     pub fn build(schema: &Schema, nulls: &[u8], data: &[u8]) -> Result<Tuple, Error> {
-        if schema.tuple_size(Some(nulls)) != data.len() {
+        schema.check_nulls(nulls)?;
+        if schema.tuple_size(Some(nulls), data) != Some(data.len()) {
             Err(Error::CorruptedTuple(format!(
                 "Data {:?} with nulls {:?} don't match with given schema {:?}",
                 data, nulls, schema
@@ -25,13 +28,56 @@ impl Tuple {
             })
         }
     }
+
+    pub fn from_values(schema: &Schema, values: &[Option<Value>]) -> Result<Tuple, Error> {
+        let (nulls, data) = schema.encode(values)?;
+        Tuple::build(schema, &nulls, &data)
+    }
+
+    pub fn values(&self, schema: &Schema) -> Result<Vec<Option<Value>>, Error> {
+        Ok(schema.decode(&self.header.nulls, &self.data)?)
+    }
+
+    pub fn from_legacy_bytes(bytes: &[u8]) -> Result<Tuple, Error> {
+        let (visibility, flags, data): (u8, Vec<u8>, Vec<u8>) = bincode::deserialize(bytes)?;
+        let flags: Vec<bool> = flags.iter().map(|flag| *flag != 0).collect();
+        Ok(Tuple {
+            header: TupleHeader {
+                visibility,
+                nulls: bitmap::pack(&flags),
+            },
+            data,
+        })
+    }
 }
 
-impl Encoding for Tuple {}
+impl Encoding for Tuple {
+    fn as_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = vec![self.header.visibility];
+        bytes.extend_from_slice(&self.header.nulls);
+        bytes.extend_from_slice(&self.data);
+        Ok(bytes)
+    }
+
+    fn from_bytes(bytes: &[u8], schema: Option<&Schema>) -> Result<Tuple, Error> {
+        let schema = schema.ok_or(Error::MissingSchema)?;
+        let header_size = 1 + schema.null_bitmap_size();
+        if bytes.len() < header_size {
+            return Err(Error::CorruptedTuple(format!(
+                "Tuple {:?} is shorter than its header",
+                bytes
+            )));
+        }
+        let mut tuple = Tuple::build(schema, &bytes[1..header_size], &bytes[header_size..])?;
+        tuple.header.visibility = bytes[0];
+        Ok(tuple)
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use crate::storage::file::encoding::Encoding;
+    use crate::storage::schema::encoding::Encoding as SchemaEncoding;
     use crate::storage::tests::get_test_schema;
 
     use super::*;
@@ -39,19 +85,80 @@ mod tests {
     #[test]
     #[should_panic]
     fn build_should_panic_if_data_dont_match_schema() {
-        Tuple::build(&get_test_schema(), &[0, 0, 1, 0], &[4; 33]).unwrap();
+        Tuple::build(&get_test_schema(), &[0b0100], &[4; 33]).unwrap();
+    }
+
+    #[test]
+    fn build_should_accept_string_data() {
+        let schema = Schema::from_str("id INT, name STRING").unwrap();
+        let tuple = Tuple::build(&schema, &[0], &[1, 0, 0, 0, 2, 0, 0, 0, 111, 107]).unwrap();
+        assert_eq!(tuple.data.len(), 10);
+    }
+
+    #[test]
+    #[should_panic]
+    fn build_should_panic_if_string_length_dont_match() {
+        let schema = Schema::from_str("id INT, name STRING").unwrap();
+        Tuple::build(&schema, &[0], &[1, 0, 0, 0, 3, 0, 0, 0, 111, 107]).unwrap();
+    }
+
+    #[test]
+    fn from_values_should_encode_row() {
+        let values = vec![
+            Some(Value::Bigint(42)),
+            Some(Value::Float(9.5)),
+            None,
+            Some(Value::Timestamp(1718000000000)),
+        ];
+        let tuple = Tuple::from_values(&get_test_schema(), &values).unwrap();
+        assert_eq!(tuple.header.nulls, vec![0b0100]);
+        assert_eq!(tuple.data.len(), 32);
+        assert_eq!(tuple.values(&get_test_schema()).unwrap(), values);
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_values_should_panic_if_type_mismatch() {
+        Tuple::from_values(
+            &get_test_schema(),
+            &[Some(Value::String("42".to_string())), None, None, None],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn build_should_panic_if_nulls_dont_match_fields() {
+        Tuple::build(&get_test_schema(), &[0b0100, 0], &[4; 32]).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn build_should_panic_if_not_null_column_is_null() {
+        let schema = Schema::from_str("id INT NOT NULL, name STRING").unwrap();
+        Tuple::build(&schema, &[0b01], &[0, 0, 0, 0]).unwrap();
+    }
+
+    #[test]
+    fn from_values_should_fill_defaults() {
+        let schema = Schema::from_str("id INT, status STRING NOT NULL DEFAULT 'new'").unwrap();
+        let tuple = Tuple::from_values(&schema, &[Some(Value::Int(3))]).unwrap();
+        assert_eq!(
+            tuple.values(&schema).unwrap(),
+            vec![Some(Value::Int(3)), Some(Value::String("new".to_string()))]
+        );
     }
 
     #[test]
     fn as_bytes_should_convert_tuple() {
         assert_eq!(
-            Tuple::build(&get_test_schema(), &[0, 0, 1, 0], &[4; 32])
+            Tuple::build(&get_test_schema(), &[0b0100], &[4; 32])
                 .unwrap()
                 .as_bytes()
                 .unwrap(),
             vec![
-                0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 32, 0, 0, 0, 0, 0, 0, 0, 4, 4, 4, 4, 4, 4,
-                4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4
+                0, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4,
+                4, 4, 4, 4, 4, 4
             ]
         )
     }
@@ -59,12 +166,33 @@ mod tests {
     #[test]
     fn from_bytes_should_convert_bytes() {
         assert_eq!(
-            Tuple::from_bytes(&[
+            Tuple::from_bytes(
+                &[
+                    0, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4,
+                    4, 4, 4, 4, 4, 4, 4, 4
+                ],
+                Some(&get_test_schema())
+            )
+            .unwrap(),
+            Tuple::build(&get_test_schema(), &[0b0100], &[4; 32]).unwrap()
+        )
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_bytes_should_panic_without_schema() {
+        Tuple::from_bytes(&[0, 0], None).unwrap();
+    }
+
+    #[test]
+    fn from_legacy_bytes_should_pack_null_flags() {
+        assert_eq!(
+            Tuple::from_legacy_bytes(&[
                 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 32, 0, 0, 0, 0, 0, 0, 0, 4, 4, 4, 4, 4, 4,
                 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4
-            ],)
+            ])
             .unwrap(),
-            Tuple::build(&get_test_schema(), &[0, 0, 1, 0], &[4; 32]).unwrap()
+            Tuple::build(&get_test_schema(), &[0b0100], &[4; 32]).unwrap()
         )
     }
 }
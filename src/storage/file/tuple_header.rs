@@ -1,4 +1,5 @@
 use crate::storage::file::encoding::Encoding;
+use crate::storage::schema::bitmap;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -14,6 +15,10 @@ impl TupleHeader {
             nulls: nulls.to_vec(),
         }
     }
+
+    pub fn is_null(&self, index: usize) -> bool {
+        bitmap::is_set(&self.nulls, index)
+    }
 }
 
 impl Encoding for TupleHeader {}
@@ -26,16 +31,23 @@ mod tests {
     #[test]
     fn as_bytes_should_convert_tuple_header() {
         assert_eq!(
-            TupleHeader::build(&[0, 1, 0, 1]).as_bytes().unwrap(),
-            [0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1]
+            TupleHeader::build(&[0b1010]).as_bytes().unwrap(),
+            [0, 1, 0, 0, 0, 0, 0, 0, 0, 10]
         )
     }
 
     #[test]
     fn from_bytes_should_convert_bytes() {
         assert_eq!(
-            TupleHeader::from_bytes(&[0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1]).unwrap(),
-            TupleHeader::build(&[0, 1, 0, 1])
+            TupleHeader::from_bytes(&[0, 1, 0, 0, 0, 0, 0, 0, 0, 10], None).unwrap(),
+            TupleHeader::build(&[0b1010])
         )
     }
+
+    #[test]
+    fn is_null_should_read_bitmap() {
+        let header = TupleHeader::build(&[0b1010]);
+        assert!(header.is_null(1));
+        assert!(!header.is_null(2));
+    }
 }
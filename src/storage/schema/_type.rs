@@ -3,7 +3,15 @@ use std::mem;
 use crate::storage::schema::encoding::Encoding;
 use serde::{Deserialize, Serialize};
 
+use crate::storage::schema::cast::Cast;
+use crate::storage::schema::decimal::MAX_PRECISION;
 use crate::storage::schema::error::Error;
+use crate::storage::schema::field::Field;
+use crate::storage::schema::parser;
+use crate::storage::schema::parser::Token;
+
+const DEFAULT_DECIMAL_PRECISION: u8 = 10;
+const MAX_ENUM_LABELS: usize = u8::MAX as usize + 1;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Type {
@@ -13,8 +21,28 @@ pub enum Type {
     Int,
     Bigint,
     Float,
+    Decimal {
+        precision: u8,
+        scale: u8,
+    },
+    /// Microseconds since 1970-01-01 00:00:00, without time zone.
     Timestamp,
-    String, // todo
+    /// Microseconds since 1970-01-01 00:00:00 UTC.
+    TimestampTz,
+    /// Days since 1970-01-01.
+    Date,
+    /// Microseconds since midnight.
+    Time,
+    /// Months, days and microseconds, kept apart as their lengths vary.
+    Interval,
+    String,
+    Binary,
+    Uuid,
+    /// Labels in declaration order, values are stored as the label index.
+    Enum(Vec<String>),
+    Array(Box<Type>),
+    Struct(Vec<Field>),
+    Map(Box<Type>, Box<Type>),
 }
 
 impl Type {
@@ -26,23 +54,102 @@ impl Type {
             Type::Int => mem::size_of::<i32>(),
             Type::Bigint => mem::size_of::<i128>(),
             Type::Float => mem::size_of::<f64>(),
+            Type::Decimal { .. } => mem::size_of::<i128>(),
             Type::Timestamp => mem::size_of::<i64>(),
-            Type::String => 0, // TODO
+            Type::TimestampTz => mem::size_of::<i64>(),
+            Type::Date => mem::size_of::<i32>(),
+            Type::Time => mem::size_of::<i64>(),
+            Type::Interval => mem::size_of::<i32>() * 2 + mem::size_of::<i64>(),
+            Type::String => mem::size_of::<u32>(),
+            Type::Binary => mem::size_of::<u32>(),
+            Type::Uuid => mem::size_of::<[u8; 16]>(),
+            Type::Enum(_) => mem::size_of::<u8>(),
+            Type::Array(_) => mem::size_of::<u32>(),
+            Type::Struct(fields) => fields.len(),
+            Type::Map(_, _) => mem::size_of::<u32>(),
+        }
+    }
+
+    pub fn get_value_size(&self, bytes: &[u8]) -> Option<usize> {
+        let prefix_size = self.get_byte_size();
+        let prefix = bytes.get(..prefix_size)?;
+        match self {
+            Type::String | Type::Binary => {
+                let length = u32::from_le_bytes(prefix.try_into().ok()?) as usize;
+                let value = bytes.get(prefix_size..prefix_size + length)?;
+                if *self == Type::String {
+                    std::str::from_utf8(value).ok()?;
+                }
+                Some(prefix_size + length)
+            }
+            Type::Array(element) => {
+                let count = u32::from_le_bytes(prefix.try_into().ok()?);
+                (0..count).try_fold(prefix_size, |offset, _| {
+                    Some(offset + element.get_nullable_size(bytes.get(offset..)?)?)
+                })
+            }
+            Type::Struct(fields) => fields.iter().try_fold(0, |offset, field| {
+                Some(offset + field.get_type().get_nullable_size(bytes.get(offset..)?)?)
+            }),
+            Type::Map(key, value) => {
+                let count = u32::from_le_bytes(prefix.try_into().ok()?);
+                (0..count).try_fold(prefix_size, |offset, _| {
+                    let offset = offset + key.get_value_size(bytes.get(offset..)?)?;
+                    Some(offset + value.get_nullable_size(bytes.get(offset..)?)?)
+                })
+            }
+            _ => Some(prefix_size),
+        }
+    }
+
+    pub fn widens_to(&self, other: &Type) -> bool {
+        self.cast_kind(other) == Cast::Implicit
+    }
+
+    pub fn get_nullable_size(&self, bytes: &[u8]) -> Option<usize> {
+        match bytes.first()? {
+            0 => Some(1 + self.get_value_size(&bytes[1..])?),
+            1 => Some(1),
+            _ => None,
         }
     }
 }
 
 impl Encoding<Type> for Type {
     fn from_str(type_str: &str) -> Result<Type, Error> {
-        match type_str.to_uppercase().trim() {
-            "BOOLEAN" => Ok(Type::Boolean),
-            "TINYINT" => Ok(Type::Tinyint),
-            "SMALLINT" => Ok(Type::Smallint),
-            "INT" => Ok(Type::Int),
-            "BIGINT" => Ok(Type::Bigint),
-            "FLOAT" => Ok(Type::Float),
-            "TIMESTAMP" => Ok(Type::Timestamp),
-            "STRING" => Ok(Type::String),
+        if let Some(nested) = nested(type_str)? {
+            return Ok(nested);
+        }
+        let type_upper = type_str.to_uppercase();
+        if type_upper.trim_start().starts_with("ENUM") {
+            if let Some(labels) = enum_labels(type_str)? {
+                return Ok(Type::Enum(labels));
+            }
+        }
+        let (name, parameters) = match type_upper.split_once('(') {
+            Some((name, parameters)) => {
+                (name.trim(), Some(parse_parameters(type_str, parameters)?))
+            }
+            None => (type_upper.trim(), None),
+        };
+        match (name, parameters.as_deref()) {
+            ("BOOLEAN", None) => Ok(Type::Boolean),
+            ("TINYINT", None) => Ok(Type::Tinyint),
+            ("SMALLINT", None) => Ok(Type::Smallint),
+            ("INT", None) => Ok(Type::Int),
+            ("BIGINT", None) => Ok(Type::Bigint),
+            ("FLOAT", None) => Ok(Type::Float),
+            ("DECIMAL", None) => decimal(type_str, DEFAULT_DECIMAL_PRECISION, 0),
+            ("DECIMAL", Some([precision])) => decimal(type_str, *precision, 0),
+            ("DECIMAL", Some([precision, scale])) => decimal(type_str, *precision, *scale),
+            ("TIMESTAMP", None) => Ok(Type::Timestamp),
+            ("TIMESTAMPTZ", None) => Ok(Type::TimestampTz),
+            ("DATE", None) => Ok(Type::Date),
+            ("TIME", None) => Ok(Type::Time),
+            ("INTERVAL", None) => Ok(Type::Interval),
+            ("STRING", None) => Ok(Type::String),
+            ("BINARY", None) | ("BLOB", None) => Ok(Type::Binary),
+            ("UUID", None) => Ok(Type::Uuid),
             _ => Err(Error::InvalidType(format!(
                 "\n- Unknown type \"{}\"",
                 type_str
@@ -51,10 +158,175 @@ impl Encoding<Type> for Type {
     }
 }
 
+fn nested(type_str: &str) -> Result<Option<Type>, Error> {
+    let (source, tokens) = parser::lex(type_str)?;
+    let (name, inner) = match tokens.as_slice() {
+        [(_, name), (_, Token::Symbol('<')), inner @ ..] => {
+            (name.name().unwrap_or_default(), inner)
+        }
+        _ => return Ok(None),
+    };
+    let inner = match inner {
+        [inner @ .., (_, Token::Symbol('>'))] => inner,
+        _ => {
+            return Err(Error::InvalidType(format!(
+                "\n- Unclosed nested type \"{}\"",
+                type_str
+            )))
+        }
+    };
+    let parts: Vec<&str> = parser::split_items(inner)
+        .into_iter()
+        .map(|(_, part)| parser::text(&source, part))
+        .collect();
+    let nested = match (name.to_uppercase().as_str(), parts.as_slice()) {
+        ("ARRAY", [element]) => Ok(Type::Array(Box::new(Type::from_str(element)?))),
+        ("MAP", [key, value]) => Ok(Type::Map(
+            Box::new(Type::from_str(key)?),
+            Box::new(Type::from_str(value)?),
+        )),
+        ("STRUCT", fields) if !fields.is_empty() => {
+            let fields = fields
+                .iter()
+                .map(|field| Field::from_str(field))
+                .collect::<Result<Vec<Field>, Error>>()?;
+            for (index, field) in fields.iter().enumerate() {
+                if field.get_generated().is_some() {
+                    return Err(Error::InvalidType(format!(
+                        "\n- Nested field \"{}\" can't be generated in type \"{}\"",
+                        field.get_name(),
+                        type_str
+                    )));
+                }
+                if fields[..index]
+                    .iter()
+                    .any(|other| parser::same_name(other.get_name(), field.get_name()))
+                {
+                    return Err(Error::InvalidType(format!(
+                        "\n- Duplicate field \"{}\" in type \"{}\"",
+                        field.get_name(),
+                        type_str
+                    )));
+                }
+            }
+            Ok(Type::Struct(fields))
+        }
+        _ => Err(Error::InvalidType(format!(
+            "\n- Unknown type \"{}\"",
+            type_str
+        ))),
+    };
+    nested.map(Some)
+}
+
+fn enum_labels(type_str: &str) -> Result<Option<Vec<String>>, Error> {
+    let error = |message: &str| {
+        Err(Error::InvalidType(format!(
+            "\n- {} in type \"{}\"",
+            message,
+            type_str.trim()
+        )))
+    };
+    let (_, tokens) = parser::lex(type_str)?;
+    let labels = match tokens.as_slice() {
+        [(_, name), (_, Token::Symbol('(')), labels @ .., (_, Token::Symbol(')'))]
+            if name.is_word("ENUM") =>
+        {
+            labels
+        }
+        [(_, name), ..] if name.is_word("ENUM") => {
+            return error("Expected a list of quoted labels")
+        }
+        _ => return Ok(None),
+    };
+    let labels = match labels
+        .split(|(_, token)| *token == Token::Symbol(','))
+        .map(|label| match label {
+            [(_, Token::String(label))] => Some(label.clone()),
+            _ => None,
+        })
+        .collect::<Option<Vec<String>>>()
+    {
+        Some(labels) => labels,
+        None => return error("Expected a list of quoted labels"),
+    };
+    if labels.len() > MAX_ENUM_LABELS {
+        return error(&format!("More than {} labels", MAX_ENUM_LABELS));
+    }
+    for (index, label) in labels.iter().enumerate() {
+        if labels[..index].contains(label) {
+            return error(&format!("Duplicate label '{}'", label));
+        }
+    }
+    Ok(Some(labels))
+}
+
+fn parse_parameters(type_str: &str, parameters: &str) -> Result<Vec<u8>, Error> {
+    parameters
+        .trim()
+        .strip_suffix(')')
+        .ok_or(Error::InvalidType(format!(
+            "\n- Unclosed parameters in type \"{}\"",
+            type_str
+        )))?
+        .split(',')
+        .map(|parameter| {
+            parameter.trim().parse::<u8>().map_err(|_| {
+                Error::InvalidType(format!(
+                    "\n- Invalid parameter \"{}\" in type \"{}\"",
+                    parameter.trim(),
+                    type_str
+                ))
+            })
+        })
+        .collect()
+}
+
+fn decimal(type_str: &str, precision: u8, scale: u8) -> Result<Type, Error> {
+    if precision == 0 || precision > MAX_PRECISION || scale > precision {
+        Err(Error::InvalidType(format!(
+            "\n- Invalid precision or scale in type \"{}\", expected scale <= precision <= {}",
+            type_str, MAX_PRECISION
+        )))
+    } else {
+        Ok(Type::Decimal { precision, scale })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn widens_to_should_only_allow_lossless_changes() {
+        assert!(Type::Int.widens_to(&Type::Bigint));
+        assert!(Type::Smallint.widens_to(&Type::Float));
+        assert!(Type::Date.widens_to(&Type::Timestamp));
+        assert!(Type::Int.widens_to(&Type::Decimal {
+            precision: 12,
+            scale: 2
+        }));
+        assert!(Type::Decimal {
+            precision: 6,
+            scale: 2
+        }
+        .widens_to(&Type::Decimal {
+            precision: 9,
+            scale: 3
+        }));
+        assert!(!Type::Decimal {
+            precision: 6,
+            scale: 2
+        }
+        .widens_to(&Type::Decimal {
+            precision: 6,
+            scale: 3
+        }));
+        assert!(!Type::Bigint.widens_to(&Type::Int));
+        assert!(!Type::Bigint.widens_to(&Type::Float));
+        assert!(!Type::String.widens_to(&Type::Binary));
+    }
+
     #[test]
     fn from_str_should_return_enum() {
         assert_eq!(Type::from_str("boolean").unwrap(), Type::Boolean);
@@ -64,7 +336,170 @@ mod tests {
         assert_eq!(Type::from_str("bigint").unwrap(), Type::Bigint);
         assert_eq!(Type::from_str("float").unwrap(), Type::Float);
         assert_eq!(Type::from_str("Timestamp").unwrap(), Type::Timestamp);
+        assert_eq!(Type::from_str("timestampTZ").unwrap(), Type::TimestampTz);
+        assert_eq!(Type::from_str("date").unwrap(), Type::Date);
+        assert_eq!(Type::from_str("TIME").unwrap(), Type::Time);
+        assert_eq!(Type::from_str("interval").unwrap(), Type::Interval);
         assert_eq!(Type::from_str("string").unwrap(), Type::String);
+        assert_eq!(Type::from_str("binary").unwrap(), Type::Binary);
+        assert_eq!(Type::from_str("BLOB").unwrap(), Type::Binary);
+        assert_eq!(Type::from_str("uuid").unwrap(), Type::Uuid);
+    }
+
+    #[test]
+    fn from_str_should_parse_decimal_parameters() {
+        assert_eq!(
+            Type::from_str("decimal(12, 2)").unwrap(),
+            Type::Decimal {
+                precision: 12,
+                scale: 2
+            }
+        );
+        assert_eq!(
+            Type::from_str("DECIMAL (5)").unwrap(),
+            Type::Decimal {
+                precision: 5,
+                scale: 0
+            }
+        );
+        assert_eq!(
+            Type::from_str("Decimal").unwrap(),
+            Type::Decimal {
+                precision: 10,
+                scale: 0
+            }
+        );
+    }
+
+    #[test]
+    fn from_str_should_parse_nested_types() {
+        assert_eq!(
+            Type::from_str("array<int>").unwrap(),
+            Type::Array(Box::new(Type::Int))
+        );
+        assert_eq!(
+            Type::from_str("MAP<STRING, ARRAY<DECIMAL(4, 1)>>").unwrap(),
+            Type::Map(
+                Box::new(Type::String),
+                Box::new(Type::Array(Box::new(Type::Decimal {
+                    precision: 4,
+                    scale: 1
+                })))
+            )
+        );
+        assert_eq!(
+            Type::from_str("STRUCT<name STRING, tags ARRAY<STRING>>").unwrap(),
+            Type::Struct(vec![
+                Field::build("name".to_string(), Type::String),
+                Field::build("tags".to_string(), Type::Array(Box::new(Type::String))),
+            ])
+        );
+        assert_eq!(
+            Type::from_str("STRUCT<\"a, <b>\" STRING, c MAP<INT, INT>>").unwrap(),
+            Type::Struct(vec![
+                Field::build("a, <b>".to_string(), Type::String),
+                Field::build(
+                    "c".to_string(),
+                    Type::Map(Box::new(Type::Int), Box::new(Type::Int))
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn from_str_should_parse_enum_labels() {
+        assert_eq!(
+            Type::from_str("enum('new', 'In Progress', 'it''s done')").unwrap(),
+            Type::Enum(vec![
+                "new".to_string(),
+                "In Progress".to_string(),
+                "it's done".to_string()
+            ])
+        );
+        assert_eq!(
+            Type::from_str("ARRAY<ENUM('a', 'b,c')>").unwrap(),
+            Type::Array(Box::new(Type::Enum(vec![
+                "a".to_string(),
+                "b,c".to_string()
+            ])))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_str_should_panic_if_duplicate_enum_label() {
+        Type::from_str("ENUM('a', 'b', 'a')").unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_str_should_panic_if_enum_label_not_quoted() {
+        Type::from_str("ENUM(a, b)").unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_str_should_panic_if_map_without_value() {
+        Type::from_str("MAP<STRING>").unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_str_should_panic_if_nested_unclosed() {
+        Type::from_str("ARRAY<INT").unwrap();
+    }
+
+    #[test]
+    fn get_value_size_should_walk_nested_values() {
+        let array = Type::Array(Box::new(Type::Smallint));
+        assert_eq!(array.get_value_size(&[2, 0, 0, 0, 0, 7, 0, 1, 9]), Some(8));
+        let _struct = Type::Struct(vec![
+            Field::build("a".to_string(), Type::String),
+            Field::build("b".to_string(), Type::Boolean),
+        ]);
+        assert_eq!(_struct.get_value_size(&[1, 0, 1]), Some(3));
+        let map = Type::Map(Box::new(Type::Tinyint), Box::new(Type::Tinyint));
+        assert_eq!(map.get_value_size(&[2, 0, 0, 0, 1, 0, 5, 2, 1]), Some(9));
+        assert_eq!(map.get_value_size(&[2, 0, 0, 0, 1, 0, 5, 2]), None);
+        assert_eq!(array.get_value_size(&[1, 0, 0, 0, 3, 0, 0]), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_str_should_panic_if_scale_greater_than_precision() {
+        Type::from_str("DECIMAL(4, 6)").unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_str_should_panic_if_precision_too_large() {
+        Type::from_str("DECIMAL(39, 2)").unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_str_should_panic_if_parameters_on_scalar() {
+        Type::from_str("INT(4)").unwrap();
+    }
+
+    #[test]
+    fn get_value_size_should_read_length_prefix() {
+        assert_eq!(
+            Type::String.get_value_size(&[3, 0, 0, 0, 97, 98, 99, 1]),
+            Some(7)
+        );
+        assert_eq!(Type::String.get_value_size(&[0, 0, 0, 0]), Some(4));
+        assert_eq!(Type::Int.get_value_size(&[1, 2, 3, 4, 5]), Some(4));
+        assert_eq!(Type::Binary.get_value_size(&[1, 0, 0, 0, 255]), Some(5));
+        assert_eq!(Type::Uuid.get_value_size(&[0; 20]), Some(16));
+    }
+
+    #[test]
+    fn get_value_size_should_return_none_if_truncated() {
+        assert_eq!(Type::String.get_value_size(&[3, 0, 0, 0, 97]), None);
+        assert_eq!(Type::String.get_value_size(&[3, 0]), None);
+        assert_eq!(Type::String.get_value_size(&[1, 0, 0, 0, 255]), None);
+        assert_eq!(Type::Bigint.get_value_size(&[1; 8]), None);
     }
 
     #[test]
@@ -0,0 +1,356 @@
+use serde::{Deserialize, Serialize};
+
+use crate::storage::schema::_type::Type;
+use crate::storage::schema::check::Check;
+use crate::storage::schema::encoding::Encoding;
+use crate::storage::schema::error::Error;
+use crate::storage::schema::expression::Expression;
+use crate::storage::schema::field::Field;
+use crate::storage::schema::generated::Generated;
+use crate::storage::schema::parser;
+use crate::storage::schema::value::Value;
+use crate::storage::schema::Schema;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Alteration {
+    AddColumn(Field),
+    DropColumn(String),
+    RenameColumn(String, String),
+    WidenColumn(String, Type),
+}
+
+impl Alteration {
+    pub fn add_column(definition: &str) -> Result<Alteration, Error> {
+        Ok(Alteration::AddColumn(Field::from_str(definition)?))
+    }
+
+    pub fn drop_column(name: &str) -> Alteration {
+        Alteration::DropColumn(name.to_string())
+    }
+
+    pub fn rename_column(from: &str, to: &str) -> Alteration {
+        Alteration::RenameColumn(from.to_string(), to.to_string())
+    }
+
+    pub fn widen_column(name: &str, type_str: &str) -> Result<Alteration, Error> {
+        Ok(Alteration::WidenColumn(
+            name.to_string(),
+            Type::from_str(type_str)?,
+        ))
+    }
+
+    pub fn apply(&self, schema: &Schema) -> Result<Schema, Error> {
+        let mut altered = schema.clone();
+        match self {
+            Alteration::AddColumn(field) => {
+                ensure_new_name(schema, field.get_name(), None)?;
+                if field.is_not_null() && field.get_default().is_none() {
+                    return Err(invalid(format!(
+                        "Column \"{}\" is NOT NULL and needs a default for existing rows",
+                        field.get_name()
+                    )));
+                }
+                altered.fields.push(field.clone());
+                altered.check_generated(field)?;
+            }
+            Alteration::DropColumn(name) => {
+                let index = position(schema, name)?;
+                if let Some(key) = schema.keys.iter().find(|key| {
+                    key.columns
+                        .iter()
+                        .any(|column| parser::same_name(column, name))
+                }) {
+                    return Err(invalid(format!("Column \"{}\" is used by {}", name, key)));
+                }
+                if let Some(check) = schema
+                    .checks
+                    .iter()
+                    .find(|check| used_by(&check.expression, name))
+                {
+                    return Err(invalid(format!(
+                        "Column \"{}\" is used by check constraint \"{}\"",
+                        name, check.name
+                    )));
+                }
+                if let Some(field) = schema.fields.iter().find(|field| {
+                    field
+                        .get_generated()
+                        .is_some_and(|generated| used_by(&generated.expression, name))
+                }) {
+                    return Err(invalid(format!(
+                        "Column \"{}\" is used by generated column \"{}\"",
+                        name,
+                        field.get_name()
+                    )));
+                }
+                if schema.fields.len() == 1 {
+                    return Err(invalid(format!("Can't drop \"{}\", the last column", name)));
+                }
+                altered.fields.remove(index);
+            }
+            Alteration::RenameColumn(from, to) => {
+                let index = position(schema, from)?;
+                ensure_new_name(schema, to, Some(index))?;
+                altered.fields[index].set_name(to);
+                for key in altered.keys.iter_mut() {
+                    for column in key
+                        .columns
+                        .iter_mut()
+                        .filter(|column| parser::same_name(column, from))
+                    {
+                        *column = to.to_string();
+                    }
+                }
+                for field in altered.fields.iter_mut() {
+                    if let Some(generated) = field.get_generated() {
+                        let expression = generated.expression.rename_column(from, to)?;
+                        field.set_generated(Some(Generated::build(generated.kind, expression)));
+                    }
+                }
+                altered.checks = schema
+                    .checks
+                    .iter()
+                    .map(|check| {
+                        Ok(Check::build(
+                            check.name.clone(),
+                            check.expression.rename_column(from, to)?,
+                        ))
+                    })
+                    .collect::<Result<Vec<Check>, Error>>()?;
+            }
+            Alteration::WidenColumn(name, _type) => {
+                let index = position(schema, name)?;
+                let field = &schema.fields[index];
+                if !field.get_type().widens_to(_type) {
+                    return Err(invalid(format!(
+                        "Column \"{}\" can't be widened from {:?} to {:?}",
+                        name,
+                        field.get_type(),
+                        _type
+                    )));
+                }
+                let default = match field.get_default() {
+                    Some(value) => Some(
+                        value
+                            .coerce(_type)
+                            .map_err(|err| invalid(format!("Default of \"{}\": {}", name, err)))?,
+                    ),
+                    None => None,
+                };
+                altered.fields[index].set_type(_type.clone(), default);
+            }
+        }
+        Ok(altered)
+    }
+
+    pub fn migrate(
+        &self,
+        schema: &Schema,
+        mut values: Vec<Option<Value>>,
+    ) -> Result<Vec<Option<Value>>, Error> {
+        if values.len() != schema.fields.len() {
+            return Err(Error::InvalidValue(format!(
+                "Row of {} values doesn't match {} columns",
+                values.len(),
+                schema.fields.len()
+            )));
+        }
+        match self {
+            Alteration::AddColumn(field) => {
+                let value = match field.get_generated() {
+                    Some(generated) => generated.compute(schema, &values, field.get_type())?,
+                    None => field.get_default().cloned(),
+                };
+                values.push(value);
+            }
+            Alteration::DropColumn(name) => {
+                values.remove(position(schema, name)?);
+            }
+            Alteration::RenameColumn(_, _) => {}
+            Alteration::WidenColumn(name, _type) => {
+                let index = position(schema, name)?;
+                if let Some(value) = &values[index] {
+                    values[index] = Some(value.coerce(_type)?);
+                }
+            }
+        }
+        Ok(values)
+    }
+}
+
+fn position(schema: &Schema, name: &str) -> Result<usize, Error> {
+    schema
+        .index_of(name)
+        .ok_or_else(|| invalid(format!("Unknown column \"{}\"", name)))
+}
+
+fn ensure_new_name(schema: &Schema, name: &str, renamed: Option<usize>) -> Result<(), Error> {
+    match schema
+        .fields
+        .iter()
+        .enumerate()
+        .find(|(index, field)| Some(*index) != renamed && parser::same_name(field.get_name(), name))
+    {
+        Some((_, field)) => Err(invalid(format!(
+            "Column \"{}\" already exists",
+            field.get_name()
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn used_by(expression: &Expression, name: &str) -> bool {
+    expression
+        .columns()
+        .iter()
+        .any(|column| parser::same_name(column, name))
+}
+
+fn invalid(message: String) -> Error {
+    Error::InvalidSchema(format!("\n- {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> Schema {
+        Schema::from_str("id INT PRIMARY KEY, qty SMALLINT DEFAULT 1, name STRING, CHECK (qty > 0)")
+            .unwrap()
+    }
+
+    #[test]
+    fn apply_should_add_column_with_default() {
+        let schema = Alteration::add_column("day DATE NOT NULL DEFAULT '2024-06-10'")
+            .unwrap()
+            .apply(&schema())
+            .unwrap();
+        assert_eq!(schema.fields[3].get_name(), "day");
+        assert_eq!(schema.fields[3].get_default(), Some(&Value::Date(19884)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn apply_should_panic_if_not_null_without_default() {
+        Alteration::add_column("day DATE NOT NULL")
+            .unwrap()
+            .apply(&schema())
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn apply_should_panic_if_dropped_column_in_key() {
+        Alteration::drop_column("id").apply(&schema()).unwrap();
+    }
+
+    #[test]
+    fn apply_should_rename_column_in_keys_and_checks() {
+        let schema = Alteration::rename_column("qty", "quantity")
+            .apply(&schema())
+            .unwrap();
+        assert_eq!(schema.fields[1].get_name(), "quantity");
+        assert_eq!(schema.checks[0].expression.to_string(), "quantity > 0");
+        let schema = Alteration::rename_column("id", "key")
+            .apply(&schema)
+            .unwrap();
+        assert_eq!(schema.primary_key().unwrap().columns, vec!["key"]);
+    }
+
+    #[test]
+    fn apply_should_match_names_regardless_of_case() {
+        let schema = Alteration::rename_column("QTY", "quantity")
+            .apply(&schema())
+            .unwrap();
+        assert_eq!(schema.checks[0].expression.to_string(), "quantity > 0");
+        assert!(Alteration::drop_column("Id").apply(&schema).is_err());
+        assert!(Alteration::drop_column("QUANTITY").apply(&schema).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn apply_should_panic_if_name_differs_only_by_case() {
+        Alteration::add_column("NAME STRING")
+            .unwrap()
+            .apply(&schema())
+            .unwrap();
+    }
+
+    #[test]
+    fn apply_should_track_generated_columns() {
+        let schema = Alteration::add_column("double INT GENERATED ALWAYS AS (qty * 2) STORED")
+            .unwrap()
+            .apply(&schema())
+            .unwrap();
+        let schema = Alteration::rename_column("qty", "quantity")
+            .apply(&schema)
+            .unwrap();
+        assert_eq!(
+            schema.fields[3]
+                .get_generated()
+                .unwrap()
+                .expression
+                .to_string(),
+            "quantity * 2"
+        );
+        assert!(Alteration::drop_column("name").apply(&schema).is_ok());
+        let values = vec![Some(Value::Int(1)), Some(Value::Smallint(4)), None];
+        let values = Alteration::add_column("half INT GENERATED ALWAYS AS (qty / 2)")
+            .unwrap()
+            .migrate(&self::schema(), values)
+            .unwrap();
+        assert_eq!(values[3], Some(Value::Int(2)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn apply_should_panic_if_dropped_column_in_generated() {
+        let schema = Alteration::add_column("double INT GENERATED ALWAYS AS (qty * 2)")
+            .unwrap()
+            .apply(&schema())
+            .unwrap();
+        Alteration::drop_column("qty").apply(&schema).unwrap();
+    }
+
+    #[test]
+    fn apply_should_widen_type_and_default() {
+        let schema = Alteration::widen_column("qty", "BIGINT")
+            .unwrap()
+            .apply(&schema())
+            .unwrap();
+        assert_eq!(*schema.fields[1].get_type(), Type::Bigint);
+        assert_eq!(schema.fields[1].get_default(), Some(&Value::Bigint(1)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn apply_should_panic_if_narrowing() {
+        Alteration::widen_column("qty", "TINYINT")
+            .unwrap()
+            .apply(&schema())
+            .unwrap();
+    }
+
+    #[test]
+    fn migrate_should_convert_values() {
+        let schema = schema();
+        let values = vec![Some(Value::Int(1)), Some(Value::Smallint(2)), None];
+        let values = Alteration::widen_column("qty", "INT")
+            .unwrap()
+            .migrate(&schema, values)
+            .unwrap();
+        assert_eq!(values[1], Some(Value::Int(2)));
+        let values = Alteration::drop_column("name")
+            .migrate(&schema, values)
+            .unwrap();
+        assert_eq!(values, vec![Some(Value::Int(1)), Some(Value::Int(2))]);
+    }
+
+    #[test]
+    fn migrate_should_reject_short_rows() {
+        assert!(matches!(
+            Alteration::drop_column("name").migrate(&schema(), vec![Some(Value::Int(1))]),
+            Err(Error::InvalidValue(_))
+        ));
+    }
+}
@@ -0,0 +1,90 @@
+use crate::storage::schema::error::Error;
+
+const UUID_GROUPS: [usize; 5] = [4, 2, 2, 2, 6];
+
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub fn decode_hex(hex_str: &str) -> Result<Vec<u8>, Error> {
+    let trimmed = hex_str.trim();
+    let digits = trimmed
+        .strip_prefix("0x")
+        .or(trimmed.strip_prefix("0X"))
+        .unwrap_or(trimmed);
+    if !digits.is_ascii() || !digits.len().is_multiple_of(2) {
+        return Err(Error::InvalidValue(format!("Invalid hex \"{}\"", hex_str)));
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|index| {
+            u8::from_str_radix(&digits[index..index + 2], 16)
+                .map_err(|_| Error::InvalidValue(format!("Invalid hex \"{}\"", hex_str)))
+        })
+        .collect()
+}
+
+pub fn format_uuid(uuid: &[u8; 16]) -> String {
+    let mut groups: Vec<String> = Vec::with_capacity(UUID_GROUPS.len());
+    let mut offset = 0;
+    for length in UUID_GROUPS {
+        groups.push(encode_hex(&uuid[offset..offset + length]));
+        offset += length;
+    }
+    groups.join("-")
+}
+
+pub fn parse_uuid(uuid_str: &str) -> Result<[u8; 16], Error> {
+    let error = || Error::InvalidValue(format!("Invalid uuid \"{}\"", uuid_str));
+    let groups: Vec<&str> = uuid_str.trim().split('-').collect();
+    if groups.len() != UUID_GROUPS.len()
+        || groups
+            .iter()
+            .zip(UUID_GROUPS)
+            .any(|(group, length)| group.len() != length * 2)
+    {
+        return Err(error());
+    }
+    decode_hex(&groups.concat())?
+        .try_into()
+        .map_err(|_| error())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_should_round_trip() {
+        assert_eq!(encode_hex(&[0, 15, 171, 255]), "000fabff");
+        assert_eq!(decode_hex("000FABff").unwrap(), vec![0, 15, 171, 255]);
+        assert_eq!(decode_hex("0x0a").unwrap(), vec![10]);
+        assert_eq!(decode_hex("").unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    #[should_panic]
+    fn decode_hex_should_panic_if_odd_length() {
+        decode_hex("abc").unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn decode_hex_should_panic_if_not_hex() {
+        decode_hex("zz").unwrap();
+    }
+
+    #[test]
+    fn uuid_should_use_canonical_form() {
+        let uuid = parse_uuid("123E4567-e89b-12d3-a456-426614174000").unwrap();
+        assert_eq!(uuid[0], 0x12);
+        assert_eq!(uuid[15], 0x00);
+        assert_eq!(format_uuid(&uuid), "123e4567-e89b-12d3-a456-426614174000");
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_uuid_should_panic_if_misplaced_dash() {
+        parse_uuid("123e4567e-89b-12d3-a456-426614174000").unwrap();
+    }
+}
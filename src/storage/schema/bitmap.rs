@@ -0,0 +1,43 @@
+pub fn width(columns: usize) -> usize {
+    columns.div_ceil(8)
+}
+
+pub fn pack(flags: &[bool]) -> Vec<u8> {
+    let mut bitmap = vec![0; width(flags.len())];
+    for (index, flag) in flags.iter().enumerate() {
+        if *flag {
+            bitmap[index / 8] |= 1 << (index % 8);
+        }
+    }
+    bitmap
+}
+
+pub fn is_set(bitmap: &[u8], index: usize) -> bool {
+    bitmap
+        .get(index / 8)
+        .is_some_and(|byte| byte >> (index % 8) & 1 == 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_should_set_one_bit_per_flag() {
+        assert_eq!(pack(&[true, false, false, true]), vec![0b1001]);
+        assert_eq!(pack(&[false; 9]), vec![0, 0]);
+        assert_eq!(pack(&[]), Vec::<u8>::new());
+        let mut flags = [false; 10];
+        flags[9] = true;
+        assert_eq!(pack(&flags), vec![0, 0b10]);
+    }
+
+    #[test]
+    fn is_set_should_read_flag() {
+        let bitmap = pack(&[false, true, false, false, false, false, false, false, true]);
+        assert!(is_set(&bitmap, 1));
+        assert!(is_set(&bitmap, 8));
+        assert!(!is_set(&bitmap, 0));
+        assert!(!is_set(&bitmap, 42));
+    }
+}
@@ -0,0 +1,387 @@
+use crate::storage::schema::_type::Type;
+use crate::storage::schema::decimal::Decimal;
+use crate::storage::schema::encoding::Encoding;
+use crate::storage::schema::error::Error;
+use crate::storage::schema::temporal::MICROS_PER_SECOND;
+use crate::storage::schema::value::Value;
+
+const MICROS_PER_DAY: i64 = 86_400 * MICROS_PER_SECOND;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Cast {
+    Implicit,
+    Explicit,
+    Rejected,
+}
+
+impl Type {
+    pub fn cast_kind(&self, target: &Type) -> Cast {
+        match (self, target) {
+            _ if self == target => Cast::Implicit,
+            (Type::Array(from), Type::Array(to)) => from.cast_kind(to),
+            (Type::Map(from_key, from_value), Type::Map(to_key, to_value)) => from_key
+                .cast_kind(to_key)
+                .max(from_value.cast_kind(to_value)),
+            (Type::Struct(from), Type::Struct(to)) if from.len() == to.len() => from
+                .iter()
+                .zip(to)
+                .map(|(from, to)| from.get_type().cast_kind(to.get_type()))
+                .max()
+                .unwrap_or(Cast::Implicit),
+            (Type::Enum(from), Type::Enum(to)) if to.starts_with(from) => Cast::Implicit,
+            (Type::Enum(_), Type::Enum(_)) => Cast::Explicit,
+            (Type::Enum(_), Type::String) | (Type::String, Type::Enum(_)) => Cast::Explicit,
+            _ if is_nested(self) || is_nested(target) => Cast::Rejected,
+            (Type::Enum(_), _) | (_, Type::Enum(_)) => Cast::Rejected,
+            _ if widens(self, target) => Cast::Implicit,
+            (Type::String, _) | (_, Type::String) => Cast::Explicit,
+            _ if is_numeric(self) && is_numeric(target) => Cast::Explicit,
+            (Type::Boolean, other) | (other, Type::Boolean) if is_integer(other) => Cast::Explicit,
+            (
+                Type::Date | Type::Timestamp | Type::TimestampTz,
+                Type::Date | Type::Timestamp | Type::TimestampTz,
+            )
+            | (Type::Timestamp | Type::TimestampTz, Type::Time)
+            | (Type::Binary, Type::Uuid)
+            | (Type::Uuid, Type::Binary) => Cast::Explicit,
+            _ => Cast::Rejected,
+        }
+    }
+}
+
+impl Value {
+    pub fn coerce(&self, target: &Type) -> Result<Value, Error> {
+        self.convert(target, Cast::Implicit)
+    }
+
+    pub fn cast(&self, target: &Type) -> Result<Value, Error> {
+        self.convert(target, Cast::Explicit)
+    }
+
+    fn convert(&self, target: &Type, allowed: Cast) -> Result<Value, Error> {
+        let nullable = |value: &Option<Value>, _type: &Type| {
+            value
+                .as_ref()
+                .map(|value| value.convert(_type, allowed))
+                .transpose()
+        };
+        match (self, target) {
+            (Value::Array(values), Type::Array(element)) => Ok(Value::Array(
+                values
+                    .iter()
+                    .map(|value| nullable(value, element))
+                    .collect::<Result<Vec<Option<Value>>, Error>>()?,
+            )),
+            (Value::Struct(values), Type::Struct(fields)) if values.len() == fields.len() => {
+                Ok(Value::Struct(
+                    values
+                        .iter()
+                        .zip(fields)
+                        .map(|(value, field)| nullable(value, field.get_type()))
+                        .collect::<Result<Vec<Option<Value>>, Error>>()?,
+                ))
+            }
+            (Value::Map(entries), Type::Map(key_type, value_type)) => Ok(Value::Map(
+                entries
+                    .iter()
+                    .map(|(key, value)| {
+                        Ok((
+                            key.convert(key_type, allowed)?,
+                            nullable(value, value_type)?,
+                        ))
+                    })
+                    .collect::<Result<Vec<(Value, Option<Value>)>, Error>>()?,
+            )),
+            // Labels are looked up by name, string literals included, so that inserting
+            // 'done' into an enum column needs no explicit cast.
+            (Value::String(label) | Value::Enum(_, label), Type::Enum(labels)) => {
+                Value::from_label(labels, label)
+            }
+            (Value::Enum(_, label), Type::String) if allowed >= Cast::Explicit => {
+                Ok(Value::String(label.clone()))
+            }
+            (Value::Enum(_, _), Type::String) => Err(Error::InvalidValue(format!(
+                "{} needs an explicit cast to {:?}",
+                self, target
+            ))),
+            _ => match self
+                .cast_type()
+                .map_or(Cast::Rejected, |_type| _type.cast_kind(target))
+            {
+                Cast::Rejected => Err(Error::InvalidValue(format!(
+                    "Can't cast {} to {:?}",
+                    self, target
+                ))),
+                kind if kind > allowed => Err(Error::InvalidValue(format!(
+                    "{} needs an explicit cast to {:?}",
+                    self, target
+                ))),
+                _ => self.convert_scalar(target),
+            },
+        }
+    }
+
+    fn cast_type(&self) -> Option<Type> {
+        match self {
+            Value::Decimal(value) => Some(Type::Decimal {
+                precision: value.precision().max(value.scale()),
+                scale: value.scale(),
+            }),
+            _ => self.scalar_type(),
+        }
+    }
+
+    fn convert_scalar(&self, target: &Type) -> Result<Value, Error> {
+        let overflow = || Error::InvalidValue(format!("{} is out of range for {:?}", self, target));
+        if let Some(value) = self.fit(target) {
+            return Ok(value);
+        }
+        Ok(match (self, target) {
+            (Value::String(text), _) => Value::parse(target, text)?,
+            (_, Type::String) => Value::String(self.to_string()),
+            (Value::Boolean(value), _) => Value::Tinyint(*value as i8).convert_scalar(target)?,
+            (_, Type::Boolean) => Value::Boolean(integer(self).ok_or_else(overflow)? != 0),
+            (Value::Date(days), Type::Timestamp | Type::TimestampTz) => {
+                let micros = (*days as i64)
+                    .checked_mul(MICROS_PER_DAY)
+                    .ok_or_else(overflow)?;
+                match target {
+                    Type::Timestamp => Value::Timestamp(micros),
+                    _ => Value::TimestampTz(micros),
+                }
+            }
+            (Value::Timestamp(micros) | Value::TimestampTz(micros), Type::Date) => Value::Date(
+                i32::try_from(micros.div_euclid(MICROS_PER_DAY)).map_err(|_| overflow())?,
+            ),
+            (Value::Timestamp(micros) | Value::TimestampTz(micros), Type::Time) => {
+                Value::Time(micros.rem_euclid(MICROS_PER_DAY))
+            }
+            (Value::Timestamp(micros), Type::TimestampTz) => Value::TimestampTz(*micros),
+            (Value::TimestampTz(micros), Type::Timestamp) => Value::Timestamp(*micros),
+            (Value::Binary(bytes), Type::Uuid) => Value::Uuid(
+                bytes
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| Error::InvalidValue(format!("{} isn't a 16 bytes UUID", self)))?,
+            ),
+            (Value::Uuid(bytes), Type::Binary) => Value::Binary(bytes.to_vec()),
+            _ => {
+                let decimal = match self {
+                    Value::Decimal(value) => *value,
+                    Value::Float(value) => {
+                        Decimal::from_str(&value.to_string()).map_err(|_| overflow())?
+                    }
+                    _ => Decimal::build(integer(self).ok_or_else(overflow)?, 0)?,
+                };
+                let rounded = || decimal.rescale(0).map(|value| value.value());
+                match target {
+                    Type::Float => {
+                        Value::Float(decimal.to_string().parse().map_err(|_| overflow())?)
+                    }
+                    Type::Decimal { precision, scale } => Value::Decimal(
+                        decimal
+                            .rescale(*scale)
+                            .filter(|value| value.precision() <= *precision)
+                            .ok_or_else(overflow)?,
+                    ),
+                    Type::Tinyint => Value::Tinyint(
+                        rounded()
+                            .and_then(|value| value.try_into().ok())
+                            .ok_or_else(overflow)?,
+                    ),
+                    Type::Smallint => Value::Smallint(
+                        rounded()
+                            .and_then(|value| value.try_into().ok())
+                            .ok_or_else(overflow)?,
+                    ),
+                    Type::Int => Value::Int(
+                        rounded()
+                            .and_then(|value| value.try_into().ok())
+                            .ok_or_else(overflow)?,
+                    ),
+                    Type::Bigint => Value::Bigint(rounded().ok_or_else(overflow)?),
+                    _ => {
+                        return Err(Error::InvalidValue(format!(
+                            "Can't cast {} to {:?}",
+                            self, target
+                        )))
+                    }
+                }
+            }
+        })
+    }
+}
+
+fn integer(value: &Value) -> Option<i128> {
+    match value {
+        Value::Tinyint(value) => Some(*value as i128),
+        Value::Smallint(value) => Some(*value as i128),
+        Value::Int(value) => Some(*value as i128),
+        Value::Bigint(value) => Some(*value),
+        _ => None,
+    }
+}
+
+fn is_integer(_type: &Type) -> bool {
+    matches!(
+        _type,
+        Type::Tinyint | Type::Smallint | Type::Int | Type::Bigint
+    )
+}
+
+fn is_numeric(_type: &Type) -> bool {
+    is_integer(_type) || matches!(_type, Type::Float | Type::Decimal { .. })
+}
+
+fn is_nested(_type: &Type) -> bool {
+    matches!(_type, Type::Array(_) | Type::Struct(_) | Type::Map(_, _))
+}
+
+fn widens(from: &Type, to: &Type) -> bool {
+    let integer_digits = |_type: &Type| match _type {
+        Type::Tinyint => Some(3),
+        Type::Smallint => Some(5),
+        Type::Int => Some(10),
+        _ => None,
+    };
+    match (from, to) {
+        (Type::Tinyint, Type::Smallint | Type::Int | Type::Bigint)
+        | (Type::Smallint, Type::Int | Type::Bigint)
+        | (Type::Int, Type::Bigint)
+        | (Type::Tinyint | Type::Smallint | Type::Int, Type::Float)
+        | (Type::Date, Type::Timestamp) => true,
+        (
+            Type::Decimal { precision, scale },
+            Type::Decimal {
+                precision: to_precision,
+                scale: to_scale,
+            },
+        ) => to_scale >= scale && to_precision - to_scale >= precision - scale,
+        (_, Type::Decimal { precision, scale }) => {
+            integer_digits(from).is_some_and(|digits| precision - scale >= digits)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimal(precision: u8, scale: u8) -> Type {
+        Type::Decimal { precision, scale }
+    }
+
+    #[test]
+    fn cast_kind_should_classify_conversions() {
+        assert_eq!(Type::Tinyint.cast_kind(&Type::Int), Cast::Implicit);
+        assert_eq!(Type::Int.cast_kind(&decimal(12, 2)), Cast::Implicit);
+        assert_eq!(Type::String.cast_kind(&Type::Timestamp), Cast::Explicit);
+        assert_eq!(Type::Bigint.cast_kind(&Type::Tinyint), Cast::Explicit);
+        assert_eq!(Type::Float.cast_kind(&Type::Int), Cast::Explicit);
+        assert_eq!(Type::Date.cast_kind(&Type::Time), Cast::Rejected);
+        assert_eq!(Type::Uuid.cast_kind(&Type::Int), Cast::Rejected);
+        assert_eq!(
+            Type::Array(Box::new(Type::Int)).cast_kind(&Type::Array(Box::new(Type::Bigint))),
+            Cast::Implicit
+        );
+        assert_eq!(
+            Type::Array(Box::new(Type::Int)).cast_kind(&Type::String),
+            Cast::Rejected
+        );
+    }
+
+    #[test]
+    fn coerce_should_only_apply_implicit_casts() {
+        assert_eq!(Value::Int(7).coerce(&Type::Bigint), Ok(Value::Bigint(7)));
+        assert_eq!(
+            Value::Tinyint(-2).coerce(&Type::Float),
+            Ok(Value::Float(-2.0))
+        );
+        assert_eq!(
+            Value::Smallint(12).coerce(&decimal(7, 2)),
+            Ok(Value::Decimal(Decimal::build(1200, 2).unwrap()))
+        );
+        assert_eq!(
+            Value::Date(1).coerce(&Type::Timestamp),
+            Ok(Value::Timestamp(86_400_000_000))
+        );
+        assert!(Value::Bigint(3).coerce(&Type::Tinyint).is_err());
+        assert!(Value::String("1".to_string()).coerce(&Type::Int).is_err());
+    }
+
+    #[test]
+    fn cast_should_convert_explicitly() {
+        assert_eq!(
+            Value::String("2024-06-10 12:00:00".to_string()).cast(&Type::Timestamp),
+            Ok(Value::Timestamp(1_718_020_800_000_000))
+        );
+        assert_eq!(Value::Float(2.5).cast(&Type::Int), Ok(Value::Int(3)));
+        assert_eq!(
+            Value::Decimal(Decimal::build(-1234, 2).unwrap()).cast(&Type::Smallint),
+            Ok(Value::Smallint(-12))
+        );
+        assert_eq!(
+            Value::Timestamp(86_400_000_001).cast(&Type::Date),
+            Ok(Value::Date(1))
+        );
+        assert_eq!(Value::Boolean(true).cast(&Type::Int), Ok(Value::Int(1)));
+        assert_eq!(
+            Value::Int(42).cast(&Type::String),
+            Ok(Value::String("42".to_string()))
+        );
+        assert_eq!(
+            Value::Array(vec![Some(Value::String("1".to_string())), None])
+                .cast(&Type::Array(Box::new(Type::Int))),
+            Ok(Value::Array(vec![Some(Value::Int(1)), None]))
+        );
+    }
+
+    #[test]
+    fn coerce_should_map_enum_labels() {
+        let status = Type::Enum(vec!["new".to_string(), "done".to_string()]);
+        let wider = Type::Enum(vec![
+            "new".to_string(),
+            "done".to_string(),
+            "lost".to_string(),
+        ]);
+        assert_eq!(status.cast_kind(&wider), Cast::Implicit);
+        assert_eq!(wider.cast_kind(&status), Cast::Explicit);
+        assert_eq!(Type::Int.cast_kind(&status), Cast::Rejected);
+        assert_eq!(
+            Value::String("done".to_string()).coerce(&status),
+            Ok(Value::Enum(1, "done".to_string()))
+        );
+        assert!(Value::String("lost".to_string()).coerce(&status).is_err());
+        assert!(Value::Enum(1, "done".to_string())
+            .coerce(&Type::String)
+            .is_err());
+        assert_eq!(
+            Value::Enum(1, "done".to_string()).cast(&Type::String),
+            Ok(Value::String("done".to_string()))
+        );
+    }
+
+    #[test]
+    fn cast_should_report_overflow_and_format_errors() {
+        assert_eq!(
+            Value::Int(300)
+                .cast(&Type::Tinyint)
+                .unwrap_err()
+                .to_string(),
+            "300 is out of range for Tinyint"
+        );
+        assert!(Value::Float(f64::NAN).cast(&Type::Int).is_err());
+        assert!(Value::Decimal(Decimal::build(12345, 2).unwrap())
+            .cast(&decimal(3, 1))
+            .is_err());
+        assert_eq!(
+            Value::String("soon".to_string())
+                .cast(&Type::Date)
+                .unwrap_err()
+                .to_string(),
+            Value::parse(&Type::Date, "soon").unwrap_err().to_string()
+        );
+        assert!(Value::Uuid([0; 16]).cast(&Type::Int).is_err());
+    }
+}
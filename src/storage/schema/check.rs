@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+
+use crate::storage::schema::encoding::Encoding;
+use crate::storage::schema::error::Error;
+use crate::storage::schema::expression::Expression;
+use crate::storage::schema::parser;
+use crate::storage::schema::parser::{Token, Tokens};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Check {
+    pub name: String,
+    pub expression: Expression,
+}
+
+impl Check {
+    pub fn build(name: String, expression: Expression) -> Check {
+        Check { name, expression }
+    }
+
+    pub(super) fn default_name(expression: &Expression) -> String {
+        match expression.columns().first() {
+            Some(column) => format!("{}_check", column),
+            None => "check".to_string(),
+        }
+    }
+
+    pub(super) fn parse(check_str: &str) -> Result<(Option<String>, Expression), Error> {
+        let (source, tokens) = parser::lex(check_str)?;
+        Check::from_tokens(&source, &tokens)
+    }
+
+    pub(super) fn from_tokens(
+        source: &str,
+        tokens: &Tokens,
+    ) -> Result<(Option<String>, Expression), Error> {
+        let error = |reason: String| {
+            Error::InvalidSchema(format!(
+                "\n- Invalid check constraint \"{}\": {}",
+                parser::text(source, tokens),
+                reason
+            ))
+        };
+        let (name, expression) = Self::split_clause(tokens).ok_or_else(|| {
+            error("expected \"[CONSTRAINT name] CHECK (expression)\"".to_string())
+        })?;
+        let expression = match expression {
+            [(_, Token::Symbol('(')), expression @ .., (_, Token::Symbol(')'))]
+                if !expression.is_empty() =>
+            {
+                parser::text(source, expression)
+            }
+            _ => return Err(error("expected a parenthesized expression".to_string())),
+        };
+        Ok((
+            name.map(str::to_string),
+            Expression::from_str(expression).map_err(|err| error(err.to_string()))?,
+        ))
+    }
+
+    pub(super) fn split_clause(tokens: &Tokens) -> Option<(Option<&str>, &Tokens)> {
+        let (name, rest) = match tokens {
+            [(_, constraint), (_, name), rest @ ..] if constraint.is_word("CONSTRAINT") => {
+                (Some(name.name()?), rest)
+            }
+            _ => (None, tokens),
+        };
+        match rest {
+            [(_, check), rest @ ..] if check.is_word("CHECK") => {
+                matches!(rest.first(), Some((_, Token::Symbol('(')))).then_some((name, rest))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Encoding<Check> for Check {
+    fn from_str(check_str: &str) -> Result<Check, Error> {
+        let (name, expression) = Check::parse(check_str)?;
+        Ok(Check::build(
+            name.unwrap_or_else(|| Check::default_name(&expression)),
+            expression,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_str_should_parse_named_and_anonymous_checks() {
+        let check = Check::from_str("CONSTRAINT positive_cost CHECK (cost >= 0)").unwrap();
+        assert_eq!(check.name, "positive_cost");
+        assert_eq!(check.expression.to_string(), "cost >= 0");
+        let check = Check::from_str("check(qty > 0 AND (qty < 10))").unwrap();
+        assert_eq!(check.name, "qty_check");
+        assert_eq!(check.expression.to_string(), "qty > 0 AND (qty < 10)");
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_str_should_panic_if_invalid_expression() {
+        Check::from_str("CHECK (a >)").unwrap();
+    }
+}
@@ -0,0 +1,98 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage::schema::encoding::Encoding;
+use crate::storage::schema::error::Error;
+use crate::storage::schema::value::Value;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Collation {
+    /// Byte by byte, the default.
+    #[default]
+    Binary,
+    /// Ignores the case of ASCII letters only.
+    NoCase,
+    /// Ignores the case of any letter, using Unicode lowercase mappings.
+    Unicode,
+}
+
+impl Collation {
+    pub fn is_binary(&self) -> bool {
+        *self == Collation::Binary
+    }
+
+    pub fn compare(&self, left: &str, right: &str) -> Ordering {
+        match self {
+            Collation::Binary => left.cmp(right),
+            Collation::NoCase => left
+                .bytes()
+                .map(|byte| byte.to_ascii_lowercase())
+                .cmp(right.bytes().map(|byte| byte.to_ascii_lowercase())),
+            Collation::Unicode => left
+                .chars()
+                .flat_map(char::to_lowercase)
+                .cmp(right.chars().flat_map(char::to_lowercase)),
+        }
+    }
+
+    pub fn compare_values(&self, left: &Value, right: &Value) -> Option<Ordering> {
+        match (left, right) {
+            (Value::String(left), Value::String(right)) => Some(self.compare(left, right)),
+            _ => left.partial_cmp(right),
+        }
+    }
+}
+
+impl Encoding<Collation> for Collation {
+    fn from_str(collation_str: &str) -> Result<Collation, Error> {
+        match collation_str.trim().to_ascii_lowercase().as_str() {
+            "binary" => Ok(Collation::Binary),
+            "nocase" => Ok(Collation::NoCase),
+            "unicode" => Ok(Collation::Unicode),
+            _ => Err(Error::InvalidField(format!(
+                "\n- Unknown collation \"{}\". Expected binary, nocase or unicode",
+                collation_str.trim()
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Collation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Collation::Binary => "binary",
+            Collation::NoCase => "nocase",
+            Collation::Unicode => "unicode",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_should_follow_collation() {
+        assert_eq!(Collation::Binary.compare("Apple", "apple"), Ordering::Less);
+        assert_eq!(Collation::NoCase.compare("Apple", "aPPLE"), Ordering::Equal);
+        assert_eq!(Collation::NoCase.compare("ÉTÉ", "été"), Ordering::Less);
+        assert_eq!(Collation::Unicode.compare("ÉTÉ", "été"), Ordering::Equal);
+        assert_eq!(Collation::Unicode.compare("b", "A"), Ordering::Greater);
+    }
+
+    #[test]
+    fn from_str_should_parse_names() {
+        assert_eq!(Collation::from_str("NOCASE").unwrap(), Collation::NoCase);
+        assert_eq!(Collation::from_str("unicode").unwrap(), Collation::Unicode);
+        assert_eq!(Collation::Binary.to_string(), "binary");
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_str_should_panic_if_unknown() {
+        Collation::from_str("german").unwrap();
+    }
+}
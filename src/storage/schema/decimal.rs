@@ -0,0 +1,311 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage::schema::encoding::Encoding;
+use crate::storage::schema::error::Error;
+
+pub const MAX_PRECISION: u8 = 38;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(try_from = "RawDecimal")]
+pub struct Decimal {
+    value: i128,
+    scale: u8,
+}
+
+/// Serialized fields of a `Decimal`, read back through `Decimal::build`.
+#[derive(Deserialize)]
+struct RawDecimal {
+    value: i128,
+    scale: u8,
+}
+
+impl TryFrom<RawDecimal> for Decimal {
+    type Error = Error;
+
+    fn try_from(raw: RawDecimal) -> Result<Decimal, Error> {
+        Decimal::build(raw.value, raw.scale)
+    }
+}
+
+impl Decimal {
+    pub fn build(value: i128, scale: u8) -> Result<Decimal, Error> {
+        if scale > MAX_PRECISION {
+            Err(Error::InvalidValue(format!(
+                "Decimal scale {} exceeds maximum {}",
+                scale, MAX_PRECISION
+            )))
+        } else {
+            Ok(Decimal { value, scale })
+        }
+    }
+
+    pub fn value(&self) -> i128 {
+        self.value
+    }
+
+    pub fn scale(&self) -> u8 {
+        self.scale
+    }
+
+    pub fn precision(&self) -> u8 {
+        let mut digits = 1;
+        let mut value = self.value.unsigned_abs() / 10;
+        while value > 0 {
+            digits += 1;
+            value /= 10;
+        }
+        digits
+    }
+
+    pub fn rescale(&self, scale: u8) -> Option<Decimal> {
+        match scale.cmp(&self.scale) {
+            Ordering::Equal => Some(*self),
+            Ordering::Greater => Some(Decimal {
+                value: self.value.checked_mul(pow10(scale - self.scale)?)?,
+                scale,
+            }),
+            Ordering::Less => {
+                let divisor = pow10(self.scale - scale)?;
+                let quotient = self.value / divisor;
+                let remainder = self.value % divisor;
+                let rounding = if remainder.unsigned_abs() * 2 >= divisor.unsigned_abs() {
+                    self.value.signum()
+                } else {
+                    0
+                };
+                Some(Decimal {
+                    value: quotient + rounding,
+                    scale,
+                })
+            }
+        }
+    }
+
+    pub fn checked_add(&self, other: &Decimal) -> Option<Decimal> {
+        let (left, right) = Self::align(self, other)?;
+        Some(Decimal {
+            value: left.value.checked_add(right.value)?,
+            scale: left.scale,
+        })
+    }
+
+    pub fn checked_sub(&self, other: &Decimal) -> Option<Decimal> {
+        let (left, right) = Self::align(self, other)?;
+        Some(Decimal {
+            value: left.value.checked_sub(right.value)?,
+            scale: left.scale,
+        })
+    }
+
+    pub fn checked_mul(&self, other: &Decimal) -> Option<Decimal> {
+        let scale = self.scale.checked_add(other.scale)?;
+        if scale > MAX_PRECISION {
+            return None;
+        }
+        Some(Decimal {
+            value: self.value.checked_mul(other.value)?,
+            scale,
+        })
+    }
+
+    pub fn checked_div(&self, other: &Decimal, scale: u8) -> Option<Decimal> {
+        if other.value == 0 || scale > MAX_PRECISION {
+            return None;
+        }
+        let numerator = self.value.checked_mul(pow10(other.scale + scale + 1)?)?;
+        let denominator = other.value.checked_mul(pow10(self.scale)?)?;
+        let quotient = numerator / denominator;
+        Some(Decimal {
+            value: quotient / 10 + quotient % 10 / 5,
+            scale,
+        })
+    }
+
+    fn align(left: &Decimal, right: &Decimal) -> Option<(Decimal, Decimal)> {
+        let scale = left.scale.max(right.scale);
+        Some((left.rescale(scale)?, right.rescale(scale)?))
+    }
+
+    fn split(&self) -> (i128, i128) {
+        let divisor = pow10(self.scale).unwrap();
+        (self.value / divisor, self.value % divisor)
+    }
+}
+
+impl Encoding<Decimal> for Decimal {
+    fn from_str(decimal_str: &str) -> Result<Decimal, Error> {
+        let error = || Error::InvalidValue(format!("Invalid decimal \"{}\"", decimal_str));
+        let decimal_str = decimal_str.trim();
+        let (negative, digits) = match decimal_str.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, decimal_str.strip_prefix('+').unwrap_or(decimal_str)),
+        };
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if integer.is_empty() && fraction.is_empty()
+            || !integer
+                .chars()
+                .chain(fraction.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            return Err(error());
+        }
+        let scale = u8::try_from(fraction.len()).map_err(|_| error())?;
+        let value = format!("{}{}", integer, fraction)
+            .parse::<i128>()
+            .map_err(|_| error())?;
+        Decimal::build(if negative { -value } else { value }, scale)
+    }
+}
+
+fn pow10(exponent: u8) -> Option<i128> {
+    10_i128.checked_pow(exponent as u32)
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (left_integer, left_fraction) = self.split();
+        let (right_integer, right_fraction) = other.split();
+        let scale = self.scale.max(other.scale);
+        left_integer.cmp(&right_integer).then_with(|| {
+            (left_fraction * pow10(scale - self.scale).unwrap())
+                .cmp(&(right_fraction * pow10(scale - other.scale).unwrap()))
+        })
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.value.unsigned_abs().to_string();
+        let sign = if self.value < 0 { "-" } else { "" };
+        if self.scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+        let digits = format!("{:0>width$}", digits, width = self.scale as usize + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - self.scale as usize);
+        write!(f, "{}{}.{}", sign, integer, fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimal(decimal_str: &str) -> Decimal {
+        Decimal::from_str(decimal_str).unwrap()
+    }
+
+    #[test]
+    fn from_str_should_parse_decimal() {
+        assert_eq!(decimal("12.34").value(), 1234);
+        assert_eq!(decimal("12.34").scale(), 2);
+        assert_eq!(decimal("-0.05").value(), -5);
+        assert_eq!(decimal("7").scale(), 0);
+        assert_eq!(decimal(".5").value(), 5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_str_should_panic_if_invalid() {
+        decimal("1.2.3");
+    }
+
+    #[test]
+    fn to_string_should_format_with_scale() {
+        assert_eq!(decimal("12.34").to_string(), "12.34");
+        assert_eq!(decimal("-0.05").to_string(), "-0.05");
+        assert_eq!(decimal("100").to_string(), "100");
+        assert_eq!(Decimal::build(5, 3).unwrap().to_string(), "0.005");
+    }
+
+    #[test]
+    fn deserialize_should_check_scale() {
+        let json = serde_json::to_string(&decimal("12.34")).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Decimal>(&json).unwrap(),
+            decimal("12.34")
+        );
+        assert!(serde_json::from_str::<Decimal>(r#"{"value":1,"scale":39}"#).is_err());
+    }
+
+    #[test]
+    fn precision_should_count_digits() {
+        assert_eq!(decimal("123.45").precision(), 5);
+        assert_eq!(decimal("0").precision(), 1);
+    }
+
+    #[test]
+    fn rescale_should_round_half_away_from_zero() {
+        assert_eq!(decimal("1.005").rescale(2).unwrap().value(), 101);
+        assert_eq!(decimal("-1.005").rescale(2).unwrap().value(), -101);
+        assert_eq!(decimal("1.004").rescale(2).unwrap().value(), 100);
+        assert_eq!(decimal("1.5").rescale(3).unwrap().value(), 1500);
+    }
+
+    #[test]
+    fn cmp_should_compare_across_scales() {
+        assert_eq!(decimal("1.10"), decimal("1.1"));
+        assert!(decimal("1.09") < decimal("1.1"));
+        assert!(decimal("-1.5") < decimal("-1.25"));
+        assert!(decimal("0.3") > decimal("0.29999999999999999999999999999999"));
+    }
+
+    #[test]
+    fn arithmetic_should_stay_exact() {
+        assert_eq!(
+            decimal("0.1").checked_add(&decimal("0.2")).unwrap(),
+            decimal("0.3")
+        );
+        assert_eq!(
+            decimal("10.00").checked_sub(&decimal("0.01")).unwrap(),
+            decimal("9.99")
+        );
+        assert_eq!(
+            decimal("1.5").checked_mul(&decimal("2.25")).unwrap(),
+            decimal("3.375")
+        );
+        assert_eq!(
+            decimal("10").checked_div(&decimal("3"), 4).unwrap(),
+            decimal("3.3333")
+        );
+        assert_eq!(
+            decimal("2").checked_div(&decimal("3"), 2).unwrap(),
+            decimal("0.67")
+        );
+        assert!(decimal("1").checked_div(&decimal("0"), 2).is_none());
+    }
+
+    #[test]
+    fn checked_div_should_keep_divisor_scale() {
+        assert_eq!(
+            decimal("0.1").checked_div(&decimal("0.03"), 3).unwrap(),
+            decimal("3.333")
+        );
+        assert_eq!(
+            decimal("-1").checked_div(&decimal("8"), 2).unwrap(),
+            decimal("-0.13")
+        );
+    }
+
+    #[test]
+    fn checked_add_should_return_none_on_overflow() {
+        let max = Decimal::build(i128::MAX, 0).unwrap();
+        assert!(max.checked_add(&decimal("1")).is_none());
+    }
+}
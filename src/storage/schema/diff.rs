@@ -0,0 +1,59 @@
+use crate::storage::schema::field::Field;
+use crate::storage::schema::Schema;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Diff {
+    pub added: Vec<Field>,
+    pub removed: Vec<Field>,
+    pub changed: Vec<(Field, Field)>,
+}
+
+impl Diff {
+    pub fn build(from: &Schema, to: &Schema) -> Diff {
+        let mut diff = Diff::default();
+        for field in &from.fields {
+            match to.get_field(field.get_name()) {
+                Some(other) if other != field => diff.changed.push((field.clone(), other.clone())),
+                Some(_) => {}
+                None => diff.removed.push(field.clone()),
+            }
+        }
+        diff.added = to
+            .fields
+            .iter()
+            .filter(|field| from.index_of(field.get_name()).is_none())
+            .cloned()
+            .collect();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::schema::encoding::Encoding;
+
+    use super::*;
+
+    #[test]
+    fn build_should_report_added_removed_and_changed_fields() {
+        let from = Schema::from_str("id INT, name STRING, qty SMALLINT").unwrap();
+        let to = Schema::from_str("id INT, qty INT NOT NULL DEFAULT 0, day DATE").unwrap();
+        let diff = Diff::build(&from, &to);
+        assert_eq!(diff.added, vec![to.fields[2].clone()]);
+        assert_eq!(diff.removed, vec![from.fields[1].clone()]);
+        assert_eq!(
+            diff.changed,
+            vec![(from.fields[2].clone(), to.fields[1].clone())]
+        );
+    }
+
+    #[test]
+    fn build_should_be_empty_if_same_fields() {
+        let schema = Schema::from_str("id INT, name STRING").unwrap();
+        assert!(Diff::build(&schema, &schema).is_empty());
+    }
+}
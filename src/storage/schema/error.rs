@@ -5,6 +5,8 @@ pub enum Error {
     InvalidType(String),
     InvalidField(String),
     InvalidSchema(String),
+    InvalidValue(String),
+    ConstraintViolation(String),
 }
 
 impl fmt::Display for Error {
@@ -13,6 +15,8 @@ impl fmt::Display for Error {
             Error::InvalidType(ref msg) => write!(f, "{}", msg),
             Error::InvalidField(ref msg) => write!(f, "{}", msg),
             Error::InvalidSchema(ref msg) => write!(f, "{}", msg),
+            Error::InvalidValue(ref msg) => write!(f, "{}", msg),
+            Error::ConstraintViolation(ref msg) => write!(f, "{}", msg),
         }
     }
 }
@@ -0,0 +1,845 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Range;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::storage::schema::_type::Type;
+use crate::storage::schema::collation::Collation;
+use crate::storage::schema::decimal::Decimal;
+use crate::storage::schema::encoding::Encoding;
+use crate::storage::schema::error::Error;
+use crate::storage::schema::field::Field;
+use crate::storage::schema::parser;
+use crate::storage::schema::value::Value;
+use crate::storage::schema::Schema;
+
+const DIVISION_SCALE: u8 = 6;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    source: String,
+    root: Node,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Literal(Option<Value>),
+    Column(String),
+    Not(Box<Node>),
+    Negate(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+    IsNull(Box<Node>, bool),
+    In(Box<Node>, Vec<Node>, bool),
+    Between(Box<Node>, Box<Node>, Box<Node>, bool),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    And,
+    Or,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(String),
+    String(String),
+    Word(String),
+    /// Quoted name, a column even when it spells a keyword.
+    Identifier(String),
+    Symbol(&'static str),
+}
+
+const KEYWORDS: [&str; 9] = [
+    "AND", "OR", "NOT", "IS", "NULL", "TRUE", "FALSE", "IN", "BETWEEN",
+];
+
+const SYMBOLS: [&str; 14] = [
+    "<=", ">=", "<>", "!=", "=", "<", ">", "+", "-", "*", "/", "%", "(", ")",
+];
+
+impl Expression {
+    pub fn columns(&self) -> Vec<&str> {
+        let mut columns: Vec<&str> = Vec::new();
+        self.root.columns(&mut columns);
+        columns
+    }
+
+    pub fn rename_column(&self, from: &str, to: &str) -> Result<Expression, Error> {
+        let mut source = String::new();
+        let mut last = 0;
+        for (span, token) in tokenize(&self.source)? {
+            let quoted = matches!(token, Token::Identifier(_));
+            match token {
+                Token::Word(name) | Token::Identifier(name)
+                    if parser::same_name(&name, from) && (quoted || !is_keyword(&name)) =>
+                {
+                    source.push_str(&self.source[last..span.start]);
+                    source.push_str(&quote_identifier(to));
+                    last = span.end;
+                }
+                _ => {}
+            }
+        }
+        source.push_str(&self.source[last..]);
+        Expression::from_str(&source)
+    }
+
+    pub fn evaluate(
+        &self,
+        schema: &Schema,
+        values: &[Option<Value>],
+    ) -> Result<Option<Value>, Error> {
+        self.root.evaluate(schema, values)
+    }
+}
+
+impl Encoding<Expression> for Expression {
+    fn from_str(expression_str: &str) -> Result<Expression, Error> {
+        let tokens: Vec<Token> = tokenize(expression_str)?
+            .into_iter()
+            .map(|(_, token)| token)
+            .collect();
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+        };
+        let root = parser.or()?;
+        match parser.peek() {
+            None => Ok(Expression {
+                source: expression_str.trim().to_string(),
+                root,
+            }),
+            Some(token) => Err(Error::InvalidValue(format!(
+                "Unexpected {:?} in expression \"{}\"",
+                token,
+                expression_str.trim()
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl Serialize for Expression {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for Expression {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Expression, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        Expression::from_str(&source).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Node {
+    fn columns<'a>(&'a self, columns: &mut Vec<&'a str>) {
+        match self {
+            Node::Literal(_) => {}
+            Node::Column(name) => {
+                if !columns.iter().any(|column| parser::same_name(column, name)) {
+                    columns.push(name)
+                }
+            }
+            Node::Not(node) | Node::Negate(node) | Node::IsNull(node, _) => node.columns(columns),
+            Node::Binary(_, left, right) => {
+                left.columns(columns);
+                right.columns(columns);
+            }
+            Node::In(node, list, _) => {
+                node.columns(columns);
+                list.iter().for_each(|item| item.columns(columns));
+            }
+            Node::Between(node, low, high, _) => {
+                node.columns(columns);
+                low.columns(columns);
+                high.columns(columns);
+            }
+        }
+    }
+
+    fn field<'a>(&self, schema: &'a Schema) -> Option<&'a Field> {
+        match self {
+            Node::Column(name) => schema.get_field(name),
+            _ => None,
+        }
+    }
+
+    fn evaluate(&self, schema: &Schema, values: &[Option<Value>]) -> Result<Option<Value>, Error> {
+        Ok(match self {
+            Node::Literal(value) => value.clone(),
+            Node::Column(name) => schema
+                .index_of(name)
+                .and_then(|index| values.get(index))
+                .ok_or_else(|| Error::InvalidValue(format!("Unknown column \"{}\"", name)))?
+                .clone(),
+            Node::Not(node) => {
+                as_bool(&node.evaluate(schema, values)?)?.map(|b| Value::Boolean(!b))
+            }
+            Node::Negate(node) => match node.evaluate(schema, values)? {
+                None => None,
+                Some(value) => Some(arithmetic(Operator::Sub, &Value::Bigint(0), &value)?),
+            },
+            Node::IsNull(node, negated) => Some(Value::Boolean(
+                node.evaluate(schema, values)?.is_none() != *negated,
+            )),
+            Node::Binary(Operator::And, left, right) => {
+                let left = as_bool(&left.evaluate(schema, values)?)?;
+                if left == Some(false) {
+                    return Ok(Some(Value::Boolean(false)));
+                }
+                match (left, as_bool(&right.evaluate(schema, values)?)?) {
+                    (_, Some(false)) => Some(Value::Boolean(false)),
+                    (Some(true), Some(true)) => Some(Value::Boolean(true)),
+                    _ => None,
+                }
+            }
+            Node::Binary(Operator::Or, left, right) => {
+                let left = as_bool(&left.evaluate(schema, values)?)?;
+                if left == Some(true) {
+                    return Ok(Some(Value::Boolean(true)));
+                }
+                match (left, as_bool(&right.evaluate(schema, values)?)?) {
+                    (_, Some(true)) => Some(Value::Boolean(true)),
+                    (Some(false), Some(false)) => Some(Value::Boolean(false)),
+                    _ => None,
+                }
+            }
+            Node::Binary(operator, left, right) => {
+                let column = left.field(schema).or(right.field(schema));
+                match (
+                    left.evaluate(schema, values)?,
+                    right.evaluate(schema, values)?,
+                ) {
+                    (Some(left), Some(right)) => match operator {
+                        Operator::Eq
+                        | Operator::NotEq
+                        | Operator::Lt
+                        | Operator::LtEq
+                        | Operator::Gt
+                        | Operator::GtEq => compare(&left, &right, column)?.map(|ordering| {
+                            Value::Boolean(match operator {
+                                Operator::Eq => ordering == Ordering::Equal,
+                                Operator::NotEq => ordering != Ordering::Equal,
+                                Operator::Lt => ordering == Ordering::Less,
+                                Operator::LtEq => ordering != Ordering::Greater,
+                                Operator::Gt => ordering == Ordering::Greater,
+                                _ => ordering != Ordering::Less,
+                            })
+                        }),
+                        _ => Some(arithmetic(*operator, &left, &right)?),
+                    },
+                    _ => None,
+                }
+            }
+            Node::In(node, list, negated) => {
+                let column = node.field(schema);
+                let value = match node.evaluate(schema, values)? {
+                    Some(value) => value,
+                    None => return Ok(None),
+                };
+                let mut found = Some(false);
+                for item in list {
+                    match item.evaluate(schema, values)? {
+                        Some(item) => {
+                            if compare(&value, &item, column)? == Some(Ordering::Equal) {
+                                found = Some(true);
+                                break;
+                            }
+                        }
+                        None => found = None,
+                    }
+                }
+                found.map(|found| Value::Boolean(found != *negated))
+            }
+            Node::Between(node, low, high, negated) => {
+                let column = node.field(schema);
+                let value = node.evaluate(schema, values)?;
+                let low = low.evaluate(schema, values)?;
+                let high = high.evaluate(schema, values)?;
+                match (value, low, high) {
+                    (Some(value), Some(low), Some(high)) => {
+                        match (
+                            compare(&value, &low, column)?,
+                            compare(&value, &high, column)?,
+                        ) {
+                            (Some(low), Some(high)) => Some(Value::Boolean(
+                                (low != Ordering::Less && high != Ordering::Greater) != *negated,
+                            )),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                }
+            }
+        })
+    }
+}
+
+fn as_bool(value: &Option<Value>) -> Result<Option<bool>, Error> {
+    match value {
+        None => Ok(None),
+        Some(Value::Boolean(value)) => Ok(Some(*value)),
+        Some(value) => Err(Error::InvalidValue(format!(
+            "Expected a boolean, actual {}",
+            value
+        ))),
+    }
+}
+
+enum Numeric {
+    Integer(i128),
+    Decimal(Decimal),
+    Float(f64),
+}
+
+impl Numeric {
+    fn from_value(value: &Value) -> Option<Numeric> {
+        match value {
+            Value::Tinyint(value) => Some(Numeric::Integer(*value as i128)),
+            Value::Smallint(value) => Some(Numeric::Integer(*value as i128)),
+            Value::Int(value) => Some(Numeric::Integer(*value as i128)),
+            Value::Bigint(value) => Some(Numeric::Integer(*value)),
+            Value::Decimal(value) => Some(Numeric::Decimal(*value)),
+            Value::Float(value) => Some(Numeric::Float(*value)),
+            _ => None,
+        }
+    }
+
+    fn as_decimal(&self) -> Option<Decimal> {
+        match self {
+            Numeric::Integer(value) => Decimal::build(*value, 0).ok(),
+            Numeric::Decimal(value) => Some(*value),
+            Numeric::Float(_) => None,
+        }
+    }
+
+    fn as_float(&self) -> f64 {
+        match self {
+            Numeric::Integer(value) => *value as f64,
+            Numeric::Decimal(value) => value.to_string().parse().unwrap_or(f64::NAN),
+            Numeric::Float(value) => *value,
+        }
+    }
+}
+
+fn compare(left: &Value, right: &Value, column: Option<&Field>) -> Result<Option<Ordering>, Error> {
+    if let (Some(left), Some(right)) = (Numeric::from_value(left), Numeric::from_value(right)) {
+        return Ok(match (&left, &right) {
+            (Numeric::Integer(l), Numeric::Integer(r)) => Some(l.cmp(r)),
+            (Numeric::Float(_), _) | (_, Numeric::Float(_)) => {
+                left.as_float().partial_cmp(&right.as_float())
+            }
+            _ => Some(left.as_decimal().cmp(&right.as_decimal())),
+        });
+    }
+    let incomparable = || Error::InvalidValue(format!("Can't compare {} with {}", left, right));
+    match (left, right) {
+        (Value::String(string), other) | (other, Value::String(string))
+            if !matches!(other, Value::String(_)) =>
+        {
+            let _type = match (other, column.map(Field::get_type)) {
+                (Value::Enum(_, _), Some(_type @ Type::Enum(_))) => _type.clone(),
+                _ => other.scalar_type().ok_or_else(incomparable)?,
+            };
+            let parsed = Value::String(string.clone()).cast(&_type)?;
+            Ok(if matches!(left, Value::String(_)) {
+                parsed.partial_cmp(other)
+            } else {
+                other.partial_cmp(&parsed)
+            })
+        }
+        _ if std::mem::discriminant(left) == std::mem::discriminant(right) => Ok(column
+            .map_or(Collation::Binary, Field::get_collation)
+            .compare_values(left, right)),
+        _ => Err(incomparable()),
+    }
+}
+
+fn arithmetic(operator: Operator, left: &Value, right: &Value) -> Result<Value, Error> {
+    let error = |reason: &str| {
+        Error::InvalidValue(format!("{} in {} {:?} {}", reason, left, operator, right))
+    };
+    let (l, r) = match (Numeric::from_value(left), Numeric::from_value(right)) {
+        (Some(l), Some(r)) => (l, r),
+        _ => return Err(error("Non numeric operand")),
+    };
+    match (&l, &r) {
+        (Numeric::Integer(l), Numeric::Integer(r)) => {
+            if matches!(operator, Operator::Div | Operator::Mod) && *r == 0 {
+                return Err(error("Division by zero"));
+            }
+            match operator {
+                Operator::Add => l.checked_add(*r),
+                Operator::Sub => l.checked_sub(*r),
+                Operator::Mul => l.checked_mul(*r),
+                Operator::Div => l.checked_div(*r),
+                _ => l.checked_rem(*r),
+            }
+            .map(Value::Bigint)
+            .ok_or_else(|| error("Overflow"))
+        }
+        (Numeric::Float(_), _) | (_, Numeric::Float(_)) => {
+            let (l, r) = (l.as_float(), r.as_float());
+            Ok(Value::Float(match operator {
+                Operator::Add => l + r,
+                Operator::Sub => l - r,
+                Operator::Mul => l * r,
+                Operator::Div => l / r,
+                _ => l % r,
+            }))
+        }
+        _ => {
+            let (l, r) = (l.as_decimal().unwrap(), r.as_decimal().unwrap());
+            match operator {
+                Operator::Add => l.checked_add(&r),
+                Operator::Sub => l.checked_sub(&r),
+                Operator::Mul => l.checked_mul(&r),
+                Operator::Div if r.value() == 0 => return Err(error("Division by zero")),
+                Operator::Div => l.checked_div(&r, l.scale().max(DIVISION_SCALE)),
+                _ => return Err(error("Unsupported operator")),
+            }
+            .map(Value::Decimal)
+            .ok_or_else(|| error("Overflow"))
+        }
+    }
+}
+
+fn is_keyword(word: &str) -> bool {
+    KEYWORDS
+        .iter()
+        .any(|keyword| keyword.eq_ignore_ascii_case(word))
+}
+
+pub(super) fn quote_identifier(name: &str) -> String {
+    let mut chars = name.chars();
+    let plain = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_');
+    if plain && !is_keyword(name) {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
+
+fn tokenize(expression_str: &str) -> Result<Vec<(Range<usize>, Token)>, Error> {
+    let error = |message: &str| {
+        Error::InvalidValue(format!(
+            "{} in expression \"{}\"",
+            message,
+            expression_str.trim()
+        ))
+    };
+    let mut tokens: Vec<(Range<usize>, Token)> = Vec::new();
+    let mut scanned = parser::tokenize(expression_str)
+        .map_err(|(_, message)| error(message))?
+        .into_iter()
+        .peekable();
+    while let Some((mut range, token)) = scanned.next() {
+        let token = match token {
+            parser::Token::Number(number) => Token::Number(number),
+            parser::Token::String(text) => Token::String(text),
+            parser::Token::Word(word) => Token::Word(word),
+            parser::Token::Identifier(name) => Token::Identifier(name),
+            parser::Token::Symbol(',') => Token::Symbol(","),
+            parser::Token::Symbol(char) => {
+                // The lexer yields single characters, operators span the adjacent ones.
+                if let Some((next, parser::Token::Symbol(_))) = scanned.peek() {
+                    if next.start == range.end
+                        && SYMBOLS.contains(&&expression_str[range.start..next.end])
+                    {
+                        range.end = next.end;
+                        scanned.next();
+                    }
+                }
+                let symbol = SYMBOLS
+                    .iter()
+                    .find(|symbol| **symbol == &expression_str[range.clone()])
+                    .ok_or_else(|| error(&format!("Unexpected character '{}'", char)))?;
+                Token::Symbol(symbol)
+            }
+        };
+        tokens.push((range, token));
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        self.position += 1;
+        self.tokens.get(self.position - 1)
+    }
+
+    fn error(&self, expected: &str) -> Error {
+        match self.peek() {
+            Some(token) => {
+                Error::InvalidValue(format!("Expected {}, actual {:?}", expected, token))
+            }
+            None => Error::InvalidValue(format!("Expected {}, actual end of expression", expected)),
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn symbol(&mut self, symbols: &[&str]) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Symbol(symbol)) if symbols.contains(symbol) => {
+                let symbol = *symbol;
+                self.position += 1;
+                Some(symbol)
+            }
+            _ => None,
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), Error> {
+        self.symbol(&[symbol])
+            .map(|_| ())
+            .ok_or_else(|| self.error(&format!("'{}'", symbol)))
+    }
+
+    fn or(&mut self) -> Result<Node, Error> {
+        let mut node = self.and()?;
+        while self.keyword("OR") {
+            node = Node::Binary(Operator::Or, Box::new(node), Box::new(self.and()?));
+        }
+        Ok(node)
+    }
+
+    fn and(&mut self) -> Result<Node, Error> {
+        let mut node = self.not()?;
+        while self.keyword("AND") {
+            node = Node::Binary(Operator::And, Box::new(node), Box::new(self.not()?));
+        }
+        Ok(node)
+    }
+
+    fn not(&mut self) -> Result<Node, Error> {
+        if self.keyword("NOT") {
+            Ok(Node::Not(Box::new(self.not()?)))
+        } else {
+            self.predicate()
+        }
+    }
+
+    fn predicate(&mut self) -> Result<Node, Error> {
+        let node = self.additive()?;
+        if let Some(symbol) = self.symbol(&["=", "<>", "!=", "<", "<=", ">", ">="]) {
+            let operator = match symbol {
+                "=" => Operator::Eq,
+                "<>" | "!=" => Operator::NotEq,
+                "<" => Operator::Lt,
+                "<=" => Operator::LtEq,
+                ">" => Operator::Gt,
+                _ => Operator::GtEq,
+            };
+            return Ok(Node::Binary(
+                operator,
+                Box::new(node),
+                Box::new(self.additive()?),
+            ));
+        }
+        if self.keyword("IS") {
+            let negated = self.keyword("NOT");
+            if !self.keyword("NULL") {
+                return Err(self.error("NULL"));
+            }
+            return Ok(Node::IsNull(Box::new(node), negated));
+        }
+        let negated = self.keyword("NOT");
+        if self.keyword("IN") {
+            self.expect("(")?;
+            let mut list = vec![self.additive()?];
+            while self.symbol(&[","]).is_some() {
+                list.push(self.additive()?);
+            }
+            self.expect(")")?;
+            Ok(Node::In(Box::new(node), list, negated))
+        } else if self.keyword("BETWEEN") {
+            let low = self.additive()?;
+            if !self.keyword("AND") {
+                return Err(self.error("AND"));
+            }
+            let high = self.additive()?;
+            Ok(Node::Between(
+                Box::new(node),
+                Box::new(low),
+                Box::new(high),
+                negated,
+            ))
+        } else if negated {
+            Err(self.error("IN or BETWEEN"))
+        } else {
+            Ok(node)
+        }
+    }
+
+    fn additive(&mut self) -> Result<Node, Error> {
+        let mut node = self.multiplicative()?;
+        while let Some(symbol) = self.symbol(&["+", "-"]) {
+            let operator = if symbol == "+" {
+                Operator::Add
+            } else {
+                Operator::Sub
+            };
+            node = Node::Binary(operator, Box::new(node), Box::new(self.multiplicative()?));
+        }
+        Ok(node)
+    }
+
+    fn multiplicative(&mut self) -> Result<Node, Error> {
+        let mut node = self.unary()?;
+        while let Some(symbol) = self.symbol(&["*", "/", "%"]) {
+            let operator = match symbol {
+                "*" => Operator::Mul,
+                "/" => Operator::Div,
+                _ => Operator::Mod,
+            };
+            node = Node::Binary(operator, Box::new(node), Box::new(self.unary()?));
+        }
+        Ok(node)
+    }
+
+    fn unary(&mut self) -> Result<Node, Error> {
+        if self.symbol(&["-"]).is_some() {
+            Ok(Node::Negate(Box::new(self.unary()?)))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Node, Error> {
+        if self.symbol(&["("]).is_some() {
+            let node = self.or()?;
+            self.expect(")")?;
+            return Ok(node);
+        }
+        let error = self.error("a value");
+        Ok(match self.next().cloned() {
+            Some(Token::Number(number)) if number.contains('.') => {
+                Node::Literal(Some(Value::Decimal(Decimal::from_str(&number)?)))
+            }
+            Some(Token::Number(number)) => {
+                Node::Literal(Some(Value::Bigint(number.parse().map_err(|_| {
+                    Error::InvalidValue(format!("Invalid number {}", number))
+                })?)))
+            }
+            Some(Token::String(string)) => Node::Literal(Some(Value::String(string))),
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("NULL") => Node::Literal(None),
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("TRUE") => {
+                Node::Literal(Some(Value::Boolean(true)))
+            }
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("FALSE") => {
+                Node::Literal(Some(Value::Boolean(false)))
+            }
+            Some(Token::Word(name)) | Some(Token::Identifier(name)) => Node::Column(name),
+            _ => return Err(error),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(expression: &str, values: &[Option<Value>]) -> Option<Value> {
+        let schema =
+            Schema::from_str("cost DECIMAL(6, 2), qty INT, day DATE, name STRING").unwrap();
+        Expression::from_str(expression)
+            .unwrap()
+            .evaluate(&schema, values)
+            .unwrap()
+    }
+
+    fn row() -> Vec<Option<Value>> {
+        vec![
+            Some(Value::Decimal(Decimal::from_str("9.99").unwrap())),
+            Some(Value::Int(3)),
+            Some(Value::Date(19884)),
+            None,
+        ]
+    }
+
+    #[test]
+    fn evaluate_should_compare_and_compute() {
+        assert_eq!(
+            evaluate("cost >= 0 AND qty * 2 + 1 = 7", &row()),
+            Some(Value::Boolean(true))
+        );
+        assert_eq!(
+            evaluate("cost * qty > 30.0", &row()),
+            Some(Value::Boolean(false))
+        );
+        assert_eq!(
+            evaluate("-qty BETWEEN -5 AND 0", &row()),
+            Some(Value::Boolean(true))
+        );
+        assert_eq!(
+            evaluate("qty NOT IN (1, 2)", &row()),
+            Some(Value::Boolean(true))
+        );
+    }
+
+    #[test]
+    fn evaluate_should_parse_string_literal_with_column_type() {
+        assert_eq!(
+            evaluate("day > '2024-01-01' AND day <= '2024-06-10'", &row()),
+            Some(Value::Boolean(true))
+        );
+    }
+
+    #[test]
+    fn evaluate_should_follow_three_valued_logic() {
+        assert_eq!(evaluate("name = 'a'", &row()), None);
+        assert_eq!(
+            evaluate("name = 'a' OR qty = 3", &row()),
+            Some(Value::Boolean(true))
+        );
+        assert_eq!(
+            evaluate("name = 'a' AND qty = 4", &row()),
+            Some(Value::Boolean(false))
+        );
+        assert_eq!(evaluate("name IS NULL", &row()), Some(Value::Boolean(true)));
+        assert_eq!(
+            evaluate("NOT name IS NOT NULL", &row()),
+            Some(Value::Boolean(true))
+        );
+    }
+
+    #[test]
+    fn evaluate_should_honour_column_collation() {
+        let schema = Schema::from_str("name STRING COLLATE nocase, code STRING").unwrap();
+        let values = vec![
+            Some(Value::String("Alice".to_string())),
+            Some(Value::String("Alice".to_string())),
+        ];
+        let evaluate = |expression: &str| {
+            Expression::from_str(expression)
+                .unwrap()
+                .evaluate(&schema, &values)
+                .unwrap()
+        };
+        assert_eq!(evaluate("name = 'ALICE'"), Some(Value::Boolean(true)));
+        assert_eq!(evaluate("'ALICE' = name"), Some(Value::Boolean(true)));
+        assert_eq!(
+            evaluate("name IN ('bob', 'alice')"),
+            Some(Value::Boolean(true))
+        );
+        assert_eq!(
+            evaluate("name BETWEEN 'a' AND 'b'"),
+            Some(Value::Boolean(true))
+        );
+        assert_eq!(evaluate("code = 'ALICE'"), Some(Value::Boolean(false)));
+    }
+
+    #[test]
+    fn evaluate_should_compare_enum_labels_in_declaration_order() {
+        let schema = Schema::from_str("status ENUM('new', 'open', 'done')").unwrap();
+        let values = vec![Some(Value::Enum(1, "open".to_string()))];
+        let evaluate = |expression: &str| {
+            Expression::from_str(expression)
+                .unwrap()
+                .evaluate(&schema, &values)
+                .unwrap()
+        };
+        assert_eq!(evaluate("status = 'open'"), Some(Value::Boolean(true)));
+        assert_eq!(evaluate("'done' > status"), Some(Value::Boolean(true)));
+        assert_eq!(
+            evaluate("status IN ('new', 'done')"),
+            Some(Value::Boolean(false))
+        );
+    }
+
+    #[test]
+    fn columns_should_list_references() {
+        let expression = Expression::from_str("\"qty\" > 0 AND cost < qty * 10").unwrap();
+        assert_eq!(expression.columns(), vec!["qty", "cost"]);
+    }
+
+    #[test]
+    fn rename_column_should_rewrite_source() {
+        let expression = Expression::from_str("qty > 0 AND \"qty\" < 'qty'").unwrap();
+        assert_eq!(
+            expression
+                .rename_column("qty", "quantity")
+                .unwrap()
+                .to_string(),
+            "quantity > 0 AND quantity < 'qty'"
+        );
+        assert_eq!(
+            expression
+                .rename_column("qty", "in stock")
+                .unwrap()
+                .columns(),
+            vec!["in stock"]
+        );
+    }
+
+    #[test]
+    fn from_str_should_read_quoted_keywords_as_columns() {
+        let schema = Schema::from_str("\"null\" INT, \"and\" BOOLEAN").unwrap();
+        let values = vec![Some(Value::Int(3)), Some(Value::Boolean(true))];
+        let expression = Expression::from_str("\"null\" > 1 AND \"and\"").unwrap();
+        assert_eq!(expression.columns(), vec!["null", "and"]);
+        assert_eq!(
+            expression.evaluate(&schema, &values).unwrap(),
+            Some(Value::Boolean(true))
+        );
+        let renamed = Expression::from_str("qty IS NOT NULL")
+            .unwrap()
+            .rename_column("qty", "true")
+            .unwrap();
+        assert_eq!(renamed.to_string(), "\"true\" IS NOT NULL");
+        assert_eq!(renamed.columns(), vec!["true"]);
+        assert_eq!(
+            renamed.rename_column("true", "qty").unwrap().to_string(),
+            "qty IS NOT NULL"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_str_should_panic_if_unbalanced() {
+        Expression::from_str("(qty > 0").unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn evaluate_should_panic_on_division_by_zero() {
+        evaluate("qty / 0 = 1", &row());
+    }
+}
@@ -1,40 +1,306 @@
 use serde::{Deserialize, Serialize};
 
 use crate::storage::schema::_type::Type;
+use crate::storage::schema::collation::Collation;
 use crate::storage::schema::encoding::Encoding;
 use crate::storage::schema::error::Error;
+use crate::storage::schema::expression::Expression;
+use crate::storage::schema::generated::{Generated, GeneratedKind};
+use crate::storage::schema::key::KeyKind;
+use crate::storage::schema::parser;
+use crate::storage::schema::parser::{Spanned, Token, Tokens};
+use crate::storage::schema::value::Value;
+
+const CONSTRAINT_KEYWORDS: [&str; 7] = [
+    "NOT",
+    "NULL",
+    "DEFAULT",
+    "PRIMARY",
+    "UNIQUE",
+    "COLLATE",
+    "GENERATED",
+];
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Field {
     name: String,
     _type: Type,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    not_null: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default: Option<Value>,
+    #[serde(default, skip_serializing_if = "Collation::is_binary")]
+    collation: Collation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    generated: Option<Generated>,
 }
 
 impl Field {
     pub fn build(name: String, _type: Type) -> Field {
-        Field { name, _type }
+        Field {
+            name,
+            _type,
+            not_null: false,
+            default: None,
+            collation: Collation::Binary,
+            generated: None,
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
     }
 
     pub fn get_type(&self) -> &Type {
         &self._type
     }
+
+    pub fn is_not_null(&self) -> bool {
+        self.not_null
+    }
+
+    pub fn get_default(&self) -> Option<&Value> {
+        self.default.as_ref()
+    }
+
+    pub fn get_collation(&self) -> Collation {
+        self.collation
+    }
+
+    pub fn get_generated(&self) -> Option<&Generated> {
+        self.generated.as_ref()
+    }
+
+    pub fn is_virtual(&self) -> bool {
+        self.generated.as_ref().is_some_and(Generated::is_virtual)
+    }
+
+    pub(super) fn parse(field_str: &str) -> Result<(Field, Vec<KeyKind>), Error> {
+        let (source, tokens) = parser::lex(field_str)?;
+        Field::from_tokens(&source, &tokens)
+    }
+
+    pub(super) fn from_tokens(
+        source: &str,
+        tokens: &Tokens,
+    ) -> Result<(Field, Vec<KeyKind>), Error> {
+        let error = || {
+            Error::InvalidField(format!(
+                "\n- Invalid field syntax. Expected \"column_name column_type\" Actual \"{}\"",
+                parser::text(source, tokens)
+            ))
+        };
+        let ((_, name), definition) = tokens.split_first().ok_or_else(error)?;
+        let name = name.name().ok_or_else(error)?;
+        let (type_tokens, constraints) = definition.split_at(split_constraints(definition));
+        if type_tokens.is_empty() {
+            return Err(error());
+        }
+        let mut field = Field::build(
+            name.to_string(),
+            Type::from_str(parser::text(source, type_tokens))?,
+        );
+        let keys = field.parse_constraints(source, tokens, constraints)?;
+        Ok((field, keys))
+    }
+
+    pub(super) fn set_not_null(&mut self, not_null: bool) {
+        self.not_null = not_null;
+    }
+
+    pub(super) fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
+    pub(super) fn set_generated(&mut self, generated: Option<Generated>) {
+        self.generated = generated;
+    }
+
+    pub(super) fn set_type(&mut self, _type: Type, default: Option<Value>) {
+        self._type = _type;
+        self.default = default;
+    }
+
+    pub(super) fn merge(&self, other: &Field) -> Result<Field, Error> {
+        let _type = if other._type.widens_to(&self._type) {
+            self._type.clone()
+        } else if self._type.widens_to(&other._type) {
+            other._type.clone()
+        } else {
+            return Err(Error::InvalidSchema(format!(
+                "\n- Column \"{}\" has incompatible types {:?} and {:?}",
+                self.name, self._type, other._type
+            )));
+        };
+        if self.collation != other.collation {
+            return Err(Error::InvalidSchema(format!(
+                "\n- Column \"{}\" has incompatible collations {} and {}",
+                self.name, self.collation, other.collation
+            )));
+        }
+        if self.generated != other.generated {
+            return Err(Error::InvalidSchema(format!(
+                "\n- Column \"{}\" is generated differently on each side",
+                self.name
+            )));
+        }
+        let default = match self.default.as_ref().or(other.default.as_ref()) {
+            Some(value) => Some(value.coerce(&_type).map_err(|err| {
+                Error::InvalidSchema(format!("\n- Default of \"{}\": {}", self.name, err))
+            })?),
+            None => None,
+        };
+        Ok(Field {
+            name: self.name.clone(),
+            _type,
+            not_null: self.not_null && other.not_null,
+            default,
+            collation: self.collation,
+            generated: self.generated.clone(),
+        })
+    }
+
+    fn parse_constraints(
+        &mut self,
+        source: &str,
+        tokens: &Tokens,
+        constraints: &Tokens,
+    ) -> Result<Vec<KeyKind>, Error> {
+        let error = || {
+            Error::InvalidField(format!(
+                "\n- Invalid constraint \"{}\" in field \"{}\"",
+                parser::text(source, constraints),
+                parser::text(source, tokens)
+            ))
+        };
+        let mut keys: Vec<KeyKind> = Vec::new();
+        let mut tokens = constraints.iter().peekable();
+        while let Some((_, token)) = tokens.next() {
+            if token.is_word("NOT") && tokens.next().is_some_and(|(_, t)| t.is_word("NULL")) {
+                self.not_null = true
+            } else if token.is_word("NULL") {
+                self.not_null = false
+            } else if token.is_word("DEFAULT") {
+                let negative = tokens.next_if(|(_, t)| *t == Token::Symbol('-')).is_some();
+                let (_, literal) = tokens.next().ok_or_else(error)?;
+                self.default = self.parse_default(literal, negative)?
+            } else if token.is_word("PRIMARY")
+                && tokens.next().is_some_and(|(_, t)| t.is_word("KEY"))
+            {
+                self.not_null = true;
+                keys.push(KeyKind::Primary)
+            } else if token.is_word("UNIQUE") {
+                keys.push(KeyKind::Unique)
+            } else if token.is_word("GENERATED")
+                && tokens.next().is_some_and(|(_, t)| t.is_word("ALWAYS"))
+                && tokens.next().is_some_and(|(_, t)| t.is_word("AS"))
+            {
+                let expression = parenthesized(source, &mut tokens).ok_or_else(error)?;
+                let expression = Expression::from_str(expression).map_err(|err| {
+                    Error::InvalidField(format!(
+                        "\n- Invalid expression for generated column \"{}\": {}",
+                        self.name, err
+                    ))
+                })?;
+                let kind =
+                    match tokens.next_if(|(_, t)| t.is_word("STORED") || t.is_word("VIRTUAL")) {
+                        Some((_, t)) if t.is_word("STORED") => GeneratedKind::Stored,
+                        _ => GeneratedKind::Virtual,
+                    };
+                self.generated = Some(Generated::build(kind, expression))
+            } else if token.is_word("COLLATE") {
+                let name = tokens
+                    .next()
+                    .and_then(|(_, t)| t.name())
+                    .ok_or_else(error)?;
+                if self._type != Type::String {
+                    return Err(Error::InvalidField(format!(
+                        "\n- Collation only applies to STRING columns, \"{}\" is {:?}",
+                        self.name, self._type
+                    )));
+                }
+                self.collation = Collation::from_str(name)?
+            } else {
+                return Err(error());
+            }
+        }
+        if self.generated.is_some() && self.default.is_some() {
+            return Err(Error::InvalidField(format!(
+                "\n- Generated column \"{}\" can't have a default",
+                self.name
+            )));
+        }
+        Ok(keys)
+    }
+
+    fn parse_default(&self, literal: &Token, negative: bool) -> Result<Option<Value>, Error> {
+        let literal = match literal {
+            Token::Word(word) if !negative && word.eq_ignore_ascii_case("NULL") => return Ok(None),
+            Token::Number(number) if negative => format!("-{}", number),
+            Token::String(text) | Token::Word(text) | Token::Number(text) if !negative => {
+                text.clone()
+            }
+            _ => {
+                return Err(Error::InvalidField(format!(
+                    "\n- Invalid default {:?} in field \"{}\"",
+                    literal, self.name
+                )))
+            }
+        };
+        Ok(Some(Value::parse(&self._type, &literal)?))
+    }
 }
 
 impl Encoding<Field> for Field {
     fn from_str(field_str: &str) -> Result<Field, Error> {
-        let name_and_type: Vec<&str> = field_str.split_whitespace().collect();
-        if name_and_type.len() != 2 {
-            Err(Error::InvalidField(format!(
-                "\n- Invalid field syntax. Expected \"column_name column_type\" Actual \"{}\"",
-                field_str
-            )))
-        } else {
-            Ok(Field {
-                name: name_and_type[0].to_string(),
-                _type: Type::from_str(name_and_type[1])?,
-            })
+        match Field::parse(field_str)? {
+            (field, keys) if keys.is_empty() => Ok(field),
+            _ => Err(Error::InvalidField(format!(
+                "\n- Key constraints are only allowed on table columns \"{}\"",
+                field_str.trim()
+            ))),
+        }
+    }
+}
+
+fn parenthesized<'a, 'b>(
+    source: &'a str,
+    tokens: &mut impl Iterator<Item = &'b Spanned>,
+) -> Option<&'a str> {
+    let open = match tokens.next()? {
+        (open, Token::Symbol('(')) => open,
+        _ => return None,
+    };
+    let mut depth = 1;
+    for (range, token) in tokens {
+        match token {
+            Token::Symbol('(') => depth += 1,
+            Token::Symbol(')') if depth == 1 => return Some(&source[open.end..range.start]),
+            Token::Symbol(')') => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn split_constraints(definition: &Tokens) -> usize {
+    let mut depth = 0;
+    for (index, (_, token)) in definition.iter().enumerate() {
+        match token {
+            Token::Symbol('(' | '<') => depth += 1,
+            Token::Symbol(')' | '>') => depth -= 1,
+            _ if depth == 0
+                && index > 0
+                && CONSTRAINT_KEYWORDS
+                    .iter()
+                    .any(|keyword| token.is_word(keyword)) =>
+            {
+                return index
+            }
+            _ => {}
         }
     }
+    definition.len()
 }
 
 #[cfg(test)]
@@ -49,6 +315,122 @@ mod tests {
         );
     }
 
+    #[test]
+    fn field_from_str_should_parse_type_parameters() {
+        assert_eq!(
+            Field::from_str(" cost  DECIMAL(8, 3) ").unwrap(),
+            Field::build(
+                "cost".to_string(),
+                Type::Decimal {
+                    precision: 8,
+                    scale: 3
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn field_from_str_should_parse_not_null_and_default() {
+        let field = Field::from_str("qty INT NOT NULL DEFAULT 1").unwrap();
+        assert!(field.is_not_null());
+        assert_eq!(field.get_default(), Some(&Value::Int(1)));
+        let field = Field::from_str("label STRING DEFAULT 'it''s new' null").unwrap();
+        assert!(!field.is_not_null());
+        assert_eq!(
+            field.get_default(),
+            Some(&Value::String("it's new".to_string()))
+        );
+        let field = Field::from_str("day DATE default '2024-06-10'").unwrap();
+        assert_eq!(field.get_default(), Some(&Value::Date(19884)));
+        let field = Field::from_str("cost DECIMAL(6, 2) DEFAULT NULL").unwrap();
+        assert_eq!(field.get_default(), None);
+    }
+
+    #[test]
+    fn field_from_str_should_read_quoted_name_and_negative_default() {
+        let field = Field::from_str("\"unit price\" INT DEFAULT -5").unwrap();
+        assert_eq!(field.get_name(), "unit price");
+        assert_eq!(field.get_default(), Some(&Value::Int(-5)));
+    }
+
+    #[test]
+    fn field_from_str_should_keep_nested_field_constraints() {
+        let field = Field::from_str("event STRUCT<kind STRING NOT NULL> NOT NULL").unwrap();
+        assert!(field.is_not_null());
+        match field.get_type() {
+            Type::Struct(fields) => assert!(fields[0].is_not_null()),
+            _ => panic!("expected a struct"),
+        }
+    }
+
+    #[test]
+    fn field_parse_should_return_inline_keys() {
+        let (field, keys) = Field::parse("id INT PRIMARY KEY").unwrap();
+        assert!(field.is_not_null());
+        assert_eq!(keys, vec![KeyKind::Primary]);
+        let (field, keys) = Field::parse("email STRING unique NOT NULL").unwrap();
+        assert!(field.is_not_null());
+        assert_eq!(keys, vec![KeyKind::Unique]);
+    }
+
+    #[test]
+    fn field_from_str_should_parse_collation() {
+        let field = Field::from_str("name STRING COLLATE nocase NOT NULL").unwrap();
+        assert_eq!(field.get_collation(), Collation::NoCase);
+        assert!(field.is_not_null());
+        let field = Field::from_str("name STRING").unwrap();
+        assert_eq!(field.get_collation(), Collation::Binary);
+    }
+
+    #[test]
+    #[should_panic]
+    fn field_from_str_should_panic_if_collation_on_non_string() {
+        Field::from_str("qty INT COLLATE nocase").unwrap();
+    }
+
+    #[test]
+    fn field_from_str_should_parse_generated_column() {
+        let field =
+            Field::from_str("total FLOAT GENERATED ALWAYS AS (cost * (qty + 1)) STORED NOT NULL")
+                .unwrap();
+        let generated = field.get_generated().unwrap();
+        assert_eq!(generated.kind, GeneratedKind::Stored);
+        assert_eq!(generated.expression.to_string(), "cost * (qty + 1)");
+        assert!(field.is_not_null());
+        let field = Field::from_str("label STRING GENERATED ALWAYS AS (name)").unwrap();
+        assert!(field.is_virtual());
+    }
+
+    #[test]
+    #[should_panic]
+    fn field_from_str_should_panic_if_generated_with_default() {
+        Field::from_str("total INT DEFAULT 0 GENERATED ALWAYS AS (qty * 2) STORED").unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn field_from_str_should_panic_if_nested_key() {
+        Field::from_str("event STRUCT<id INT PRIMARY KEY>").unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn field_from_str_should_panic_if_default_dont_match_type() {
+        Field::from_str("qty INT DEFAULT 'abc'").unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn field_from_str_should_panic_if_unknown_constraint() {
+        Field::from_str("qty INT NOT EMPTY").unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn field_from_str_should_panic_if_missing_type() {
+        Field::from_str("id").unwrap();
+    }
+
     #[test]
     #[should_panic]
     fn field_from_str_should_panic_if_invalid_string() {
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+use crate::storage::schema::_type::Type;
+use crate::storage::schema::error::Error;
+use crate::storage::schema::expression::Expression;
+use crate::storage::schema::value::Value;
+use crate::storage::schema::Schema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GeneratedKind {
+    /// Computed when the tuple is built and written with the other columns.
+    Stored,
+    /// Computed on read, the tuple only keeps its null flag.
+    Virtual,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Generated {
+    pub kind: GeneratedKind,
+    pub expression: Expression,
+}
+
+impl Generated {
+    pub fn build(kind: GeneratedKind, expression: Expression) -> Generated {
+        Generated { kind, expression }
+    }
+
+    pub fn is_virtual(&self) -> bool {
+        self.kind == GeneratedKind::Virtual
+    }
+
+    pub fn compute(
+        &self,
+        schema: &Schema,
+        values: &[Option<Value>],
+        _type: &Type,
+    ) -> Result<Option<Value>, Error> {
+        self.expression
+            .evaluate(schema, values)?
+            .map(|value| value.cast(_type))
+            .transpose()
+    }
+}
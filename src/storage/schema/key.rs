@@ -0,0 +1,130 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage::schema::encoding::Encoding;
+use crate::storage::schema::error::Error;
+use crate::storage::schema::expression::quote_identifier;
+use crate::storage::schema::parser;
+use crate::storage::schema::parser::{Token, Tokens};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum KeyKind {
+    Primary,
+    Unique,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Key {
+    pub kind: KeyKind,
+    pub columns: Vec<String>,
+}
+
+impl Key {
+    pub fn build(kind: KeyKind, columns: Vec<String>) -> Key {
+        Key { kind, columns }
+    }
+
+    pub(super) fn split_clause(tokens: &Tokens) -> Option<(KeyKind, &Tokens)> {
+        let (kind, rest) = match tokens {
+            [(_, primary), (_, key), rest @ ..]
+                if primary.is_word("PRIMARY") && key.is_word("KEY") =>
+            {
+                (KeyKind::Primary, rest)
+            }
+            [(_, unique), rest @ ..] if unique.is_word("UNIQUE") => (KeyKind::Unique, rest),
+            _ => return None,
+        };
+        matches!(rest.first(), Some((_, Token::Symbol('(')))).then_some((kind, rest))
+    }
+
+    pub(super) fn from_tokens(source: &str, tokens: &Tokens) -> Result<Key, Error> {
+        let error = || {
+            Error::InvalidSchema(format!(
+                "\n- Invalid key syntax. Expected \"PRIMARY KEY (col, ...)\" or \"UNIQUE (col, ...)\" Actual \"{}\"",
+                parser::text(source, tokens)
+            ))
+        };
+        let (kind, columns) = Self::split_clause(tokens).ok_or_else(error)?;
+        let columns = match columns {
+            [(_, Token::Symbol('(')), columns @ .., (_, Token::Symbol(')'))] => columns,
+            _ => return Err(error()),
+        };
+        let columns = columns
+            .split(|(_, token)| *token == Token::Symbol(','))
+            .map(|column| match column {
+                [(_, token)] => token.name().map(str::to_string),
+                _ => None,
+            })
+            .collect::<Option<Vec<String>>>()
+            .ok_or_else(error)?;
+        Ok(Key::build(kind, columns))
+    }
+}
+
+impl Encoding<Key> for Key {
+    fn from_str(key_str: &str) -> Result<Key, Error> {
+        let (source, tokens) = parser::lex(key_str)?;
+        Key::from_tokens(&source, &tokens)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            KeyKind::Primary => "PRIMARY KEY",
+            KeyKind::Unique => "UNIQUE",
+        };
+        let columns: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| quote_identifier(column))
+            .collect();
+        write!(f, "{} ({})", kind, columns.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_str_should_parse_key_clauses() {
+        assert_eq!(
+            Key::from_str("PRIMARY KEY (id, day)").unwrap(),
+            Key::build(KeyKind::Primary, vec!["id".to_string(), "day".to_string()])
+        );
+        assert_eq!(
+            Key::from_str(" unique(email) ").unwrap(),
+            Key::build(KeyKind::Unique, vec!["email".to_string()])
+        );
+    }
+
+    #[test]
+    fn from_str_should_read_quoted_columns() {
+        assert_eq!(
+            Key::from_str("UNIQUE (\"order id\", day)").unwrap().columns,
+            vec!["order id".to_string(), "day".to_string()]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_str_should_panic_if_empty_column() {
+        Key::from_str("UNIQUE (a, )").unwrap();
+    }
+
+    #[test]
+    fn to_string_should_render_clause() {
+        assert_eq!(
+            Key::build(KeyKind::Primary, vec!["a".to_string(), "b".to_string()]).to_string(),
+            "PRIMARY KEY (a, b)"
+        );
+        let key = Key::build(
+            KeyKind::Unique,
+            vec!["order id".to_string(), "null".to_string()],
+        );
+        assert_eq!(key.to_string(), "UNIQUE (\"order id\", \"null\")");
+        assert_eq!(Key::from_str(&key.to_string()).unwrap(), key);
+    }
+}
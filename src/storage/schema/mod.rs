@@ -1,50 +1,554 @@
+use std::cmp::Ordering;
+
 use crate::storage::schema::encoding::Encoding;
 use serde::{Deserialize, Serialize};
 
+use crate::storage::schema::check::Check;
+use crate::storage::schema::collation::Collation;
+use crate::storage::schema::diff::Diff;
 use crate::storage::schema::error::Error;
+use crate::storage::schema::expression::Expression;
 use crate::storage::schema::field::Field;
+use crate::storage::schema::key::{Key, KeyKind};
+use crate::storage::schema::value::Value;
 
-mod _type;
+pub mod _type;
+pub mod alteration;
+pub mod binary;
+pub mod bitmap;
+pub mod cast;
+pub mod check;
+pub mod collation;
+pub mod decimal;
+pub mod diff;
 pub mod encoding;
 pub mod error;
-mod field;
+pub mod expression;
+pub mod field;
+pub mod generated;
+pub mod key;
+mod parser;
+pub mod temporal;
+pub mod value;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Schema {
     pub fields: Vec<Field>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<Key>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checks: Vec<Check>,
 }
 
 impl Schema {
-    pub fn tuple_size(&self, nulls: Option<&[u8]>) -> usize {
+    pub fn index_of(&self, name: &str) -> Option<usize> {
         self.fields
             .iter()
-            .zip(nulls.unwrap_or(&vec![0; self.fields.len()]).iter())
-            .filter(|(_, n)| **n == 0)
-            .map(|(f, _)| f.get_type().get_byte_size())
-            .sum()
+            .position(|field| parser::same_name(field.get_name(), name))
+    }
+
+    pub fn get_field(&self, name: &str) -> Option<&Field> {
+        self.index_of(name).map(|index| &self.fields[index])
+    }
+
+    pub fn project(&self, names: &[&str]) -> Result<Schema, Error> {
+        let mut fields: Vec<Field> = Vec::with_capacity(names.len());
+        for (index, name) in names.iter().enumerate() {
+            if names[..index]
+                .iter()
+                .any(|other| parser::same_name(other, name))
+            {
+                return Err(Error::InvalidSchema(format!(
+                    "\n- Column \"{}\" is projected twice",
+                    name
+                )));
+            }
+            fields.push(
+                self.get_field(name).cloned().ok_or_else(|| {
+                    Error::InvalidSchema(format!("\n- Unknown column \"{}\"", name))
+                })?,
+            );
+        }
+        let projected = |columns: Vec<&str>| {
+            columns
+                .iter()
+                .all(|column| names.iter().any(|name| parser::same_name(name, column)))
+        };
+        let schema = Schema {
+            fields,
+            keys: self
+                .keys
+                .iter()
+                .filter(|key| projected(key.columns.iter().map(String::as_str).collect()))
+                .cloned()
+                .collect(),
+            checks: self
+                .checks
+                .iter()
+                .filter(|check| projected(check.expression.columns()))
+                .cloned()
+                .collect(),
+        };
+        for field in &schema.fields {
+            schema.check_generated(field)?;
+        }
+        Ok(schema)
+    }
+
+    pub fn merge(&self, other: &Schema) -> Result<Schema, Error> {
+        let mut fields: Vec<Field> = Vec::with_capacity(self.fields.len());
+        for field in &self.fields {
+            fields.push(match other.get_field(field.get_name()) {
+                Some(other_field) => field.merge(other_field)?,
+                None => Self::one_sided(field),
+            });
+        }
+        for field in &other.fields {
+            if self.index_of(field.get_name()).is_none() {
+                fields.push(Self::one_sided(field));
+            }
+        }
+        Ok(Schema {
+            fields,
+            keys: self
+                .keys
+                .iter()
+                .filter(|key| other.keys.contains(key))
+                .cloned()
+                .collect(),
+            checks: self
+                .checks
+                .iter()
+                .filter(|check| other.checks.contains(check))
+                .cloned()
+                .collect(),
+        })
+    }
+
+    fn one_sided(field: &Field) -> Field {
+        let mut field = field.clone();
+        field.set_not_null(field.is_not_null() && field.get_default().is_some());
+        field
+    }
+
+    pub fn diff(&self, other: &Schema) -> Diff {
+        Diff::build(self, other)
+    }
+
+    pub fn null_bitmap_size(&self) -> usize {
+        bitmap::width(self.fields.len())
+    }
+
+    pub fn tuple_size(&self, nulls: Option<&[u8]>, data: &[u8]) -> Option<usize> {
+        self.fields
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !nulls.is_some_and(|nulls| bitmap::is_set(nulls, *index)))
+            .try_fold(0, |offset, (_, f)| {
+                Some(offset + f.get_type().get_value_size(data.get(offset..)?)?)
+            })
+    }
+
+    pub fn check_nulls(&self, nulls: &[u8]) -> Result<(), Error> {
+        if nulls.len() != self.null_bitmap_size()
+            || (self.fields.len()..nulls.len() * 8).any(|index| bitmap::is_set(nulls, index))
+        {
+            return Err(Error::InvalidValue(format!(
+                "Null bitmap {:?} doesn't match {} columns",
+                nulls,
+                self.fields.len()
+            )));
+        }
+        for (index, field) in self.fields.iter().enumerate() {
+            match (field.is_virtual(), bitmap::is_set(nulls, index)) {
+                (true, false) => {
+                    return Err(Error::InvalidValue(format!(
+                        "Virtual column \"{}\" can't hold stored data",
+                        field.get_name()
+                    )))
+                }
+                (false, true) if field.is_not_null() => return Err(not_null_violation(field)),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn primary_key(&self) -> Option<&Key> {
+        self.keys.iter().find(|key| key.kind == KeyKind::Primary)
+    }
+
+    pub fn unique_keys(&self) -> Vec<&Key> {
+        self.keys
+            .iter()
+            .filter(|key| key.kind == KeyKind::Unique)
+            .collect()
+    }
+
+    pub fn check_keys(&self, values: &[Option<Value>], row: &[Option<Value>]) -> Result<(), Error> {
+        for key in &self.keys {
+            let key_values = self.key_values(key, values)?;
+            if key_values.iter().any(Option::is_none) {
+                continue;
+            }
+            if self.compare_keys(key, values, row)? == Some(Ordering::Equal) {
+                return Err(Error::ConstraintViolation(format!(
+                    "Duplicate value ({}) violates {} constraint",
+                    key_values
+                        .iter()
+                        .map(|value| value.map(Value::to_string).unwrap_or_default())
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                    key
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Incomparable key values, like a NaN, give `None` and never count as duplicates.
+    pub fn compare_keys(
+        &self,
+        key: &Key,
+        left: &[Option<Value>],
+        right: &[Option<Value>],
+    ) -> Result<Option<Ordering>, Error> {
+        let left = self.key_values(key, left)?;
+        let right = self.key_values(key, right)?;
+        for ((column, left), right) in key.columns.iter().zip(left).zip(right) {
+            let collation = self
+                .get_field(column)
+                .map_or(Collation::Binary, Field::get_collation);
+            let ordering = match (left, right) {
+                (Some(left), Some(right)) => collation.compare_values(left, right),
+                (left, right) => Some(left.is_some().cmp(&right.is_some())),
+            };
+            if ordering != Some(Ordering::Equal) {
+                return Ok(ordering);
+            }
+        }
+        Ok(Some(Ordering::Equal))
+    }
+
+    fn key_values<'a>(
+        &self,
+        key: &Key,
+        values: &'a [Option<Value>],
+    ) -> Result<Vec<Option<&'a Value>>, Error> {
+        key.columns
+            .iter()
+            .map(|column| {
+                self.index_of(column)
+                    .and_then(|index| values.get(index))
+                    .map(Option::as_ref)
+                    .ok_or_else(|| {
+                        Error::InvalidValue(format!("Missing value for key column \"{}\"", column))
+                    })
+            })
+            .collect()
+    }
+
+    pub fn check_constraints(&self, values: &[Option<Value>]) -> Result<(), Error> {
+        for check in &self.checks {
+            match check.expression.evaluate(self, values)? {
+                Some(Value::Boolean(true)) | None => {}
+                Some(Value::Boolean(false)) => {
+                    return Err(Error::ConstraintViolation(format!(
+                        "Row violates check constraint \"{}\" ({})",
+                        check.name, check.expression
+                    )))
+                }
+                Some(value) => {
+                    return Err(Error::InvalidValue(format!(
+                        "Check constraint \"{}\" must return a boolean, actual {}",
+                        check.name, value
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn add_check(&mut self, name: Option<String>, expression: Expression) -> Result<(), Error> {
+        if let Some(column) = expression
+            .columns()
+            .into_iter()
+            .find(|column| self.index_of(column).is_none())
+        {
+            return Err(Error::InvalidSchema(format!(
+                "\n- Unknown column \"{}\" in check ({})",
+                column, expression
+            )));
+        }
+        let name = match name {
+            Some(name) if self.checks.iter().any(|check| check.name == name) => {
+                return Err(Error::InvalidSchema(format!(
+                    "\n- Check constraint \"{}\" is declared twice",
+                    name
+                )))
+            }
+            Some(name) => name,
+            None => {
+                let base = Check::default_name(&expression);
+                let mut name = base.clone();
+                let mut suffix = 1;
+                while self.checks.iter().any(|check| check.name == name) {
+                    name = format!("{}{}", base, suffix);
+                    suffix += 1;
+                }
+                name
+            }
+        };
+        self.checks.push(Check::build(name, expression));
+        Ok(())
+    }
+
+    fn check_generated(&self, field: &Field) -> Result<(), Error> {
+        let generated = match field.get_generated() {
+            Some(generated) => generated,
+            None => return Ok(()),
+        };
+        for column in generated.expression.columns() {
+            match self.get_field(column) {
+                None => {
+                    return Err(Error::InvalidSchema(format!(
+                        "\n- Unknown column \"{}\" in generated column \"{}\"",
+                        column,
+                        field.get_name()
+                    )))
+                }
+                Some(other) if other.get_generated().is_some() => {
+                    return Err(Error::InvalidSchema(format!(
+                        "\n- Generated column \"{}\" can't use generated column \"{}\"",
+                        field.get_name(),
+                        column
+                    )))
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn add_key(&mut self, key: Key) -> Result<(), Error> {
+        if key.kind == KeyKind::Primary && self.primary_key().is_some() {
+            return Err(Error::InvalidSchema(format!(
+                "\n- Multiple primary keys declared, {} is redundant",
+                key
+            )));
+        }
+        for (index, column) in key.columns.iter().enumerate() {
+            if key.columns[..index]
+                .iter()
+                .any(|other| parser::same_name(other, column))
+            {
+                return Err(Error::InvalidSchema(format!(
+                    "\n- Column \"{}\" is repeated in {}",
+                    column, key
+                )));
+            }
+            match self
+                .fields
+                .iter_mut()
+                .find(|field| parser::same_name(field.get_name(), column))
+            {
+                Some(field) if key.kind == KeyKind::Primary => field.set_not_null(true),
+                Some(_) => {}
+                None => {
+                    return Err(Error::InvalidSchema(format!(
+                        "\n- Unknown column \"{}\" in {}",
+                        column, key
+                    )))
+                }
+            }
+        }
+        if !self.keys.contains(&key) {
+            self.keys.push(key);
+        }
+        Ok(())
+    }
+
+    pub fn encode(&self, values: &[Option<Value>]) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        if values.len() > self.fields.len() {
+            return Err(Error::InvalidValue(format!(
+                "Expected at most {} values, actual {}",
+                self.fields.len(),
+                values.len()
+            )));
+        }
+        let omitted = self.fields[values.len()..]
+            .iter()
+            .map(|field| field.get_default().cloned());
+        let column_error = |field: &Field, err: Error| {
+            Error::InvalidValue(format!("Column \"{}\": {}", field.get_name(), err))
+        };
+        // Generated columns are always recomputed, whatever the producer passed for them.
+        let mut values = values
+            .iter()
+            .cloned()
+            .chain(omitted)
+            .zip(&self.fields)
+            .map(|(value, field)| match value {
+                Some(value) if field.get_generated().is_none() => value
+                    .coerce(field.get_type())
+                    .map(Some)
+                    .map_err(|err| column_error(field, err)),
+                _ => Ok(None),
+            })
+            .collect::<Result<Vec<Option<Value>>, Error>>()?;
+        for (index, field) in self.fields.iter().enumerate() {
+            if let Some(generated) = field.get_generated() {
+                values[index] = generated
+                    .compute(self, &values, field.get_type())
+                    .map_err(|err| column_error(field, err))?;
+            }
+        }
+        let mut nulls: Vec<bool> = Vec::with_capacity(values.len());
+        let mut data: Vec<u8> = Vec::new();
+        for (field, value) in self.fields.iter().zip(&values) {
+            match value {
+                Some(value) if !field.is_virtual() => {
+                    nulls.push(false);
+                    data.extend_from_slice(&value.as_bytes());
+                }
+                None if field.is_not_null() => return Err(not_null_violation(field)),
+                _ => nulls.push(true),
+            }
+        }
+        let nulls = bitmap::pack(&nulls);
+        self.check_nulls(&nulls)?;
+        Ok((nulls, data))
+    }
+
+    pub fn decode(&self, nulls: &[u8], data: &[u8]) -> Result<Vec<Option<Value>>, Error> {
+        let mut offset = 0;
+        let mut values: Vec<Option<Value>> = Vec::with_capacity(self.fields.len());
+        for (index, field) in self.fields.iter().enumerate() {
+            if !bitmap::is_set(nulls, index) {
+                let bytes = data.get(offset..).unwrap_or(&[]);
+                let value = Value::from_bytes(field.get_type(), bytes)?;
+                offset += field
+                    .get_type()
+                    .get_value_size(bytes)
+                    .unwrap_or(bytes.len());
+                values.push(Some(value));
+            } else {
+                values.push(None);
+            }
+        }
+        if offset != data.len() {
+            return Err(Error::InvalidValue(format!(
+                "{} bytes left after the last column",
+                data.len() - offset
+            )));
+        }
+        for (index, field) in self.fields.iter().enumerate() {
+            if let Some(generated) = field
+                .get_generated()
+                .filter(|generated| generated.is_virtual())
+            {
+                values[index] = generated.compute(self, &values, field.get_type())?;
+            }
+        }
+        Ok(values)
     }
 }
 
 impl Encoding<Schema> for Schema {
     fn from_str(schema: &str) -> Result<Schema, Error> {
-        let fields_str = schema.trim().split_terminator(',');
-        let fields_result: Vec<Result<Field, Error>> = fields_str.map(Field::from_str).collect();
-        if fields_result.iter().any(|res| res.is_err()) {
-            let errors: Vec<Error> = fields_result.into_iter().filter_map(Result::err).collect();
+        let (source, tokens) = parser::lex(schema)?;
+        let mut errors: Vec<(usize, Error)> = Vec::new();
+        let mut fields: Vec<(usize, Field)> = Vec::new();
+        let mut inline_keys: Vec<(usize, Key)> = Vec::new();
+        let mut table_keys: Vec<(usize, Key)> = Vec::new();
+        let mut checks: Vec<(usize, Option<String>, Expression)> = Vec::new();
+        for (offset, item) in parser::split_items(&tokens) {
+            let result = if item.is_empty() {
+                Err(Error::InvalidSchema("\n- Empty definition".to_string()))
+            } else if Key::split_clause(item).is_some() {
+                Key::from_tokens(&source, item).map(|key| table_keys.push((offset, key)))
+            } else if Check::split_clause(item).is_some() {
+                Check::from_tokens(&source, item)
+                    .map(|(name, expression)| checks.push((offset, name, expression)))
+            } else {
+                Field::from_tokens(&source, item).map(|(field, kinds)| {
+                    for kind in kinds {
+                        let columns = vec![field.get_name().to_string()];
+                        inline_keys.push((offset, Key::build(kind, columns)));
+                    }
+                    fields.push((offset, field));
+                })
+            };
+            if let Err(error) = result {
+                errors.push((offset, error));
+            }
+        }
+        for (index, (offset, field)) in fields.iter().enumerate() {
+            if let Some((first, _)) = fields[..index]
+                .iter()
+                .find(|(_, other)| parser::same_name(other.get_name(), field.get_name()))
+            {
+                let (line, column) = parser::position(&source, *first);
+                errors.push((
+                    *offset,
+                    Error::InvalidSchema(format!(
+                        "\n- Duplicate column \"{}\", already declared at line {}, column {}",
+                        field.get_name(),
+                        line,
+                        column
+                    )),
+                ));
+            }
+        }
+        let (offsets, fields): (Vec<usize>, Vec<Field>) = fields.into_iter().unzip();
+        let mut schema_struct = Schema {
+            fields,
+            keys: Vec::new(),
+            checks: Vec::new(),
+        };
+        if errors.is_empty() {
+            for (offset, field) in offsets.iter().zip(&schema_struct.fields) {
+                if let Err(error) = schema_struct.check_generated(field) {
+                    errors.push((*offset, error));
+                }
+            }
+            for (offset, key) in inline_keys.into_iter().chain(table_keys) {
+                if let Err(error) = schema_struct.add_key(key) {
+                    errors.push((offset, error));
+                }
+            }
+            for (offset, name, expression) in checks {
+                if let Err(error) = schema_struct.add_check(name, expression) {
+                    errors.push((offset, error));
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(schema_struct)
+        } else {
+            errors.sort_by_key(|(offset, _)| *offset);
             Err(Error::InvalidSchema(format!(
-                "There are some errors in your schema: {:?}\nGiven schema: {}",
-                errors, schema
+                "There are some errors in your schema:{}",
+                errors
+                    .iter()
+                    .map(|(offset, error)| parser::located(&source, *offset, error))
+                    .collect::<String>()
             )))
-        } else {
-            let fields: Vec<Field> = fields_result.into_iter().filter_map(Result::ok).collect();
-            Ok(Schema { fields })
         }
     }
 }
 
+fn not_null_violation(field: &Field) -> Error {
+    Error::ConstraintViolation(format!(
+        "Column \"{}\" violates NOT NULL constraint",
+        field.get_name()
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::storage::schema::_type::Type;
+    use crate::storage::schema::decimal::Decimal;
 
     use super::*;
 
@@ -58,27 +562,522 @@ mod tests {
                     Field::build("name".to_string(), Type::String),
                     Field::build("minor".to_string(), Type::Boolean)
                 ],
+                keys: vec![],
+                checks: vec![],
+            },
+        );
+    }
+
+    #[test]
+    fn from_str_should_parse_type_parameters() {
+        assert_eq!(
+            Schema::from_str("id INT, cost DECIMAL(10, 2)").unwrap(),
+            Schema {
+                fields: vec![
+                    Field::build("id".to_string(), Type::Int),
+                    Field::build(
+                        "cost".to_string(),
+                        Type::Decimal {
+                            precision: 10,
+                            scale: 2
+                        }
+                    ),
+                ],
+                keys: vec![],
+                checks: vec![],
             },
         );
     }
 
+    #[test]
+    fn from_str_should_parse_nested_types() {
+        let schema =
+            Schema::from_str("id INT, event STRUCT<kind STRING, attrs MAP<STRING, INT>>").unwrap();
+        assert_eq!(schema.fields.len(), 2);
+        assert_eq!(
+            *schema.fields[1].get_type(),
+            Type::Struct(vec![
+                Field::build("kind".to_string(), Type::String),
+                Field::build(
+                    "attrs".to_string(),
+                    Type::Map(Box::new(Type::String), Box::new(Type::Int))
+                ),
+            ])
+        );
+    }
+
     #[test]
     #[should_panic]
     fn from_str_should_panic_with_invalid_schema() {
         Schema::from_str("id INT, name STRIN, minor ").unwrap();
     }
 
+    #[test]
+    fn from_str_should_report_error_positions() {
+        let error = Schema::from_str("id INT,\n  name STRIN,\n  minor ").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "There are some errors in your schema:\
+            \n- line 2, column 3: Unknown type \"STRIN\"\
+            \n- line 3, column 3: Invalid field syntax. Expected \"column_name column_type\" Actual \"minor\""
+        );
+    }
+
+    #[test]
+    fn from_str_should_read_quoted_identifiers_and_comments() {
+        let schema = Schema::from_str(
+            "\"order id\" INT, -- identifier\n\"Name, full\" STRING /* shown as is */,\
+            UNIQUE (\"order id\"), CHECK (\"order id\" > 0)",
+        )
+        .unwrap();
+        assert_eq!(schema.fields[0].get_name(), "order id");
+        assert_eq!(schema.fields[1].get_name(), "Name, full");
+        assert_eq!(schema.keys[0].columns, vec!["order id"]);
+        assert_eq!(schema.checks[0].expression.to_string(), "\"order id\" > 0");
+    }
+
+    #[test]
+    fn from_str_should_report_duplicate_columns_ignoring_case() {
+        let error = Schema::from_str("id INT, name STRING,\nID BIGINT").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "There are some errors in your schema:\
+            \n- line 2, column 1: Duplicate column \"ID\", already declared at line 1, column 1"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_str_should_panic_if_duplicate_struct_fields() {
+        Schema::from_str("event STRUCT<kind STRING, Kind INT>").unwrap();
+    }
+
+    #[test]
+    fn from_str_should_parse_inline_and_table_keys() {
+        let schema = Schema::from_str(
+            "id INT PRIMARY KEY, email STRING UNIQUE, first STRING, last STRING, UNIQUE (first, last)",
+        )
+        .unwrap();
+        assert_eq!(
+            schema.primary_key(),
+            Some(&Key::build(KeyKind::Primary, vec!["id".to_string()]))
+        );
+        assert_eq!(
+            schema.unique_keys(),
+            vec![
+                &Key::build(KeyKind::Unique, vec!["email".to_string()]),
+                &Key::build(
+                    KeyKind::Unique,
+                    vec!["first".to_string(), "last".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn from_str_should_set_primary_key_columns_not_null() {
+        let schema = Schema::from_str("a INT, b INT, c INT, PRIMARY KEY (a, b)").unwrap();
+        assert!(schema.fields[0].is_not_null());
+        assert!(schema.fields[1].is_not_null());
+        assert!(!schema.fields[2].is_not_null());
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_str_should_panic_if_multiple_primary_keys() {
+        Schema::from_str("a INT PRIMARY KEY, b INT, PRIMARY KEY (b)").unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_str_should_panic_if_key_column_unknown() {
+        Schema::from_str("a INT, UNIQUE (b)").unwrap();
+    }
+
+    #[test]
+    fn check_keys_should_reject_duplicates() {
+        let schema = Schema::from_str("id INT PRIMARY KEY, email STRING UNIQUE").unwrap();
+        let rows = [
+            vec![Some(Value::Int(1)), Some(Value::String("a@b".to_string()))],
+            vec![Some(Value::Int(2)), None],
+        ];
+        assert!(rows
+            .iter()
+            .all(|row| schema.check_keys(&[Some(Value::Int(3)), None], row).is_ok()));
+        assert_eq!(
+            schema.check_keys(&[Some(Value::Int(2)), None], &rows[1]),
+            Err(Error::ConstraintViolation(
+                "Duplicate value (2) violates PRIMARY KEY (id) constraint".to_string()
+            ))
+        );
+        assert!(schema
+            .check_keys(
+                &[Some(Value::Int(4)), Some(Value::String("a@b".to_string()))],
+                &rows[0]
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn check_keys_should_honour_collation() {
+        let schema = Schema::from_str("email STRING COLLATE nocase UNIQUE").unwrap();
+        let rows = [vec![Some(Value::String("A@B".to_string()))]];
+        assert!(schema
+            .check_keys(&[Some(Value::String("a@b".to_string()))], &rows[0])
+            .is_err());
+        let key = &schema.keys[0];
+        assert_eq!(
+            schema
+                .compare_keys(key, &[Some(Value::String("b".to_string()))], &rows[0])
+                .unwrap(),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            schema.compare_keys(key, &[None], &rows[0]).unwrap(),
+            Some(Ordering::Less)
+        );
+    }
+
+    #[test]
+    fn check_keys_should_keep_nan_distinct() {
+        let schema = Schema::from_str("cost FLOAT UNIQUE").unwrap();
+        let rows = [
+            vec![Some(Value::Float(1.0))],
+            vec![Some(Value::Float(f64::NAN))],
+        ];
+        assert!(schema
+            .check_keys(&[Some(Value::Float(f64::NAN))], &rows[1])
+            .is_ok());
+        assert!(schema
+            .check_keys(&[Some(Value::Float(1.0))], &rows[0])
+            .is_err());
+    }
+
+    #[test]
+    fn from_str_should_name_checks() {
+        let schema = Schema::from_str(
+            "cost FLOAT, qty INT, CHECK (qty > 0), CHECK (qty < 100), CONSTRAINT positive_cost CHECK (cost >= 0)",
+        )
+        .unwrap();
+        let names: Vec<&str> = schema.checks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["qty_check", "qty_check1", "positive_cost"]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_str_should_panic_if_check_column_unknown() {
+        Schema::from_str("qty INT, CHECK (cost > 0)").unwrap();
+    }
+
+    #[test]
+    fn check_constraints_should_name_violated_check() {
+        let schema = Schema::from_str(
+            "cost FLOAT, qty INT, CONSTRAINT positive_cost CHECK (cost >= 0), CHECK (qty > 0)",
+        )
+        .unwrap();
+        assert!(schema
+            .check_constraints(&[Some(Value::Float(1.5)), None])
+            .is_ok());
+        assert_eq!(
+            schema.check_constraints(&[Some(Value::Float(-1.0)), Some(Value::Int(1))]),
+            Err(Error::ConstraintViolation(
+                "Row violates check constraint \"positive_cost\" (cost >= 0)".to_string()
+            ))
+        );
+    }
+
     #[test]
     fn tuple_size_should_return_max_bytes() {
         let schema =
             Schema::from_str("id BIGINT, cost FLOAT, available BOOLEAN, date TIMESTAMP").unwrap();
-        assert_eq!(33, schema.tuple_size(None));
+        assert_eq!(Some(33), schema.tuple_size(None, &[0; 33]));
     }
 
     #[test]
     fn tuple_size_should_return_size_with_null() {
         let schema =
             Schema::from_str("id BIGINT, cost FLOAT, available BOOLEAN, date TIMESTAMP").unwrap();
-        assert_eq!(9, schema.tuple_size(Some(&[1, 0, 0, 1])));
+        assert_eq!(Some(9), schema.tuple_size(Some(&[0b1001]), &[0; 9]));
+    }
+
+    #[test]
+    fn encode_should_return_nulls_and_data() {
+        let schema = Schema::from_str("id INT, name STRING, minor BOOLEAN").unwrap();
+        let (nulls, data) = schema
+            .encode(&[Some(Value::Int(1)), None, Some(Value::Boolean(true))])
+            .unwrap();
+        assert_eq!(nulls, vec![0b010]);
+        assert_eq!(data, vec![1, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn encode_should_panic_if_type_mismatch() {
+        let schema = Schema::from_str("id INT, name STRING").unwrap();
+        schema
+            .encode(&[Some(Value::Bigint(1)), Some(Value::String("a".to_string()))])
+            .unwrap();
+    }
+
+    #[test]
+    fn encode_should_compute_stored_and_virtual_columns() {
+        let schema = Schema::from_str(
+            "cost FLOAT, qty INT, \
+             total FLOAT GENERATED ALWAYS AS (cost * qty) STORED, \
+             half INT GENERATED ALWAYS AS (qty / 2) VIRTUAL",
+        )
+        .unwrap();
+        let (nulls, data) = schema
+            .encode(&[
+                Some(Value::Float(1.5)),
+                Some(Value::Int(4)),
+                Some(Value::Float(0.0)),
+            ])
+            .unwrap();
+        assert_eq!(nulls, vec![0b1000]);
+        assert_eq!(data.len(), 8 + 4 + 8);
+        assert_eq!(
+            schema.decode(&nulls, &data).unwrap(),
+            vec![
+                Some(Value::Float(1.5)),
+                Some(Value::Int(4)),
+                Some(Value::Float(6.0)),
+                Some(Value::Int(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn from_str_should_report_invalid_generated_references() {
+        assert_eq!(
+            Schema::from_str(
+                "a INT, b INT GENERATED ALWAYS AS (a + 1), c INT GENERATED ALWAYS AS (b + d)"
+            )
+            .unwrap_err()
+            .to_string(),
+            "There are some errors in your schema:\n\
+             - line 1, column 43: Generated column \"c\" can't use generated column \"b\""
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn check_nulls_should_panic_if_virtual_column_stored() {
+        let schema = Schema::from_str("a INT, b INT GENERATED ALWAYS AS (a + 1)").unwrap();
+        schema.check_nulls(&[0b00]).unwrap();
+    }
+
+    #[test]
+    fn encode_should_store_enum_labels_as_codes() {
+        let schema =
+            Schema::from_str("id INT, status ENUM('new', 'done') NOT NULL DEFAULT 'new'").unwrap();
+        let (nulls, data) = schema
+            .encode(&[Some(Value::Int(1)), Some(Value::String("done".to_string()))])
+            .unwrap();
+        assert_eq!(data, vec![1, 0, 0, 0, 1]);
+        assert_eq!(
+            schema.decode(&nulls, &data).unwrap(),
+            vec![
+                Some(Value::Int(1)),
+                Some(Value::Enum(1, "done".to_string()))
+            ]
+        );
+        let (_, data) = schema.encode(&[Some(Value::Int(2))]).unwrap();
+        assert_eq!(data, vec![2, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn encode_should_panic_if_unknown_enum_label() {
+        let schema = Schema::from_str("status ENUM('new', 'done')").unwrap();
+        schema
+            .encode(&[Some(Value::String("lost".to_string()))])
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn encode_should_panic_if_too_many_values() {
+        let schema = Schema::from_str("id INT").unwrap();
+        schema
+            .encode(&[Some(Value::Int(1)), Some(Value::Int(2))])
+            .unwrap();
+    }
+
+    #[test]
+    fn encode_should_fill_omitted_values_with_default() {
+        let schema = Schema::from_str(
+            "id INT, qty INT NOT NULL DEFAULT 1, name STRING, ok BOOLEAN DEFAULT true",
+        )
+        .unwrap();
+        let (nulls, data) = schema.encode(&[Some(Value::Int(7))]).unwrap();
+        assert_eq!(nulls, vec![0b0100]);
+        assert_eq!(
+            schema.decode(&nulls, &data).unwrap(),
+            vec![
+                Some(Value::Int(7)),
+                Some(Value::Int(1)),
+                None,
+                Some(Value::Boolean(true))
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn encode_should_panic_if_not_null_violated() {
+        let schema = Schema::from_str("id INT NOT NULL, name STRING").unwrap();
+        schema.encode(&[None, None]).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn check_nulls_should_panic_if_length_mismatch() {
+        let schema = Schema::from_str("id INT, name STRING").unwrap();
+        schema.check_nulls(&[0, 0]).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn check_nulls_should_panic_if_padding_bits_set() {
+        let schema = Schema::from_str("id INT, name STRING").unwrap();
+        schema.check_nulls(&[0b100]).unwrap();
+    }
+
+    #[test]
+    fn null_bitmap_size_should_round_up_to_bytes() {
+        let fields: Vec<String> = (0..17).map(|index| format!("c{} INT", index)).collect();
+        assert_eq!(
+            Schema::from_str(&fields[..8].join(", "))
+                .unwrap()
+                .null_bitmap_size(),
+            1
+        );
+        assert_eq!(
+            Schema::from_str(&fields.join(", "))
+                .unwrap()
+                .null_bitmap_size(),
+            3
+        );
+    }
+
+    #[test]
+    fn encode_should_store_decimal_with_column_scale() {
+        let schema = Schema::from_str("cost DECIMAL(6, 2)").unwrap();
+        let (_, data) = schema
+            .encode(&[Some(Value::Decimal(Decimal::from_str("19.9").unwrap()))])
+            .unwrap();
+        assert_eq!(data, 1990_i128.to_le_bytes().to_vec());
+        assert_eq!(
+            schema.decode(&[0], &data).unwrap(),
+            vec![Some(Value::Decimal(Decimal::from_str("19.90").unwrap()))]
+        );
+    }
+
+    #[test]
+    fn decode_should_return_values() {
+        let schema = Schema::from_str("id INT, name STRING, minor BOOLEAN").unwrap();
+        let values = vec![
+            Some(Value::Int(7)),
+            Some(Value::String("john".to_string())),
+            None,
+        ];
+        let (nulls, data) = schema.encode(&values).unwrap();
+        assert_eq!(schema.decode(&nulls, &data).unwrap(), values);
+    }
+
+    #[test]
+    #[should_panic]
+    fn decode_should_panic_if_trailing_bytes() {
+        let schema = Schema::from_str("id INT, name STRING").unwrap();
+        let (nulls, mut data) = schema
+            .encode(&[Some(Value::Int(7)), Some(Value::String("john".to_string()))])
+            .unwrap();
+        data.push(0);
+        schema.decode(&nulls, &data).unwrap();
+    }
+
+    #[test]
+    fn tuple_size_should_read_string_length() {
+        let schema = Schema::from_str("id INT, name STRING, minor BOOLEAN").unwrap();
+        assert_eq!(
+            Some(13),
+            schema.tuple_size(None, &[1, 0, 0, 0, 4, 0, 0, 0, 106, 111, 104, 110, 1])
+        );
+        assert_eq!(Some(5), schema.tuple_size(Some(&[0b010]), &[1, 0, 0, 0, 1]));
+    }
+
+    #[test]
+    fn tuple_size_should_return_none_if_data_too_short() {
+        let schema = Schema::from_str("id INT, name STRING, minor BOOLEAN").unwrap();
+        assert_eq!(
+            None,
+            schema.tuple_size(None, &[1, 0, 0, 0, 9, 0, 0, 0, 106, 1])
+        );
+    }
+
+    #[test]
+    fn index_of_should_find_column() {
+        let schema = Schema::from_str("id INT, name STRING").unwrap();
+        assert_eq!(schema.index_of("name"), Some(1));
+        assert_eq!(schema.index_of("age"), None);
+        assert_eq!(*schema.get_field("id").unwrap().get_type(), Type::Int);
+    }
+
+    #[test]
+    fn from_str_should_resolve_names_regardless_of_case() {
+        let schema =
+            Schema::from_str("id INT, qty INT, PRIMARY KEY (ID), CHECK (QTY > 0)").unwrap();
+        assert_eq!(schema.index_of("Qty"), Some(1));
+        assert!(schema.fields[0].is_not_null());
+        assert!(schema
+            .check_constraints(&[Some(Value::Int(1)), Some(Value::Int(0))])
+            .is_err());
+        assert!(Schema::from_str("id INT, UNIQUE (id, ID)").is_err());
+    }
+
+    #[test]
+    fn project_should_keep_order_and_covered_constraints() {
+        let schema = Schema::from_str(
+            "id INT PRIMARY KEY, name STRING UNIQUE, qty INT, CHECK (qty > 0), CHECK (id > qty)",
+        )
+        .unwrap();
+        let projected = schema.project(&["qty", "id"]).unwrap();
+        assert_eq!(
+            projected.fields,
+            vec![schema.fields[2].clone(), schema.fields[0].clone()]
+        );
+        assert_eq!(projected.keys, vec![schema.keys[0].clone()]);
+        assert_eq!(projected.checks, schema.checks);
+        assert!(schema.project(&["name"]).unwrap().checks.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn project_should_panic_if_column_unknown() {
+        Schema::from_str("id INT")
+            .unwrap()
+            .project(&["age"])
+            .unwrap();
+    }
+
+    #[test]
+    fn merge_should_widen_shared_columns_and_append_new_ones() {
+        let schema = Schema::from_str("id INT NOT NULL, qty SMALLINT, UNIQUE (id)").unwrap();
+        let other = Schema::from_str("id BIGINT NOT NULL, day DATE NOT NULL, UNIQUE (id)").unwrap();
+        let merged = schema.merge(&other).unwrap();
+        assert_eq!(
+            merged,
+            Schema::from_str("id BIGINT NOT NULL, qty SMALLINT, day DATE, UNIQUE (id)").unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn merge_should_panic_if_types_incompatible() {
+        Schema::from_str("id INT")
+            .unwrap()
+            .merge(&Schema::from_str("id STRING").unwrap())
+            .unwrap();
     }
 }
@@ -0,0 +1,255 @@
+use std::ops::Range;
+
+use crate::storage::schema::error::Error;
+
+pub(super) type Spanned = (Range<usize>, Token);
+
+pub(super) type Tokens = [Spanned];
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Token {
+    Word(String),
+    Identifier(String),
+    String(String),
+    Number(String),
+    Symbol(char),
+}
+
+impl Token {
+    pub(super) fn is_word(&self, word: &str) -> bool {
+        matches!(self, Token::Word(token) if token.eq_ignore_ascii_case(word))
+    }
+
+    pub(super) fn name(&self) -> Option<&str> {
+        match self {
+            Token::Word(name) | Token::Identifier(name) if !name.is_empty() => Some(name),
+            _ => None,
+        }
+    }
+}
+
+/// Names of columns and fields match regardless of ASCII case, quoted or not.
+pub(super) fn same_name(left: &str, right: &str) -> bool {
+    left.eq_ignore_ascii_case(right)
+}
+
+pub(super) fn lex(input: &str) -> Result<(String, Vec<Spanned>), Error> {
+    let source = strip_comments(input)?;
+    let tokens =
+        tokenize(&source).map_err(|(offset, message)| syntax_error(&source, offset, message))?;
+    Ok((source, tokens))
+}
+
+pub(super) fn text<'a>(source: &'a str, tokens: &Tokens) -> &'a str {
+    match (tokens.first(), tokens.last()) {
+        (Some((first, _)), Some((last, _))) => &source[first.start..last.end],
+        _ => "",
+    }
+}
+
+pub(super) fn split_items(tokens: &Tokens) -> Vec<(usize, &Tokens)> {
+    let mut items: Vec<(usize, &Tokens)> = Vec::new();
+    let mut parentheses = 0;
+    let mut angles = 0;
+    let mut start = 0;
+    let mut offset = tokens.first().map_or(0, |(range, _)| range.start);
+    for (index, (range, token)) in tokens.iter().enumerate() {
+        match token {
+            Token::Symbol('(') => parentheses += 1,
+            Token::Symbol(')') => parentheses -= 1,
+            Token::Symbol('<') if parentheses == 0 => angles += 1,
+            Token::Symbol('>') if parentheses == 0 => angles -= 1,
+            Token::Symbol(',') if parentheses == 0 && angles == 0 => {
+                items.push((offset, &tokens[start..index]));
+                start = index + 1;
+                offset = tokens
+                    .get(start)
+                    .map_or(range.end, |(range, _)| range.start);
+            }
+            _ => {}
+        }
+    }
+    if start < tokens.len() {
+        items.push((offset, &tokens[start..]));
+    }
+    items
+}
+
+pub(super) fn position(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+pub(super) fn located(source: &str, offset: usize, error: &Error) -> String {
+    let (line, column) = position(source, offset);
+    format!(
+        "\n- line {}, column {}: {}",
+        line,
+        column,
+        error.to_string().trim_start().trim_start_matches("- ")
+    )
+}
+
+fn syntax_error(source: &str, offset: usize, message: &str) -> Error {
+    Error::InvalidSchema(located(
+        source,
+        offset,
+        &Error::InvalidSchema(message.to_string()),
+    ))
+}
+
+fn strip_comments(input: &str) -> Result<String, Error> {
+    let bytes = input.as_bytes();
+    let mut stripped = bytes.to_vec();
+    let mut quote: Option<u8> = None;
+    let mut index = 0;
+    while index < bytes.len() {
+        let comment_end = match (quote, bytes[index], bytes.get(index + 1)) {
+            (Some(open), byte, _) => {
+                if byte == open {
+                    quote = None;
+                }
+                None
+            }
+            (None, b'\'' | b'"', _) => {
+                quote = Some(bytes[index]);
+                None
+            }
+            (None, b'-', Some(b'-')) => Some(
+                input[index..]
+                    .find('\n')
+                    .map_or(bytes.len(), |end| index + end),
+            ),
+            (None, b'/', Some(b'*')) => match input[index + 2..].find("*/") {
+                Some(end) => Some(index + 2 + end + 2),
+                None => return Err(syntax_error(input, index, "Unterminated comment")),
+            },
+            _ => None,
+        };
+        match comment_end {
+            Some(end) => {
+                stripped[index..end]
+                    .iter_mut()
+                    .filter(|byte| **byte != b'\n')
+                    .for_each(|byte| *byte = b' ');
+                index = end;
+            }
+            None => index += 1,
+        }
+    }
+    String::from_utf8(stripped).map_err(|_| syntax_error(input, 0, "Invalid characters in comment"))
+}
+
+/// Scans both schema definitions and expressions, errors give the offset they happened at.
+pub(super) fn tokenize(source: &str) -> Result<Vec<Spanned>, (usize, &'static str)> {
+    let mut tokens: Vec<Spanned> = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some((index, char)) = chars.next() {
+        let token = match char {
+            c if c.is_whitespace() => continue,
+            '\'' | '"' => {
+                let mut text = String::new();
+                let mut closed = false;
+                while let Some((_, next)) = chars.next() {
+                    if next == char {
+                        if chars.peek().is_some_and(|(_, c)| *c == char) {
+                            chars.next();
+                        } else {
+                            closed = true;
+                            break;
+                        }
+                    }
+                    text.push(next);
+                }
+                if !closed {
+                    return Err((index, "Unterminated quote"));
+                }
+                if char == '\'' {
+                    Token::String(text)
+                } else {
+                    Token::Identifier(text)
+                }
+            }
+            c if c.is_ascii_digit()
+                || (c == '.' && chars.peek().is_some_and(|(_, c)| c.is_ascii_digit())) =>
+            {
+                let mut number = String::from(c);
+                while let Some((_, next)) = chars.next_if(|(_, c)| c.is_ascii_digit() || *c == '.')
+                {
+                    number.push(next);
+                }
+                Token::Number(number)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut word = String::from(c);
+                while let Some((_, next)) = chars.next_if(|(_, c)| c.is_alphanumeric() || *c == '_')
+                {
+                    word.push(next);
+                }
+                Token::Word(word)
+            }
+            c => Token::Symbol(c),
+        };
+        let end = chars.peek().map_or(source.len(), |(end, _)| *end);
+        tokens.push((index..end, token));
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lex_should_blank_comments_and_read_quotes() {
+        let input = "\"order id\" INT, -- the id\nname STRING DEFAULT 'a--b' /* note */";
+        let (source, tokens) = lex(input).unwrap();
+        assert_eq!(source.len(), input.len());
+        let tokens: Vec<Token> = tokens.into_iter().map(|(_, token)| token).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Identifier("order id".to_string()),
+                Token::Word("INT".to_string()),
+                Token::Symbol(','),
+                Token::Word("name".to_string()),
+                Token::Word("STRING".to_string()),
+                Token::Word("DEFAULT".to_string()),
+                Token::String("a--b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_items_should_ignore_nested_commas() {
+        let (source, tokens) =
+            lex("a MAP<STRING, INT>, CHECK (a IN (1, 2)), b INT DEFAULT 'x, y'").unwrap();
+        let items: Vec<&str> = split_items(&tokens)
+            .into_iter()
+            .map(|(_, item)| text(&source, item))
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                "a MAP<STRING, INT>",
+                "CHECK (a IN (1, 2))",
+                "b INT DEFAULT 'x, y'"
+            ]
+        );
+    }
+
+    #[test]
+    fn position_should_count_lines_and_columns() {
+        assert_eq!(position("id INT,\n  name STRING", 10), (2, 3));
+    }
+
+    #[test]
+    #[should_panic]
+    fn lex_should_panic_if_unterminated_comment() {
+        lex("id INT /* never closed").unwrap();
+    }
+}
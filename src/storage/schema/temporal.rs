@@ -0,0 +1,296 @@
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::storage::schema::encoding::Encoding;
+use crate::storage::schema::error::Error;
+
+pub const MICROS_PER_SECOND: i64 = 1_000_000;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M:%S%.f";
+const TIMESTAMP_FORMATS: [&str; 2] = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"];
+const TIMESTAMPTZ_FORMATS: [&str; 2] = ["%Y-%m-%d %H:%M:%S%.f%:z", "%Y-%m-%dT%H:%M:%S%.f%:z"];
+
+fn epoch() -> NaiveDate {
+    DateTime::UNIX_EPOCH.date_naive()
+}
+
+fn invalid(kind: &str, value: &str) -> Error {
+    Error::InvalidValue(format!("Invalid {} \"{}\"", kind, value))
+}
+
+pub fn parse_date(date_str: &str) -> Result<i32, Error> {
+    let date = NaiveDate::parse_from_str(date_str.trim(), DATE_FORMAT)
+        .map_err(|_| invalid("date", date_str))?;
+    Ok(date.signed_duration_since(epoch()).num_days() as i32)
+}
+
+pub fn format_date(days: i32) -> String {
+    epoch()
+        .checked_add_signed(chrono::Duration::days(days as i64))
+        .map(|date| date.format(DATE_FORMAT).to_string())
+        .unwrap_or_else(|| format!("{} days", days))
+}
+
+pub fn parse_time(time_str: &str) -> Result<i64, Error> {
+    let time = NaiveTime::parse_from_str(time_str.trim(), TIME_FORMAT)
+        .map_err(|_| invalid("time", time_str))?;
+    Ok(time.num_seconds_from_midnight() as i64 * MICROS_PER_SECOND
+        + time.nanosecond() as i64 / 1_000)
+}
+
+pub fn format_time(micros: i64) -> String {
+    NaiveTime::from_num_seconds_from_midnight_opt(
+        micros.div_euclid(MICROS_PER_SECOND) as u32,
+        micros.rem_euclid(MICROS_PER_SECOND) as u32 * 1_000,
+    )
+    .map(|time| time.format(TIME_FORMAT).to_string())
+    .unwrap_or_else(|| format!("{} us", micros))
+}
+
+pub fn parse_timestamp(timestamp_str: &str) -> Result<i64, Error> {
+    TIMESTAMP_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(timestamp_str.trim(), format).ok())
+        .map(|timestamp| timestamp.and_utc().timestamp_micros())
+        .ok_or(invalid("timestamp", timestamp_str))
+}
+
+pub fn format_timestamp(micros: i64) -> String {
+    DateTime::from_timestamp_micros(micros)
+        .map(|timestamp| {
+            timestamp
+                .naive_utc()
+                .format(TIMESTAMP_FORMATS[0])
+                .to_string()
+        })
+        .unwrap_or_else(|| format!("{} us", micros))
+}
+
+pub fn parse_timestamptz(timestamp_str: &str) -> Result<i64, Error> {
+    TIMESTAMPTZ_FORMATS
+        .iter()
+        .find_map(|format| DateTime::parse_from_str(timestamp_str.trim(), format).ok())
+        .map(|timestamp| timestamp.timestamp_micros())
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(timestamp_str.trim())
+                .ok()
+                .map(|timestamp| timestamp.timestamp_micros())
+        })
+        .ok_or(invalid("timestamptz", timestamp_str))
+}
+
+pub fn format_timestamptz(micros: i64) -> String {
+    DateTime::<Utc>::from_timestamp_micros(micros)
+        .map(|timestamp| timestamp.format(TIMESTAMPTZ_FORMATS[0]).to_string())
+        .unwrap_or_else(|| format!("{} us", micros))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Interval {
+    pub months: i32,
+    pub days: i32,
+    pub micros: i64,
+}
+
+impl Interval {
+    pub fn build(months: i32, days: i32, micros: i64) -> Interval {
+        Interval {
+            months,
+            days,
+            micros,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.months.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&self.days.to_le_bytes());
+        bytes.extend_from_slice(&self.micros.to_le_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8; 16]) -> Interval {
+        Interval {
+            months: i32::from_le_bytes(bytes[0..4].try_into().unwrap()),
+            days: i32::from_le_bytes(bytes[4..8].try_into().unwrap()),
+            micros: i64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+        }
+    }
+}
+
+impl Encoding<Interval> for Interval {
+    fn from_str(interval_str: &str) -> Result<Interval, Error> {
+        let tokens: Vec<&str> = interval_str.split_whitespace().collect();
+        if tokens.is_empty() || !tokens.len().is_multiple_of(2) {
+            return Err(invalid("interval", interval_str));
+        }
+        let mut interval = Interval::build(0, 0, 0);
+        for pair in tokens.chunks_exact(2) {
+            let unit = pair[1].to_lowercase();
+            let unit = unit.strip_suffix('s').unwrap_or(&unit);
+            let amount: i64 = pair[0]
+                .parse()
+                .map_err(|_| invalid("interval", interval_str))?;
+            let overflow = || invalid("interval", interval_str);
+            let add_i32 = |field: i32, factor: i64| {
+                amount
+                    .checked_mul(factor)
+                    .and_then(|amount| i32::try_from(amount).ok())
+                    .and_then(|amount| field.checked_add(amount))
+                    .ok_or_else(overflow)
+            };
+            let add_i64 = |field: i64, factor: i64| {
+                amount
+                    .checked_mul(factor)
+                    .and_then(|amount| field.checked_add(amount))
+                    .ok_or_else(overflow)
+            };
+            match unit {
+                "year" => interval.months = add_i32(interval.months, 12)?,
+                "month" => interval.months = add_i32(interval.months, 1)?,
+                "week" => interval.days = add_i32(interval.days, 7)?,
+                "day" => interval.days = add_i32(interval.days, 1)?,
+                "hour" => interval.micros = add_i64(interval.micros, 3_600 * MICROS_PER_SECOND)?,
+                "minute" => interval.micros = add_i64(interval.micros, 60 * MICROS_PER_SECOND)?,
+                "second" => interval.micros = add_i64(interval.micros, MICROS_PER_SECOND)?,
+                "microsecond" => interval.micros = add_i64(interval.micros, 1)?,
+                _ => return Err(invalid("interval", interval_str)),
+            }
+        }
+        Ok(interval)
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = Vec::new();
+        let mut push = |amount: i64, unit: &str| {
+            if amount != 0 {
+                parts.push(format!(
+                    "{} {}{}",
+                    amount,
+                    unit,
+                    if amount.abs() == 1 { "" } else { "s" }
+                ));
+            }
+        };
+        push(self.months as i64 / 12, "year");
+        push(self.months as i64 % 12, "month");
+        push(self.days as i64, "day");
+        push(self.micros / (3_600 * MICROS_PER_SECOND), "hour");
+        push(self.micros / (60 * MICROS_PER_SECOND) % 60, "minute");
+        push(self.micros / MICROS_PER_SECOND % 60, "second");
+        push(self.micros % MICROS_PER_SECOND, "microsecond");
+        if parts.is_empty() {
+            write!(f, "0 seconds")
+        } else {
+            write!(f, "{}", parts.join(" "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn date_should_count_days_from_epoch() {
+        assert_eq!(parse_date("1970-01-01").unwrap(), 0);
+        assert_eq!(parse_date("2024-06-10").unwrap(), 19884);
+        assert_eq!(parse_date("1969-12-31").unwrap(), -1);
+        assert_eq!(format_date(19884), "2024-06-10");
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_date_should_panic_if_invalid() {
+        parse_date("2024-02-30").unwrap();
+    }
+
+    #[test]
+    fn time_should_count_micros_from_midnight() {
+        assert_eq!(parse_time("00:00:01.5").unwrap(), 1_500_000);
+        assert_eq!(format_time(1_500_000), "00:00:01.500");
+        assert_eq!(format_time(parse_time("23:59:59").unwrap()), "23:59:59");
+    }
+
+    #[test]
+    fn timestamp_should_count_micros_from_epoch() {
+        assert_eq!(parse_timestamp("1970-01-01 00:00:01").unwrap(), 1_000_000);
+        assert_eq!(
+            parse_timestamp("2024-06-10T12:30:00.000250").unwrap(),
+            1_718_022_600_000_250
+        );
+        assert_eq!(
+            format_timestamp(1_718_022_600_000_250),
+            "2024-06-10 12:30:00.000250"
+        );
+    }
+
+    #[test]
+    fn timestamptz_should_normalize_to_utc() {
+        assert_eq!(
+            parse_timestamptz("2024-06-10 14:30:00+02:00").unwrap(),
+            parse_timestamp("2024-06-10 12:30:00").unwrap()
+        );
+        assert_eq!(
+            parse_timestamptz("2024-06-10T12:30:00Z").unwrap(),
+            parse_timestamp("2024-06-10 12:30:00").unwrap()
+        );
+        assert_eq!(
+            format_timestamptz(parse_timestamp("2024-06-10 12:30:00").unwrap()),
+            "2024-06-10 12:30:00+00:00"
+        );
+    }
+
+    #[test]
+    fn interval_from_str_should_sum_units() {
+        assert_eq!(
+            Interval::from_str("1 year 2 months 3 days 4 hours").unwrap(),
+            Interval::build(14, 3, 4 * 3_600 * MICROS_PER_SECOND)
+        );
+        assert_eq!(
+            Interval::from_str("-1 day").unwrap(),
+            Interval::build(0, -1, 0)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn interval_from_str_should_panic_if_unknown_unit() {
+        Interval::from_str("3 fortnights").unwrap();
+    }
+
+    #[test]
+    fn interval_from_str_should_reject_overflow() {
+        for interval_str in [
+            "9999999999999 hours",
+            "3000000000 months",
+            "2147483647 days 1 day",
+            "9223372036854775807 microseconds 1 microsecond",
+        ] {
+            assert!(matches!(
+                Interval::from_str(interval_str),
+                Err(Error::InvalidValue(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn interval_to_string_should_render_units() {
+        assert_eq!(
+            Interval::build(14, 3, 90 * MICROS_PER_SECOND).to_string(),
+            "1 year 2 months 3 days 1 minute 30 seconds"
+        );
+        assert_eq!(Interval::build(0, 0, 0).to_string(), "0 seconds");
+    }
+
+    #[test]
+    fn interval_as_bytes_should_round_trip() {
+        let interval = Interval::build(-3, 12, 123_456_789);
+        let bytes: [u8; 16] = interval.as_bytes().try_into().unwrap();
+        assert_eq!(Interval::from_bytes(&bytes), interval);
+    }
+}
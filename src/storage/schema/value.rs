@@ -0,0 +1,536 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage::schema::_type::Type;
+use crate::storage::schema::binary;
+use crate::storage::schema::decimal::{Decimal, MAX_PRECISION};
+use crate::storage::schema::encoding::Encoding;
+use crate::storage::schema::error::Error;
+use crate::storage::schema::temporal;
+use crate::storage::schema::temporal::Interval;
+
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum Value {
+    Boolean(bool),
+    Tinyint(i8),
+    Smallint(i16),
+    Int(i32),
+    Bigint(i128),
+    Float(f64),
+    Decimal(Decimal),
+    Timestamp(i64),
+    TimestampTz(i64),
+    Date(i32),
+    Time(i64),
+    Interval(Interval),
+    String(String),
+    Binary(Vec<u8>),
+    Uuid([u8; 16]),
+    Enum(u8, String),
+    Array(Vec<Option<Value>>),
+    Struct(Vec<Option<Value>>),
+    Map(Vec<(Value, Option<Value>)>),
+}
+
+impl Value {
+    pub fn fit(&self, _type: &Type) -> Option<Value> {
+        match (self, _type) {
+            (Value::Boolean(_), Type::Boolean)
+            | (Value::Tinyint(_), Type::Tinyint)
+            | (Value::Smallint(_), Type::Smallint)
+            | (Value::Int(_), Type::Int)
+            | (Value::Bigint(_), Type::Bigint)
+            | (Value::Float(_), Type::Float)
+            | (Value::Timestamp(_), Type::Timestamp)
+            | (Value::TimestampTz(_), Type::TimestampTz)
+            | (Value::Date(_), Type::Date)
+            | (Value::Time(_), Type::Time)
+            | (Value::Interval(_), Type::Interval)
+            | (Value::String(_), Type::String)
+            | (Value::Binary(_), Type::Binary)
+            | (Value::Uuid(_), Type::Uuid) => Some(self.clone()),
+            (Value::Decimal(value), Type::Decimal { precision, scale })
+                if value.scale() <= *scale =>
+            {
+                let value = value
+                    .rescale(*scale)
+                    .filter(|value| value.precision() <= *precision)?;
+                Some(Value::Decimal(value))
+            }
+            (Value::Enum(code, label), Type::Enum(labels))
+                if labels.get(*code as usize) == Some(label) =>
+            {
+                Some(self.clone())
+            }
+            (Value::Array(values), Type::Array(element)) => Some(Value::Array(
+                values
+                    .iter()
+                    .map(|value| fit_nullable(value, element))
+                    .collect::<Option<Vec<Option<Value>>>>()?,
+            )),
+            (Value::Struct(values), Type::Struct(fields)) if values.len() == fields.len() => {
+                Some(Value::Struct(
+                    values
+                        .iter()
+                        .zip(fields)
+                        .map(|(value, field)| fit_nullable(value, field.get_type()))
+                        .collect::<Option<Vec<Option<Value>>>>()?,
+                ))
+            }
+            (Value::Map(entries), Type::Map(key_type, value_type)) => Some(Value::Map(
+                entries
+                    .iter()
+                    .map(|(key, value)| {
+                        Some((key.fit(key_type)?, fit_nullable(value, value_type)?))
+                    })
+                    .collect::<Option<Vec<(Value, Option<Value>)>>>()?,
+            )),
+            _ => None,
+        }
+    }
+
+    pub fn scalar_type(&self) -> Option<Type> {
+        Some(match self {
+            Value::Boolean(_) => Type::Boolean,
+            Value::Tinyint(_) => Type::Tinyint,
+            Value::Smallint(_) => Type::Smallint,
+            Value::Int(_) => Type::Int,
+            Value::Bigint(_) => Type::Bigint,
+            Value::Float(_) => Type::Float,
+            Value::Decimal(value) => Type::Decimal {
+                precision: MAX_PRECISION,
+                scale: value.scale(),
+            },
+            Value::Timestamp(_) => Type::Timestamp,
+            Value::TimestampTz(_) => Type::TimestampTz,
+            Value::Date(_) => Type::Date,
+            Value::Time(_) => Type::Time,
+            Value::Interval(_) => Type::Interval,
+            Value::String(_) => Type::String,
+            Value::Binary(_) => Type::Binary,
+            Value::Uuid(_) => Type::Uuid,
+            Value::Enum(_, _) | Value::Array(_) | Value::Struct(_) | Value::Map(_) => return None,
+        })
+    }
+
+    pub fn as_bytes(&self) -> Vec<u8> {
+        match self {
+            Value::Boolean(value) => vec![*value as u8],
+            Value::Tinyint(value) => value.to_le_bytes().to_vec(),
+            Value::Smallint(value) => value.to_le_bytes().to_vec(),
+            Value::Int(value) => value.to_le_bytes().to_vec(),
+            Value::Bigint(value) => value.to_le_bytes().to_vec(),
+            Value::Float(value) => value.to_le_bytes().to_vec(),
+            Value::Decimal(value) => value.value().to_le_bytes().to_vec(),
+            Value::Timestamp(value) => value.to_le_bytes().to_vec(),
+            Value::TimestampTz(value) => value.to_le_bytes().to_vec(),
+            Value::Date(value) => value.to_le_bytes().to_vec(),
+            Value::Time(value) => value.to_le_bytes().to_vec(),
+            Value::Interval(value) => value.as_bytes(),
+            Value::String(value) => {
+                let mut bytes = (value.len() as u32).to_le_bytes().to_vec();
+                bytes.extend_from_slice(value.as_bytes());
+                bytes
+            }
+            Value::Binary(value) => {
+                let mut bytes = (value.len() as u32).to_le_bytes().to_vec();
+                bytes.extend_from_slice(value);
+                bytes
+            }
+            Value::Uuid(value) => value.to_vec(),
+            Value::Enum(code, _) => vec![*code],
+            Value::Array(values) => {
+                let mut bytes = (values.len() as u32).to_le_bytes().to_vec();
+                values
+                    .iter()
+                    .for_each(|value| bytes.extend_from_slice(&nullable_as_bytes(value)));
+                bytes
+            }
+            Value::Struct(values) => values.iter().flat_map(nullable_as_bytes).collect(),
+            Value::Map(entries) => {
+                let mut bytes = (entries.len() as u32).to_le_bytes().to_vec();
+                entries.iter().for_each(|(key, value)| {
+                    bytes.extend_from_slice(&key.as_bytes());
+                    bytes.extend_from_slice(&nullable_as_bytes(value));
+                });
+                bytes
+            }
+        }
+    }
+
+    pub fn from_bytes(_type: &Type, bytes: &[u8]) -> Result<Value, Error> {
+        let size = _type
+            .get_value_size(bytes)
+            .ok_or(Error::InvalidValue(format!(
+                "Bytes {:?} can't be read as {:?}",
+                bytes, _type
+            )))?;
+        let bytes = &bytes[..size];
+        Ok(match _type {
+            Type::Boolean => Value::Boolean(bytes[0] != 0),
+            Type::Tinyint => Value::Tinyint(i8::from_le_bytes(bytes.try_into().unwrap())),
+            Type::Smallint => Value::Smallint(i16::from_le_bytes(bytes.try_into().unwrap())),
+            Type::Int => Value::Int(i32::from_le_bytes(bytes.try_into().unwrap())),
+            Type::Bigint => Value::Bigint(i128::from_le_bytes(bytes.try_into().unwrap())),
+            Type::Float => Value::Float(f64::from_le_bytes(bytes.try_into().unwrap())),
+            Type::Decimal { scale, .. } => Value::Decimal(Decimal::build(
+                i128::from_le_bytes(bytes.try_into().unwrap()),
+                *scale,
+            )?),
+            Type::Timestamp => Value::Timestamp(i64::from_le_bytes(bytes.try_into().unwrap())),
+            Type::TimestampTz => Value::TimestampTz(i64::from_le_bytes(bytes.try_into().unwrap())),
+            Type::Date => Value::Date(i32::from_le_bytes(bytes.try_into().unwrap())),
+            Type::Time => Value::Time(i64::from_le_bytes(bytes.try_into().unwrap())),
+            Type::Interval => Value::Interval(Interval::from_bytes(bytes.try_into().unwrap())),
+            Type::String => Value::String(String::from_utf8(bytes[4..].to_vec()).unwrap()),
+            Type::Binary => Value::Binary(bytes[4..].to_vec()),
+            Type::Uuid => Value::Uuid(bytes.try_into().unwrap()),
+            Type::Enum(labels) => Value::Enum(
+                bytes[0],
+                labels
+                    .get(bytes[0] as usize)
+                    .ok_or(Error::InvalidValue(format!(
+                        "Code {} has no label in {:?}",
+                        bytes[0], _type
+                    )))?
+                    .clone(),
+            ),
+            Type::Array(element) => {
+                let count = u32::from_le_bytes(bytes[..4].try_into().unwrap());
+                let mut offset = 4;
+                let mut values: Vec<Option<Value>> = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    let (value, size) = nullable_from_bytes(element, &bytes[offset..])?;
+                    values.push(value);
+                    offset += size;
+                }
+                Value::Array(values)
+            }
+            Type::Struct(fields) => {
+                let mut offset = 0;
+                let mut values: Vec<Option<Value>> = Vec::with_capacity(fields.len());
+                for field in fields {
+                    let (value, size) = nullable_from_bytes(field.get_type(), &bytes[offset..])?;
+                    values.push(value);
+                    offset += size;
+                }
+                Value::Struct(values)
+            }
+            Type::Map(key_type, value_type) => {
+                let count = u32::from_le_bytes(bytes[..4].try_into().unwrap());
+                let mut offset = 4;
+                let mut entries: Vec<(Value, Option<Value>)> = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    let key = Value::from_bytes(key_type, &bytes[offset..])?;
+                    offset += key_type.get_value_size(&bytes[offset..]).unwrap();
+                    let (value, size) = nullable_from_bytes(value_type, &bytes[offset..])?;
+                    entries.push((key, value));
+                    offset += size;
+                }
+                Value::Map(entries)
+            }
+        })
+    }
+
+    pub fn from_label(labels: &[String], label: &str) -> Result<Value, Error> {
+        match labels.iter().position(|other| other == label) {
+            Some(code) => Ok(Value::Enum(code as u8, label.to_string())),
+            None => Err(Error::InvalidValue(format!(
+                "Unknown label '{}', expected one of {}",
+                label,
+                labels
+                    .iter()
+                    .map(|label| format!("'{}'", label))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ))),
+        }
+    }
+
+    pub fn parse(_type: &Type, value_str: &str) -> Result<Value, Error> {
+        let error =
+            || Error::InvalidValue(format!("\"{}\" can't be parsed as {:?}", value_str, _type));
+        let trimmed = value_str.trim();
+        Ok(match _type {
+            Type::Boolean => match trimmed.to_lowercase().as_str() {
+                "true" => Value::Boolean(true),
+                "false" => Value::Boolean(false),
+                _ => return Err(error()),
+            },
+            Type::Tinyint => Value::Tinyint(trimmed.parse().map_err(|_| error())?),
+            Type::Smallint => Value::Smallint(trimmed.parse().map_err(|_| error())?),
+            Type::Int => Value::Int(trimmed.parse().map_err(|_| error())?),
+            Type::Bigint => Value::Bigint(trimmed.parse().map_err(|_| error())?),
+            Type::Float => Value::Float(trimmed.parse().map_err(|_| error())?),
+            Type::Decimal { .. } => Value::Decimal(Decimal::from_str(trimmed)?)
+                .fit(_type)
+                .ok_or_else(error)?,
+            Type::Timestamp => Value::Timestamp(temporal::parse_timestamp(trimmed)?),
+            Type::TimestampTz => Value::TimestampTz(temporal::parse_timestamptz(trimmed)?),
+            Type::Date => Value::Date(temporal::parse_date(trimmed)?),
+            Type::Time => Value::Time(temporal::parse_time(trimmed)?),
+            Type::Interval => Value::Interval(Interval::from_str(trimmed)?),
+            Type::String => Value::String(value_str.to_string()),
+            Type::Binary => Value::Binary(binary::decode_hex(trimmed)?),
+            Type::Uuid => Value::Uuid(binary::parse_uuid(trimmed)?),
+            Type::Enum(labels) => Value::from_label(labels, value_str)?,
+            Type::Array(_) | Type::Struct(_) | Type::Map(_, _) => return Err(error()),
+        })
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Boolean(value) => write!(f, "{}", value),
+            Value::Tinyint(value) => write!(f, "{}", value),
+            Value::Smallint(value) => write!(f, "{}", value),
+            Value::Int(value) => write!(f, "{}", value),
+            Value::Bigint(value) => write!(f, "{}", value),
+            Value::Float(value) => write!(f, "{}", value),
+            Value::Decimal(value) => write!(f, "{}", value),
+            Value::Timestamp(value) => write!(f, "{}", temporal::format_timestamp(*value)),
+            Value::TimestampTz(value) => write!(f, "{}", temporal::format_timestamptz(*value)),
+            Value::Date(value) => write!(f, "{}", temporal::format_date(*value)),
+            Value::Time(value) => write!(f, "{}", temporal::format_time(*value)),
+            Value::Interval(value) => write!(f, "{}", value),
+            Value::String(value) => write!(f, "{}", value),
+            Value::Binary(value) => write!(f, "{}", binary::encode_hex(value)),
+            Value::Uuid(value) => write!(f, "{}", binary::format_uuid(value)),
+            Value::Enum(_, label) => write!(f, "{}", label),
+            Value::Array(values) => write!(
+                f,
+                "[{}]",
+                values
+                    .iter()
+                    .map(nullable_to_string)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Value::Struct(values) => write!(
+                f,
+                "{{{}}}",
+                values
+                    .iter()
+                    .map(nullable_to_string)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Value::Map(entries) => write!(
+                f,
+                "{{{}}}",
+                entries
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, nullable_to_string(value)))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+fn fit_nullable(value: &Option<Value>, _type: &Type) -> Option<Option<Value>> {
+    match value {
+        None => Some(None),
+        Some(value) => Some(Some(value.fit(_type)?)),
+    }
+}
+
+fn nullable_as_bytes(value: &Option<Value>) -> Vec<u8> {
+    match value {
+        None => vec![1],
+        Some(value) => {
+            let mut bytes = vec![0];
+            bytes.extend_from_slice(&value.as_bytes());
+            bytes
+        }
+    }
+}
+
+fn nullable_from_bytes(_type: &Type, bytes: &[u8]) -> Result<(Option<Value>, usize), Error> {
+    let size = _type
+        .get_nullable_size(bytes)
+        .ok_or(Error::InvalidValue(format!(
+            "Bytes {:?} can't be read as nullable {:?}",
+            bytes, _type
+        )))?;
+    match bytes[0] {
+        0 => Ok((Some(Value::from_bytes(_type, &bytes[1..])?), size)),
+        _ => Ok((None, size)),
+    }
+}
+
+fn nullable_to_string(value: &Option<Value>) -> String {
+    match value {
+        None => "NULL".to_string(),
+        Some(value) => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::schema::field::Field;
+
+    use super::*;
+
+    #[test]
+    fn as_bytes_should_encode_little_endian() {
+        assert_eq!(Value::Boolean(true).as_bytes(), vec![1]);
+        assert_eq!(Value::Smallint(-2).as_bytes(), vec![254, 255]);
+        assert_eq!(Value::Int(258).as_bytes(), vec![2, 1, 0, 0]);
+        assert_eq!(Value::Bigint(1).as_bytes().len(), 16);
+        assert_eq!(
+            Value::String("abc".to_string()).as_bytes(),
+            vec![3, 0, 0, 0, 97, 98, 99]
+        );
+    }
+
+    #[test]
+    fn from_bytes_should_decode_value() {
+        let values = vec![
+            (Type::Boolean, Value::Boolean(false)),
+            (Type::Tinyint, Value::Tinyint(-7)),
+            (Type::Smallint, Value::Smallint(1024)),
+            (Type::Int, Value::Int(-70000)),
+            (Type::Bigint, Value::Bigint(i128::MAX)),
+            (Type::Float, Value::Float(3.25)),
+            (
+                Type::Decimal {
+                    precision: 6,
+                    scale: 2,
+                },
+                Value::Decimal(Decimal::from_str("-1234.56").unwrap()),
+            ),
+            (Type::Timestamp, Value::Timestamp(1718000000000)),
+            (Type::TimestampTz, Value::TimestampTz(-1)),
+            (Type::Date, Value::Date(19884)),
+            (Type::Time, Value::Time(3_600_000_000)),
+            (Type::Interval, Value::Interval(Interval::build(1, -2, 3))),
+            (Type::String, Value::String("été".to_string())),
+            (Type::Binary, Value::Binary(vec![0, 255, 7])),
+            (Type::Uuid, Value::Uuid([9; 16])),
+            (
+                Type::Array(Box::new(Type::String)),
+                Value::Array(vec![Some(Value::String("a".to_string())), None]),
+            ),
+            (
+                Type::Struct(vec![
+                    Field::build("id".to_string(), Type::Int),
+                    Field::build("tags".to_string(), Type::Array(Box::new(Type::Int))),
+                ]),
+                Value::Struct(vec![None, Some(Value::Array(vec![Some(Value::Int(4))]))]),
+            ),
+            (
+                Type::Map(Box::new(Type::String), Box::new(Type::Float)),
+                Value::Map(vec![
+                    (Value::String("x".to_string()), Some(Value::Float(1.5))),
+                    (Value::String("y".to_string()), None),
+                ]),
+            ),
+        ];
+        for (_type, value) in values {
+            assert_eq!(Value::from_bytes(&_type, &value.as_bytes()).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn fit_should_rescale_decimal() {
+        let _type = Type::Decimal {
+            precision: 5,
+            scale: 2,
+        };
+        let value = Value::Decimal(Decimal::from_str("12.5").unwrap())
+            .fit(&_type)
+            .unwrap();
+        assert_eq!(value.as_bytes(), 1250_i128.to_le_bytes().to_vec());
+        assert!(Value::Decimal(Decimal::from_str("1234.5").unwrap())
+            .fit(&_type)
+            .is_none());
+        assert!(Value::Int(1).fit(&_type).is_none());
+    }
+
+    #[test]
+    fn fit_should_check_nested_types() {
+        let _type = Type::Array(Box::new(Type::Decimal {
+            precision: 4,
+            scale: 2,
+        }));
+        let value = Value::Array(vec![
+            None,
+            Some(Value::Decimal(Decimal::from_str("1.5").unwrap())),
+        ]);
+        assert_eq!(
+            value.fit(&_type).unwrap().as_bytes()[6..],
+            150_i128.to_le_bytes()
+        );
+        assert!(Value::Array(vec![Some(Value::Int(1))])
+            .fit(&_type)
+            .is_none());
+        let _struct = Type::Struct(vec![Field::build("a".to_string(), Type::Int)]);
+        assert!(Value::Struct(vec![None, None]).fit(&_struct).is_none());
+    }
+
+    #[test]
+    fn to_string_should_render_nested_values() {
+        let value = Value::Map(vec![(
+            Value::String("k".to_string()),
+            Some(Value::Array(vec![Some(Value::Int(1)), None])),
+        )]);
+        assert_eq!(value.to_string(), "{k: [1, NULL]}");
+    }
+
+    #[test]
+    fn parse_should_round_trip_with_to_string() {
+        let values = vec![
+            (Type::Boolean, "true"),
+            (Type::Int, "-42"),
+            (Type::Float, "2.5"),
+            (
+                Type::Decimal {
+                    precision: 5,
+                    scale: 2,
+                },
+                "-12.30",
+            ),
+            (Type::Timestamp, "2024-06-10 12:30:00"),
+            (Type::TimestampTz, "2024-06-10 12:30:00+00:00"),
+            (Type::Date, "2024-06-10"),
+            (Type::Time, "08:15:00.250"),
+            (Type::Interval, "1 month 2 days"),
+            (Type::String, " text "),
+            (Type::Binary, "00ff10"),
+            (Type::Uuid, "123e4567-e89b-12d3-a456-426614174000"),
+        ];
+        for (_type, value_str) in values {
+            assert_eq!(
+                Value::parse(&_type, value_str).unwrap().to_string(),
+                value_str
+            );
+        }
+    }
+
+    #[test]
+    fn parse_should_decode_temporal_units() {
+        assert_eq!(
+            Value::parse(&Type::Date, "1970-01-11").unwrap(),
+            Value::Date(10)
+        );
+        assert_eq!(
+            Value::parse(&Type::Timestamp, "1970-01-01T00:00:00.000001").unwrap(),
+            Value::Timestamp(1)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn parse_should_panic_if_invalid_literal() {
+        Value::parse(&Type::Smallint, "70000").unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_bytes_should_panic_if_bytes_too_short() {
+        Value::from_bytes(&Type::Float, &[0; 4]).unwrap();
+    }
+}
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::rc::Rc;
 
+use crate::storage::schema::key::Key;
 use crate::storage::tablespace::database::Database;
 use crate::storage::tablespace::encoding::Encoding;
 use crate::storage::tablespace::error::Error;
@@ -16,13 +17,21 @@ pub struct Catalog {
 #[derive(Debug, Clone, PartialEq)]
 pub struct CatalogTable {
     pub database: Rc<Database>,
-    pub table: Table, // todo index, droits, keys...
+    pub table: Table, // todo index, droits...
 }
 
 impl CatalogTable {
     pub fn build(database: Rc<Database>, table: Table) -> CatalogTable {
         CatalogTable { database, table }
     }
+
+    pub fn primary_key(&self) -> Option<&Key> {
+        self.table.schema.primary_key()
+    }
+
+    pub fn unique_keys(&self) -> Vec<&Key> {
+        self.table.schema.unique_keys()
+    }
 }
 
 impl Catalog {
@@ -118,4 +127,18 @@ mod tests {
         assert_eq!(catalog.tables.len(), 4);
         delete_test_env(TEST_PATH, "build");
     }
+
+    #[test]
+    fn catalog_table_should_expose_keys() {
+        let path = init_test_env(TEST_PATH, "keys");
+        let schema = Schema::from_str("id BIGINT, email STRING UNIQUE, PRIMARY KEY (id)").unwrap();
+        let mut metastore = Metastore::build(path.to_str().unwrap()).unwrap();
+        let mut database = metastore.new_database("database_01", None).unwrap();
+        database.new_table("table_010", None, &schema).unwrap();
+        let catalog: Catalog = Catalog::build(path.to_str().unwrap()).unwrap();
+        let catalog_table = catalog.tables.get("database_01.table_010").unwrap();
+        assert_eq!(catalog_table.primary_key().unwrap().columns, vec!["id"]);
+        assert_eq!(catalog_table.unique_keys()[0].columns, vec!["email"]);
+        delete_test_env(TEST_PATH, "keys");
+    }
 }
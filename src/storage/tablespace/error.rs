@@ -1,10 +1,13 @@
 use std::fmt;
 
+use crate::storage::schema;
+
 pub enum Error {
     SerdeJson(serde_json::Error),
     FileError(std::io::Error),
     ObjectExists(String, String),
     ObjectNotFound(String, String),
+    Schema(schema::error::Error),
 }
 
 impl fmt::Display for Error {
@@ -18,6 +21,7 @@ impl fmt::Display for Error {
             Error::ObjectNotFound(ref object, ref name) => {
                 write!(f, "{} {} not found.", object, name)
             }
+            Error::Schema(ref err) => write!(f, "Schema error: {}.", err),
         }
     }
 }
@@ -42,6 +46,12 @@ impl From<std::io::Error> for Error {
     }
 }
 
+impl From<schema::error::Error> for Error {
+    fn from(value: schema::error::Error) -> Self {
+        Error::Schema(value)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -5,6 +5,8 @@ use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 use serde_json::from_str;
 
+use crate::storage::schema::alteration::Alteration;
+use crate::storage::schema::value::Value;
 use crate::storage::schema::Schema;
 use crate::storage::tablespace::encoding::Encoding;
 use crate::storage::tablespace::error::Error;
@@ -18,12 +20,22 @@ pub struct Table {
     pub name: String,
     pub schema: Schema,
     pub location: PathBuf,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<SchemaVersion>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub file_versions: HashMap<String, usize>,
     #[serde(skip)]
     pub file_paths: HashMap<String, PathBuf>,
     #[serde(skip)]
     pub meta: Meta,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaVersion {
+    pub schema: Schema,
+    pub alteration: Alteration,
+}
+
 impl Table {
     pub fn build(name: &str, location: &str, schema: &Schema) -> Result<Table, Error> {
         fs::create_dir_all(location)?;
@@ -32,6 +44,8 @@ impl Table {
             name: name.to_string(),
             schema: schema.clone(),
             location: location.clone(),
+            history: Vec::new(),
+            file_versions: HashMap::new(),
             file_paths: HashMap::new(),
             meta: Meta::build(location.join(META_FOLDER))?,
         };
@@ -44,6 +58,67 @@ impl Table {
         Ok(())
     }
 
+    pub fn version(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn schema_at(&self, version: usize) -> Option<&Schema> {
+        match self.history.get(version) {
+            Some(schema_version) => Some(&schema_version.schema),
+            None if version == self.version() => Some(&self.schema),
+            None => None,
+        }
+    }
+
+    pub fn file_version(&self, name: &str) -> usize {
+        *self.file_versions.get(name).unwrap_or(&0)
+    }
+
+    pub fn file_schema(&self, name: &str) -> Option<&Schema> {
+        self.schema_at(self.file_version(name))
+    }
+
+    pub fn upgrade(
+        &self,
+        version: usize,
+        values: Vec<Option<Value>>,
+    ) -> Result<Vec<Option<Value>>, Error> {
+        let history = self.history.get(version..).ok_or(Error::ObjectNotFound(
+            "Schema version".to_string(),
+            version.to_string(),
+        ))?;
+        history.iter().try_fold(values, |values, schema_version| {
+            Ok(schema_version
+                .alteration
+                .migrate(&schema_version.schema, values)?)
+        })
+    }
+
+    pub fn add_column(&mut self, definition: &str) -> Result<(), Error> {
+        self.alter(Alteration::add_column(definition)?)
+    }
+
+    pub fn drop_column(&mut self, name: &str) -> Result<(), Error> {
+        self.alter(Alteration::drop_column(name))
+    }
+
+    pub fn rename_column(&mut self, from: &str, to: &str) -> Result<(), Error> {
+        self.alter(Alteration::rename_column(from, to))
+    }
+
+    pub fn widen_column(&mut self, name: &str, type_str: &str) -> Result<(), Error> {
+        self.alter(Alteration::widen_column(name, type_str)?)
+    }
+
+    fn alter(&mut self, alteration: Alteration) -> Result<(), Error> {
+        let schema = alteration.apply(&self.schema)?;
+        self.history.push(SchemaVersion {
+            schema: std::mem::replace(&mut self.schema, schema),
+            alteration,
+        });
+        self.save()
+    }
+
     pub fn load_file_paths(&mut self) -> Result<(), Error> {
         for entry in fs::read_dir(&self.location)? {
             let path = entry?.path();
@@ -63,6 +138,10 @@ impl Table {
         fs::File::create(&file_path)?;
         self.file_paths
             .insert(file_name.to_string(), file_path.clone());
+        if self.version() > 0 {
+            self.file_versions
+                .insert(file_name.to_string(), self.version());
+        }
         self.save()?;
         Ok((file_name, file_path))
     }
@@ -74,6 +153,7 @@ impl Table {
                 .ok_or(Error::ObjectNotFound("File".to_string(), name.to_string()))?,
         )?;
         self.file_paths.remove(name);
+        self.file_versions.remove(name);
         self.save()?;
         Ok(())
     }
@@ -152,6 +232,112 @@ mod tests {
         delete_test_env(TEST_PATH, "from_file");
     }
 
+    #[test]
+    fn from_file_should_keep_column_constraints() {
+        let path = init_test_env(TEST_PATH, "from_file_constraints");
+        let schema = Schema::from_str(
+            "id BIGINT NOT NULL, cost DECIMAL(8, 2) DEFAULT 9.99, day DATE DEFAULT '2024-06-10'",
+        )
+        .unwrap();
+        let table = Table::build("test", path.to_str().unwrap(), &schema).unwrap();
+        assert!(table.as_json().unwrap().contains("\"not_null\":true"));
+        assert_eq!(Table::from_file(&path).unwrap().schema, schema);
+        delete_test_env(TEST_PATH, "from_file_constraints");
+    }
+
+    #[test]
+    fn from_file_should_keep_keys() {
+        let path = init_test_env(TEST_PATH, "from_file_keys");
+        let schema =
+            Schema::from_str("id BIGINT PRIMARY KEY, a INT, b INT, UNIQUE (a, b)").unwrap();
+        let table = Table::build("test", path.to_str().unwrap(), &schema).unwrap();
+        assert!(table
+            .as_json()
+            .unwrap()
+            .contains("\"keys\":[{\"kind\":\"Primary\",\"columns\":[\"id\"]}"));
+        assert_eq!(Table::from_file(&path).unwrap().schema, schema);
+        delete_test_env(TEST_PATH, "from_file_keys");
+    }
+
+    #[test]
+    fn from_file_should_keep_checks() {
+        let path = init_test_env(TEST_PATH, "from_file_checks");
+        let schema =
+            Schema::from_str("cost FLOAT, CONSTRAINT positive_cost CHECK (cost >= 0)").unwrap();
+        let table = Table::build("test", path.to_str().unwrap(), &schema).unwrap();
+        assert!(table
+            .as_json()
+            .unwrap()
+            .contains("\"checks\":[{\"name\":\"positive_cost\",\"expression\":\"cost >= 0\"}]"));
+        assert_eq!(Table::from_file(&path).unwrap().schema, schema);
+        delete_test_env(TEST_PATH, "from_file_checks");
+    }
+
+    #[test]
+    fn from_file_should_keep_generated_columns() {
+        let path = init_test_env(TEST_PATH, "from_file_generated");
+        let schema = Schema::from_str(
+            "cost FLOAT, qty INT, total FLOAT GENERATED ALWAYS AS (cost * qty) STORED",
+        )
+        .unwrap();
+        let table = Table::build("test", path.to_str().unwrap(), &schema).unwrap();
+        assert!(table
+            .as_json()
+            .unwrap()
+            .contains("\"generated\":{\"kind\":\"Stored\",\"expression\":\"cost * qty\"}"));
+        assert_eq!(Table::from_file(&path).unwrap().schema, schema);
+        delete_test_env(TEST_PATH, "from_file_generated");
+    }
+
+    #[test]
+    fn alter_should_keep_schema_history() {
+        let path = init_test_env(TEST_PATH, "alter");
+        let schema = Schema::from_str("id INT, qty SMALLINT, label STRING").unwrap();
+        let mut table = Table::build("test", path.to_str().unwrap(), &schema).unwrap();
+        let (old_file, _) = table.new_file().unwrap();
+        table.add_column("day DATE DEFAULT '2024-06-10'").unwrap();
+        table.drop_column("label").unwrap();
+        table.rename_column("qty", "quantity").unwrap();
+        table.widen_column("quantity", "INT").unwrap();
+        let (new_file, _) = table.new_file().unwrap();
+        let table = Table::from_file(&path).unwrap();
+        assert_eq!(table.version(), 4);
+        assert_eq!(table.file_schema(&old_file), Some(&schema));
+        assert_eq!(
+            table.file_schema(&new_file),
+            Some(&Schema::from_str("id INT, quantity INT, day DATE DEFAULT '2024-06-10'").unwrap())
+        );
+        assert_eq!(
+            table
+                .upgrade(
+                    0,
+                    vec![
+                        Some(Value::Int(1)),
+                        Some(Value::Smallint(5)),
+                        Some(Value::String("a".to_string()))
+                    ]
+                )
+                .unwrap(),
+            vec![
+                Some(Value::Int(1)),
+                Some(Value::Int(5)),
+                Some(Value::Date(19884))
+            ]
+        );
+        delete_test_env(TEST_PATH, "alter");
+    }
+
+    #[test]
+    fn alter_should_not_save_invalid_change() {
+        let path = init_test_env(TEST_PATH, "alter_invalid");
+        let schema = Schema::from_str("id INT").unwrap();
+        let mut table = Table::build("test", path.to_str().unwrap(), &schema).unwrap();
+        assert!(table.drop_column("id").is_err());
+        assert!(table.widen_column("id", "SMALLINT").is_err());
+        assert_eq!(Table::from_file(&path).unwrap().version(), 0);
+        delete_test_env(TEST_PATH, "alter_invalid");
+    }
+
     #[test]
     fn new_file_should_create_empty_new_file() {
         let path = init_test_env(TEST_PATH, "new_file");